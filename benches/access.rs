@@ -10,8 +10,8 @@ use bort::{
         heap::{Heap, Slot},
         token::{is_main_thread, MainThreadToken},
     },
-    debug::{alive_entity_count, force_reset_database},
-    flush, query, storage, Entity, Obj, OwnedEntity, OwnedObj, Tag, VecEventList,
+    debug::{alive_entity_count, force_reset_database, heap_count},
+    flush, query, storage, Entity, Obj, OwnedEntity, OwnedObj, Storage, Tag, VecEventList,
 };
 use criterion::{criterion_main, Criterion};
 
@@ -73,6 +73,35 @@ fn access_tests() {
         assert_eq!(alive_entity_count(), 0);
     });
 
+    c.bench_function("spawn.bulk.unreserved", |c| {
+        let pos = storage::<Position>();
+
+        c.iter(|| {
+            for _ in 0..1000 {
+                pos.insert(Entity::new_unmanaged(), Position(0.0));
+            }
+        });
+        force_reset_database();
+        assert_eq!(alive_entity_count(), 0);
+    });
+
+    c.bench_function("spawn.bulk.reserved", |c| {
+        let pos = storage::<Position>();
+
+        c.iter(|| {
+            pos.reserve(1000);
+            let reserved_heaps = heap_count();
+
+            for _ in 0..1000 {
+                pos.insert(Entity::new_unmanaged(), Position(0.0));
+            }
+
+            assert_eq!(heap_count(), reserved_heaps);
+        });
+        force_reset_database();
+        assert_eq!(alive_entity_count(), 0);
+    });
+
     c.bench_function("get.entity.normal.ref", |c| {
         let _pop = spawn_anon_pos_pop();
         let obj = OwnedEntity::new().with(Position(1.0));
@@ -103,6 +132,21 @@ fn access_tests() {
         c.iter(|| storage.get_mut(obj.entity()));
     });
 
+    c.bench_function("has.entity.normal", |c| {
+        let _pop = spawn_anon_pos_pop();
+        let obj = OwnedEntity::new().with(Position(1.0));
+
+        c.iter(|| obj.has::<Position>());
+    });
+
+    c.bench_function("has.entity.storage", |c| {
+        let _pop = spawn_anon_pos_pop();
+        let obj = OwnedEntity::new().with(Position(1.0));
+        let storage = storage::<Position>();
+
+        c.iter(|| storage.has(obj.entity()));
+    });
+
     c.bench_function("get.obj.normal.ref", |c| {
         let _pop = spawn_anon_pos_pop();
         let obj = OwnedObj::new(Position(1.0));
@@ -259,6 +303,66 @@ fn access_tests() {
         flush();
     });
 
+    c.bench_function("query.normal.one_component.for_each_mut", |c| {
+        let pos_tag = Tag::new();
+        let vel_tag = Tag::new();
+        let entities = spawn_tagged_pos_vel_pop(pos_tag, vel_tag);
+        flush();
+
+        let pos = storage::<Position>();
+
+        c.iter(|| {
+            pos.for_each_mut(|_, pos| {
+                pos.0 += 1.0;
+            });
+        });
+
+        drop(entities);
+        flush();
+    });
+
+    c.bench_function("query.normal.sum.query", |c| {
+        let pos_tag = Tag::new();
+        let vel_tag = Tag::new();
+        let entities = spawn_tagged_pos_vel_pop(pos_tag, vel_tag);
+        flush();
+
+        c.iter(|| {
+            let mut sum = 0.0;
+            query! {
+                for (ref pos in pos_tag) {
+                    sum += pos.0 as f64;
+                }
+            }
+            sum
+        });
+
+        drop(entities);
+        flush();
+    });
+
+    c.bench_function("query.normal.sum.column_chunks", |c| {
+        let pos_tag = Tag::new();
+        let vel_tag = Tag::new();
+        let entities = spawn_tagged_pos_vel_pop(pos_tag, vel_tag);
+        flush();
+
+        let pos = storage::<Position>();
+
+        let mut expected = 0.0;
+        query! {
+            for (ref pos in pos_tag) {
+                expected += pos.0 as f64;
+            }
+        }
+        assert_eq!(sum_column_chunks(pos), expected);
+
+        c.iter(|| sum_column_chunks(pos));
+
+        drop(entities);
+        flush();
+    });
+
     c.bench_function("query.normal.no_entity.normal", |c| {
         let pos_tag = Tag::new();
         let vel_tag = Tag::new();
@@ -729,6 +833,14 @@ fn spawn_tagged_pos_vel_pop(pos_tag: Tag<Position>, vel_tag: Tag<Velocity>) -> V
         .collect()
 }
 
+fn sum_column_chunks(storage: Storage<Position>) -> f64 {
+    let mut sum = 0.0;
+    for chunk in storage.column_chunks().iter() {
+        sum += chunk.iter().map(|pos| pos.0 as f64).sum::<f64>();
+    }
+    sum
+}
+
 fn generate_permuted_chain(n: usize) -> (usize, Vec<usize>) {
     fastrand::seed(4);
 
@@ -0,0 +1,88 @@
+use bort::{
+    debug::alive_entity_count,
+    prelude::*,
+    snapshot::{
+        register_snapshot_component, register_snapshot_tag, restore, snapshot, LoadContext,
+        SaveContext, SnapshotComponent, WorldSnapshot,
+    },
+};
+use serde_json::{json, Value};
+
+#[derive(Debug, PartialEq)]
+struct Health(u32);
+
+impl SnapshotComponent for Health {
+    fn save(&self, _cx: &SaveContext) -> Value {
+        json!(self.0)
+    }
+
+    fn load(value: &Value, _cx: &LoadContext) -> Option<Self> {
+        Some(Self(value.as_u64()? as u32))
+    }
+}
+
+struct Target(Entity);
+
+impl SnapshotComponent for Target {
+    fn save(&self, cx: &SaveContext) -> Value {
+        cx.entity(self.0)
+    }
+
+    fn load(value: &Value, cx: &LoadContext) -> Option<Self> {
+        cx.entity(value).map(Self)
+    }
+}
+
+struct Watching(Obj<Health>);
+
+impl SnapshotComponent for Watching {
+    fn save(&self, cx: &SaveContext) -> Value {
+        cx.obj(self.0)
+    }
+
+    fn load(value: &Value, cx: &LoadContext) -> Option<Self> {
+        cx.obj(value).map(Self)
+    }
+}
+
+fn main() {
+    register_snapshot_component::<Health>("health");
+    register_snapshot_component::<Target>("target");
+    register_snapshot_component::<Watching>("watching");
+
+    let hostile = VirtualTag::new();
+    register_snapshot_tag("hostile", hostile);
+
+    // Build a small world whose components refer to one another.
+    let player = OwnedEntity::new().with(Health(100));
+    let enemy = OwnedEntity::new()
+        .with(Health(20))
+        .with(Target(player.entity()))
+        .with(Watching(player.obj()))
+        .with_tag(hostile);
+
+    let player_id = player.entity().id();
+    let enemy_id = enemy.entity().id();
+
+    // Round-trip the world through bytes.
+    let bytes = snapshot().to_bytes();
+    drop((player, enemy));
+    bort::flush();
+    assert_eq!(alive_entity_count(), 0);
+
+    let world = restore(&WorldSnapshot::from_bytes(&bytes).unwrap()).unwrap();
+    bort::flush();
+
+    let player = world.entity(player_id).unwrap();
+    let enemy = world.entity(enemy_id).unwrap();
+
+    assert_eq!(*player.get::<Health>(), Health(100));
+    assert_eq!(*enemy.get::<Health>(), Health(20));
+    assert_eq!(enemy.get::<Target>().0, player);
+    assert_eq!(enemy.get::<Watching>().0.entity(), player);
+    assert_eq!(*enemy.get::<Watching>().0.get(), Health(100));
+    assert!(enemy.is_tagged_virtual(hostile));
+    assert!(!player.is_tagged_virtual(hostile));
+
+    println!("{:#}", snapshot().as_json());
+}
@@ -1,8 +1,12 @@
 use std::{
     any::{Any, TypeId},
     fmt, hash,
-    ops::{Deref, DerefMut},
-    sync::OnceLock,
+    num::NonZeroU64,
+    ops::{ControlFlow, Deref, DerefMut},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering::Relaxed},
+        Arc, OnceLock,
+    },
 };
 
 use derive_where::derive_where;
@@ -476,10 +480,12 @@ impl BehaviorRegistry {
         bhv
     }
 
-    pub fn register_cx<B: Behavior, M>(&mut self, meta: M, delegate: B) -> &mut Self
+    pub fn register_cx<B: Behavior, M>(&mut self, meta: M, delegate: B) -> BehaviorHandle
     where
         B::List: ExtendableBehaviorList<M>,
     {
+        self.remove_spent();
+
         let own_registry = self
             .behaviors
             .entry(NamedTypeId::of::<B>())
@@ -488,24 +494,79 @@ impl BehaviorRegistry {
             .downcast_mut::<B::List>()
             .unwrap();
 
-        own_registry.push_cx(delegate, meta);
-
-        self
+        BehaviorHandle {
+            behavior: NamedTypeId::of::<B>(),
+            id: own_registry.push_cx(delegate, meta),
+        }
     }
 
-    pub fn register<B: Behavior>(&mut self, delegate: B) -> &mut Self
+    pub fn register<B: Behavior>(&mut self, delegate: B) -> BehaviorHandle
     where
         B::List: ExtendableBehaviorList,
     {
         self.register_cx((), delegate)
     }
 
+    pub fn register_prioritized<B: Behavior>(
+        &mut self,
+        priority: i32,
+        delegate: B,
+    ) -> BehaviorHandle
+    where
+        B::List: ExtendableBehaviorList<Prioritized>,
+    {
+        self.register_cx(Prioritized(priority, ()), delegate)
+    }
+
+    pub fn register_if<B: Behavior>(
+        &mut self,
+        cond: impl 'static + Send + Sync + Fn() -> bool,
+        delegate: B,
+    ) -> BehaviorHandle
+    where
+        B::List: ExtendableBehaviorList<Conditional>,
+    {
+        self.register_cx(Conditional(BehaviorCondition::new(cond), ()), delegate)
+    }
+
+    /// Registers a behavior which only runs on the first dispatch of its list. Once it has run, it
+    /// is skipped by later dispatches, is not copied by [`register_from`](Self::register_from), and
+    /// is removed from its list the next time this registry is modified. Clones of this registry
+    /// share the behavior's "has run" state.
+    pub fn register_once<B: Behavior>(&mut self, delegate: B) -> BehaviorHandle
+    where
+        B::List: ExtendableBehaviorList<RunOnce>,
+    {
+        self.register_cx(RunOnce, delegate)
+    }
+
+    /// Removes the behavior registered under `handle`, returning whether it was still registered.
+    /// Behaviors copied in by [`register_from`](Self::register_from) keep the handle they were
+    /// originally registered under.
+    pub fn unregister(&mut self, handle: BehaviorHandle) -> bool {
+        self.remove_spent();
+
+        self.behaviors
+            .get_mut(&handle.behavior)
+            .is_some_and(|list| list.remove_dyn(handle.id))
+    }
+
+    /// Removes every behavior registered with [`register_once`](Self::register_once) which has
+    /// already run.
+    pub fn remove_spent(&mut self) {
+        for list in self.behaviors.values_mut() {
+            list.remove_spent_dyn();
+        }
+    }
+
     pub fn register_many(&mut self, registrar: impl FnOnce(&mut Self)) -> &mut Self {
         registrar(self);
         self
     }
 
     pub fn register_from(&mut self, registry: &BehaviorRegistry) {
+        self.remove_spent();
+
         for (key, list) in self.behaviors.iter_mut() {
             if let Some(other) = registry.behaviors.get(key) {
                 list.extend_dyn(&**other);
@@ -528,6 +589,25 @@ impl BehaviorRegistry {
         self.with_cx((), delegate)
     }
 
+    pub fn with_prioritized<B: Behavior>(self, priority: i32, delegate: B) -> Self
+    where
+        B::List: ExtendableBehaviorList<Prioritized>,
+    {
+        self.with_cx(Prioritized(priority, ()), delegate)
+    }
+
+    pub fn with_if<B: Behavior>(
+        mut self,
+        cond: impl 'static + Send + Sync + Fn() -> bool,
+        delegate: B,
+    ) -> Self
+    where
+        B::List: ExtendableBehaviorList<Conditional>,
+    {
+        self.register_if(cond, delegate);
+        self
+    }
+
     pub fn with_many(mut self, registrar: impl FnOnce(&mut Self)) -> Self {
         self.register_many(registrar);
         self
@@ -604,6 +684,10 @@ pub trait DynBehaviorList: 'static + Send + Sync + fmt::Debug {
     fn clone_box(&self) -> Box<dyn DynBehaviorList>;
 
     fn extend_dyn(&mut self, other: &dyn DynBehaviorList);
+
+    fn remove_dyn(&mut self, id: BehaviorId) -> bool;
+
+    fn remove_spent_dyn(&mut self);
 }
 
 impl<T: BehaviorList> DynBehaviorList for T {
@@ -622,6 +706,14 @@ impl<T: BehaviorList> DynBehaviorList for T {
     fn extend_dyn(&mut self, other: &dyn DynBehaviorList) {
         self.extend_ref(other.as_any().downcast_ref().unwrap())
     }
+
+    fn remove_dyn(&mut self, id: BehaviorId) -> bool {
+        self.remove(id)
+    }
+
+    fn remove_spent_dyn(&mut self) {
+        self.remove_spent()
+    }
 }
 
 pub trait BehaviorList: BehaviorSafe + Default + fmt::Debug {
@@ -637,12 +729,20 @@ pub trait BehaviorList: BehaviorSafe + Default + fmt::Debug {
     fn view(&self) -> Self::View<'_> {
         Self::opt_view(Some(self))
     }
+
+    /// Removes the behavior which was assigned `id` when it was pushed, returning whether it was
+    /// found.
+    fn remove(&mut self, id: BehaviorId) -> bool;
+
+    /// Removes one-shot behaviors which have already run. Lists without one-shot behaviors don't
+    /// need to override this.
+    fn remove_spent(&mut self) {}
 }
 
 pub trait ExtendableBehaviorList<M = ()>: BehaviorList {
-    fn push_cx(&mut self, delegate: Self::Delegate, meta: M);
+    fn push_cx(&mut self, delegate: Self::Delegate, meta: M) -> BehaviorId;
 
-    fn push(&mut self, delegate: Self::Delegate)
+    fn push(&mut self, delegate: Self::Delegate) -> BehaviorId
     where
         IsUnit<M>: Truthy<Unit = M>,
     {
@@ -650,6 +750,64 @@ pub trait ExtendableBehaviorList<M = ()>: BehaviorList {
     }
 }
 
+/// A process-unique identifier assigned to every behavior pushed into a [`BehaviorList`].
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub struct BehaviorId(NonZeroU64);
+
+impl BehaviorId {
+    pub fn allocate() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+        Self(NonZeroU64::new(NEXT_ID.fetch_add(1, Relaxed)).unwrap())
+    }
+}
+
+/// A token identifying a single registration in a [`BehaviorRegistry`]. It is returned by the
+/// `register` family of methods and can be passed to [`BehaviorRegistry::unregister`].
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub struct BehaviorHandle {
+    behavior: NamedTypeId,
+    id: BehaviorId,
+}
+
+impl BehaviorHandle {
+    pub fn id(self) -> BehaviorId {
+        self.id
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Prioritized<M = ()>(pub i32, pub M);
+
+/// Registration metadata for a behavior which is skipped during dispatch whenever its condition
+/// evaluates to `false`. The condition is re-evaluated on every dispatch.
+#[derive(Debug, Clone)]
+pub struct Conditional<M = ()>(pub BehaviorCondition, pub M);
+
+#[derive(Clone)]
+pub struct BehaviorCondition(Arc<dyn Fn() -> bool + Send + Sync>);
+
+impl fmt::Debug for BehaviorCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BehaviorCondition").finish_non_exhaustive()
+    }
+}
+
+impl BehaviorCondition {
+    pub fn new(cond: impl 'static + Send + Sync + Fn() -> bool) -> Self {
+        Self(Arc::new(cond))
+    }
+
+    pub fn holds(&self) -> bool {
+        (self.0)()
+    }
+}
+
+/// Registration metadata for a behavior which runs on the first dispatch of its list and is
+/// removed afterwards. See [`BehaviorRegistry::register_once`].
+#[derive(Debug, Copy, Clone, Default)]
+pub struct RunOnce;
+
 pub trait BehaviorSafe: 'static + Sized + Send + Sync + Clone + fmt::Debug {}
 
 impl<T: 'static + Send + Sync + Clone + fmt::Debug> BehaviorSafe for T {}
@@ -876,38 +1034,201 @@ pub use behavior;
 #[derive(Debug, Clone)]
 #[derive_where(Default)]
 pub struct SimpleBehaviorList<B> {
-    pub behaviors: Vec<B>,
+    behaviors: Vec<SimpleBehavior<B>>,
+}
+
+#[derive(Debug, Clone)]
+struct SimpleBehavior<B> {
+    id: BehaviorId,
+    priority: i32,
+    condition: Option<BehaviorCondition>,
+    spent: Option<Arc<AtomicBool>>,
+    behavior: B,
+}
+
+impl<B> SimpleBehavior<B> {
+    fn is_spent(&self) -> bool {
+        self.spent.as_ref().is_some_and(|spent| spent.load(Relaxed))
+    }
+}
+
+impl<B> SimpleBehaviorList<B> {
+    /// Returns the registered behaviors in dispatch order, including those whose condition
+    /// currently fails and one-shot behaviors which have run but haven't been removed yet.
+    pub fn behaviors(&self) -> impl ExactSizeIterator<Item = &B> + '_ {
+        self.behaviors.iter().map(|bhv| &bhv.behavior)
+    }
+
+    pub fn len(&self) -> usize {
+        self.behaviors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.behaviors.is_empty()
+    }
+
+    fn push_new(
+        &mut self,
+        behavior: B,
+        priority: i32,
+        condition: Option<BehaviorCondition>,
+        once: bool,
+    ) -> BehaviorId {
+        let id = BehaviorId::allocate();
+
+        self.insert(SimpleBehavior {
+            id,
+            priority,
+            condition,
+            spent: once.then(Default::default),
+            behavior,
+        });
+
+        id
+    }
+
+    fn insert(&mut self, behavior: SimpleBehavior<B>) {
+        // Insert after every behavior with an equal priority to keep ordering within a band stable.
+        let idx = self
+            .behaviors
+            .partition_point(|other| other.priority <= behavior.priority);
+
+        self.behaviors.insert(idx, behavior);
+    }
+
+    fn merge(&mut self, other: Self) {
+        for bhv in other.behaviors {
+            if !bhv.is_spent() {
+                self.insert(bhv);
+            }
+        }
+    }
+
+    fn merge_ref(&mut self, other: &Self)
+    where
+        B: Clone,
+    {
+        for bhv in &other.behaviors {
+            if !bhv.is_spent() {
+                self.insert(bhv.clone());
+            }
+        }
+    }
+
+    fn remove_id(&mut self, id: BehaviorId) -> bool {
+        let Some(idx) = self.behaviors.iter().position(|bhv| bhv.id == id) else {
+            return false;
+        };
+
+        self.behaviors.remove(idx);
+        true
+    }
+
+    fn retain_unspent(&mut self) {
+        self.behaviors.retain(|bhv| !bhv.is_spent());
+    }
 }
 
 impl<B: BehaviorSafe + Multiplexable> BehaviorList for SimpleBehaviorList<B> {
     type View<'a> = B::Multiplexer<'a, Option<&'a SimpleBehaviorList<B>>>;
     type Delegate = B;
 
-    fn extend(&mut self, mut other: Self) {
-        self.behaviors.append(&mut other.behaviors);
+    fn extend(&mut self, other: Self) {
+        self.merge(other);
     }
 
     fn extend_ref(&mut self, other: &Self) {
-        self.behaviors.extend(other.behaviors.iter().cloned());
+        self.merge_ref(other);
     }
 
     fn opt_view(me: Option<&Self>) -> Self::View<'_> {
         B::make_multiplexer(me)
     }
+
+    fn remove(&mut self, id: BehaviorId) -> bool {
+        self.remove_id(id)
+    }
+
+    fn remove_spent(&mut self) {
+        self.retain_unspent();
+    }
 }
 
 impl<B: BehaviorSafe + Multiplexable> ExtendableBehaviorList for SimpleBehaviorList<B> {
-    fn push_cx(&mut self, delegate: Self::Delegate, _meta: ()) {
-        self.behaviors.push(delegate);
+    fn push_cx(&mut self, delegate: Self::Delegate, _meta: ()) -> BehaviorId {
+        self.push_new(delegate, 0, None, false)
+    }
+}
+
+impl<B: BehaviorSafe + Multiplexable> ExtendableBehaviorList<Prioritized>
+    for SimpleBehaviorList<B>
+{
+    fn push_cx(
+        &mut self,
+        delegate: Self::Delegate,
+        Prioritized(priority, ()): Prioritized,
+    ) -> BehaviorId {
+        self.push_new(delegate, priority, None, false)
     }
 }
 
-impl<B: Multiplexable> MultiplexDriver for SimpleBehaviorList<B> {
+impl<B: BehaviorSafe + Multiplexable> ExtendableBehaviorList<Conditional>
+    for SimpleBehaviorList<B>
+{
+    fn push_cx(
+        &mut self,
+        delegate: Self::Delegate,
+        Conditional(cond, ()): Conditional,
+    ) -> BehaviorId {
+        self.push_new(delegate, 0, Some(cond), false)
+    }
+}
+
+impl<B: BehaviorSafe + Multiplexable> ExtendableBehaviorList<Conditional<Prioritized>>
+    for SimpleBehaviorList<B>
+{
+    fn push_cx(
+        &mut self,
+        delegate: Self::Delegate,
+        Conditional(cond, Prioritized(priority, ())): Conditional<Prioritized>,
+    ) -> BehaviorId {
+        self.push_new(delegate, priority, Some(cond), false)
+    }
+}
+
+impl<B: BehaviorSafe + Multiplexable> ExtendableBehaviorList<RunOnce> for SimpleBehaviorList<B> {
+    fn push_cx(&mut self, delegate: Self::Delegate, _meta: RunOnce) -> BehaviorId {
+        self.push_new(delegate, 0, None, true)
+    }
+}
+
+impl<B> MultiplexDriver for SimpleBehaviorList<B> {
     type Item = B;
 
     fn drive<'a>(&'a self, mut target: impl FnMut(&'a Self::Item)) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "bort::behavior",
+            behavior = std::any::type_name::<B>(),
+            delegates = self.behaviors.len(),
+        )
+        .entered();
+
         for bhv in &self.behaviors {
-            target(bhv);
+            if bhv.condition.as_ref().is_some_and(|cond| !cond.holds()) {
+                continue;
+            }
+
+            // One-shot behaviors claim their only run here.
+            if bhv
+                .spent
+                .as_ref()
+                .is_some_and(|spent| spent.swap(true, Relaxed))
+            {
+                continue;
+            }
+
+            target(&bhv.behavior);
         }
     }
 }
@@ -924,6 +1245,9 @@ pub struct OrderedBehaviorList<B, D> {
 
 #[derive(Debug, Clone)]
 struct OrderedBehavior<B, D> {
+    /// The identifier assigned to the behavior when it was pushed.
+    id: BehaviorId,
+
     /// The behavior delegate
     behavior: B,
 
@@ -962,6 +1286,35 @@ where
     fn opt_view(me: Option<&Self>) -> Self::View<'_> {
         B::make_multiplexer(me)
     }
+
+    fn remove(&mut self, id: BehaviorId) -> bool {
+        let Some(bhv_idx) = self.behaviors.iter().position(|bhv| bhv.id == id) else {
+            return false;
+        };
+
+        let removed = self.behaviors.remove(bhv_idx);
+
+        // Unregister the dependencies it resolved.
+        for resolved in &removed.resolves {
+            self.dependents_on.get_mut(resolved).unwrap().resolvers -= 1;
+        }
+
+        // Unregister it as a dependent and shift the indices of the behaviors which came after it.
+        for info in self.dependents_on.values_mut() {
+            info.dependents.retain(|&dependent| dependent != bhv_idx);
+
+            for dependent in &mut info.dependents {
+                if *dependent > bhv_idx {
+                    *dependent -= 1;
+                }
+            }
+        }
+
+        // Invalidate the existing topological sort if applicable
+        let _ = OnceLock::take(&mut self.behaviors_topos);
+
+        true
+    }
 }
 
 impl<B, D, I1, I2> ExtendableBehaviorList<(I1, I2)> for OrderedBehaviorList<B, D>
@@ -971,10 +1324,12 @@ where
     I1: IntoIterator<Item = D>,
     I2: IntoIterator<Item = D>,
 {
-    fn push_cx(&mut self, delegate: Self::Delegate, (depends, resolves): (I1, I2)) {
+    fn push_cx(&mut self, delegate: Self::Delegate, (depends, resolves): (I1, I2)) -> BehaviorId {
         // Register the behavior
+        let id = BehaviorId::allocate();
         let bhv_idx = self.behaviors.len();
         self.behaviors.push(OrderedBehavior {
+            id,
             behavior: delegate,
             dep_count: 0, // This will be adjusted later.
             resolves: resolves.into_iter().collect(),
@@ -1017,6 +1372,8 @@ where
 
         // Invalidate the existing topological sort if applicable
         let _ = OnceLock::take(&mut self.behaviors_topos);
+
+        id
     }
 }
 
@@ -1028,6 +1385,14 @@ where
     type Item = B;
 
     fn drive<'a>(&'a self, mut target: impl FnMut(&'a Self::Item)) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "bort::behavior",
+            behavior = std::any::type_name::<B>(),
+            delegates = self.behaviors.len(),
+        )
+        .entered();
+
         let topos = self.behaviors_topos.get_or_init(|| {
             let mut toposorted = Vec::new();
 
@@ -1140,6 +1505,12 @@ impl PartialEntity<'_> {
     }
 }
 
+// Phased
+/// Behavior list metadata which assigns a handler to an initialization phase. Every handler of
+/// phase `n` runs before any handler of phase `n + 1`, regardless of their dependencies.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Phased<M>(pub u32, pub M);
+
 // InitializerBehaviorList
 #[derive(Debug, Clone)]
 #[derive_where(Default)]
@@ -1151,62 +1522,150 @@ pub struct InitializerBehaviorList<B> {
 
 #[derive(Debug, Clone)]
 struct InitHandler<B> {
+    id: BehaviorId,
     delegate: B,
     deps: FxHashSet<TypeId>,
+    priority: i32,
+    phase: u32,
 }
 
 impl<B> InitializerBehaviorList<B> {
-    pub fn execute(&self, mut executor: impl FnMut(&B, PartialEntity<'_>), target: Entity) {
-        // Execute handlers without dependencies
-        for &handler in &self.handlers_without_any_deps {
-            executor(
-                &self.handlers[handler].delegate,
-                PartialEntity {
-                    target,
-                    can_access: &self.handlers[handler].deps,
-                },
-            )
+    fn push(
+        &mut self,
+        delegate: B,
+        deps: FxHashSet<TypeId>,
+        priority: i32,
+        phase: u32,
+    ) -> BehaviorId {
+        let id = BehaviorId::allocate();
+        let handler_idx = self.handlers.len();
+
+        if deps.is_empty() {
+            let idx = self
+                .handlers_without_any_deps
+                .partition_point(|&other| self.handlers[other].priority <= priority);
+
+            self.handlers_without_any_deps.insert(idx, handler_idx);
+        } else {
+            for &dep in &deps {
+                self.handlers_with_deps
+                    .entry(dep)
+                    .or_default()
+                    .push(handler_idx);
+            }
         }
 
-        // Execute handlers with dependencies
-        let mut remaining_dep_types = self.handlers_with_deps.keys().copied().collect::<Vec<_>>();
+        self.handlers.push(InitHandler {
+            id,
+            delegate,
+            deps,
+            priority,
+            phase,
+        });
+
+        id
+    }
+
+    pub fn execute(&self, mut executor: impl FnMut(&B, PartialEntity<'_>), target: Entity) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "bort::behavior",
+            behavior = std::any::type_name::<B>(),
+            delegates = self.handlers.len(),
+            entity = ?target,
+        )
+        .entered();
+
+        let mut phases = self
+            .handlers
+            .iter()
+            .map(|handler| handler.phase)
+            .collect::<Vec<_>>();
+
+        phases.sort_unstable();
+        phases.dedup();
+
         let mut dep_counts = self
             .handlers
             .iter()
             .map(|handler| handler.deps.len())
             .collect::<Vec<_>>();
 
-        while !remaining_dep_types.is_empty() {
-            let old_len = remaining_dep_types.len();
+        let mut ready = Vec::new();
 
-            remaining_dep_types.retain(|&dep| {
-                if !target.has_dyn(dep) {
-                    return true;
+        for phase in phases {
+            // Execute handlers without dependencies
+            for &handler in &self.handlers_without_any_deps {
+                if self.handlers[handler].phase != phase {
+                    continue;
                 }
 
-                for &handler in &self.handlers_with_deps[&dep] {
-                    dep_counts[handler] -= 1;
-
-                    if dep_counts[handler] == 0 {
-                        executor(
-                            &self.handlers[handler].delegate,
-                            PartialEntity {
-                                target,
-                                can_access: &self.handlers[handler].deps,
-                            },
-                        );
+                executor(
+                    &self.handlers[handler].delegate,
+                    PartialEntity {
+                        target,
+                        can_access: &self.handlers[handler].deps,
+                    },
+                )
+            }
+
+            // Execute handlers with dependencies
+            let mut remaining_dep_types = self
+                .handlers_with_deps
+                .iter()
+                .filter(|(_, handlers)| {
+                    handlers
+                        .iter()
+                        .any(|&handler| self.handlers[handler].phase == phase)
+                })
+                .map(|(&dep, _)| dep)
+                .collect::<Vec<_>>();
+
+            while !remaining_dep_types.is_empty() {
+                let old_len = remaining_dep_types.len();
+
+                remaining_dep_types.retain(|&dep| {
+                    if !target.has_dyn(dep) {
+                        return true;
                     }
-                }
 
-                false
-            });
+                    for &handler in &self.handlers_with_deps[&dep] {
+                        if self.handlers[handler].phase != phase {
+                            continue;
+                        }
 
-            assert_ne!(
-                remaining_dep_types.len(),
-                old_len,
-                "InitializerBehaviorList is unable to load the following required component types: {:?}",
-                remaining_dep_types
-            );
+                        dep_counts[handler] -= 1;
+
+                        if dep_counts[handler] == 0 {
+                            ready.push(handler);
+                        }
+                    }
+
+                    false
+                });
+
+                // Run the newly unblocked handlers in priority order, falling back to registration
+                // order within a priority band.
+                ready.sort_by_key(|&handler| (self.handlers[handler].priority, handler));
+
+                for handler in ready.drain(..) {
+                    executor(
+                        &self.handlers[handler].delegate,
+                        PartialEntity {
+                            target,
+                            can_access: &self.handlers[handler].deps,
+                        },
+                    );
+                }
+
+                assert_ne!(
+                    remaining_dep_types.len(),
+                    old_len,
+                    "InitializerBehaviorList is unable to load the following required component types \
+                     in phase {phase}: {:?}",
+                    remaining_dep_types
+                );
+            }
         }
     }
 }
@@ -1235,11 +1694,43 @@ impl<B: BehaviorSafe> BehaviorList for InitializerBehaviorList<B> {
         );
 
         self.handlers.extend(other.handlers.iter().cloned());
+
+        // N.B. this sort is stable so handlers within the same priority band keep their
+        // registration order.
+        self.handlers_without_any_deps
+            .sort_by_key(|&handler| self.handlers[handler].priority);
     }
 
     fn opt_view(me: Option<&Self>) -> Self::View<'_> {
         InitializerBehaviorListView(me)
     }
+
+    fn remove(&mut self, id: BehaviorId) -> bool {
+        let Some(handler_idx) = self.handlers.iter().position(|handler| handler.id == id) else {
+            return false;
+        };
+
+        self.handlers.remove(handler_idx);
+
+        let fix_indices = |indices: &mut Vec<usize>| {
+            indices.retain(|&idx| idx != handler_idx);
+
+            for idx in indices {
+                if *idx > handler_idx {
+                    *idx -= 1;
+                }
+            }
+        };
+
+        fix_indices(&mut self.handlers_without_any_deps);
+
+        self.handlers_with_deps.retain(|_, handlers| {
+            fix_indices(handlers);
+            !handlers.is_empty()
+        });
+
+        true
+    }
 }
 
 impl<B, I> ExtendableBehaviorList<I> for InitializerBehaviorList<B>
@@ -1247,19 +1738,32 @@ where
     B: BehaviorSafe,
     I: IntoIterator<Item = TypeId>,
 {
-    fn push_cx(&mut self, delegate: Self::Delegate, deps: I) {
-        let deps = deps.into_iter().collect::<FxHashSet<_>>();
-        if deps.is_empty() {
-            self.handlers_without_any_deps.push(self.handlers.len());
-        } else {
-            for &dep in &deps {
-                self.handlers_with_deps
-                    .entry(dep)
-                    .or_default()
-                    .push(self.handlers.len());
-            }
-        }
-        self.handlers.push(InitHandler { delegate, deps });
+    fn push_cx(&mut self, delegate: Self::Delegate, deps: I) -> BehaviorId {
+        self.push(delegate, deps.into_iter().collect(), 0, 0)
+    }
+}
+
+impl<B, I> ExtendableBehaviorList<Prioritized<I>> for InitializerBehaviorList<B>
+where
+    B: BehaviorSafe,
+    I: IntoIterator<Item = TypeId>,
+{
+    fn push_cx(
+        &mut self,
+        delegate: Self::Delegate,
+        Prioritized(priority, deps): Prioritized<I>,
+    ) -> BehaviorId {
+        self.push(delegate, deps.into_iter().collect(), priority, 0)
+    }
+}
+
+impl<B, I> ExtendableBehaviorList<Phased<I>> for InitializerBehaviorList<B>
+where
+    B: BehaviorSafe,
+    I: IntoIterator<Item = TypeId>,
+{
+    fn push_cx(&mut self, delegate: Self::Delegate, Phased(phase, deps): Phased<I>) -> BehaviorId {
+        self.push(delegate, deps.into_iter().collect(), 0, phase)
     }
 }
 
@@ -1274,3 +1778,435 @@ impl<B> InitializerBehaviorListView<'_, B> {
         }
     }
 }
+
+// === FallbackBehaviorList === //
+
+/// Behavior list metadata which marks a handler as a fallback. Fallback handlers only run if none
+/// of the regular handlers in the list handled the dispatch.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Fallback;
+
+#[derive(Debug, Clone)]
+#[derive_where(Default)]
+pub struct FallbackBehaviorList<B> {
+    handlers: Vec<(BehaviorId, B)>,
+    fallbacks: Vec<(BehaviorId, B)>,
+}
+
+impl<B: BehaviorSafe> BehaviorList for FallbackBehaviorList<B> {
+    type View<'a> = FallbackBehaviorListView<'a, B>;
+    type Delegate = B;
+
+    fn extend(&mut self, mut other: Self) {
+        self.handlers.append(&mut other.handlers);
+        self.fallbacks.append(&mut other.fallbacks);
+    }
+
+    fn extend_ref(&mut self, other: &Self) {
+        self.handlers.extend(other.handlers.iter().cloned());
+        self.fallbacks.extend(other.fallbacks.iter().cloned());
+    }
+
+    fn opt_view(me: Option<&Self>) -> Self::View<'_> {
+        FallbackBehaviorListView(me)
+    }
+
+    fn remove(&mut self, id: BehaviorId) -> bool {
+        for list in [&mut self.handlers, &mut self.fallbacks] {
+            if let Some(idx) = list.iter().position(|&(other, _)| other == id) {
+                list.remove(idx);
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+impl<B: BehaviorSafe> ExtendableBehaviorList for FallbackBehaviorList<B> {
+    fn push_cx(&mut self, delegate: Self::Delegate, _meta: ()) -> BehaviorId {
+        let id = BehaviorId::allocate();
+        self.handlers.push((id, delegate));
+        id
+    }
+}
+
+impl<B: BehaviorSafe> ExtendableBehaviorList<Fallback> for FallbackBehaviorList<B> {
+    fn push_cx(&mut self, delegate: Self::Delegate, _meta: Fallback) -> BehaviorId {
+        let id = BehaviorId::allocate();
+        self.fallbacks.push((id, delegate));
+        id
+    }
+}
+
+#[derive(Debug)]
+#[derive_where(Clone, Copy)]
+pub struct FallbackBehaviorListView<'a, B>(Option<&'a FallbackBehaviorList<B>>);
+
+impl<B> FallbackBehaviorListView<'_, B> {
+    /// Runs `handler` on every regular handler in registration order until one of them breaks. If
+    /// none of them do, the fallback handlers are run in the same way. Returns the value of the
+    /// first break, if any.
+    pub fn dispatch<R>(&self, mut handler: impl FnMut(&B) -> ControlFlow<R>) -> Option<R> {
+        let inner = self.0?;
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "bort::behavior",
+            behavior = std::any::type_name::<B>(),
+            delegates = inner.handlers.len() + inner.fallbacks.len(),
+        )
+        .entered();
+
+        let mut dispatch =
+            |list: &[(BehaviorId, B)]| list.iter().try_for_each(|(_, bhv)| handler(bhv));
+
+        match dispatch(&inner.handlers) {
+            ControlFlow::Break(value) => Some(value),
+            ControlFlow::Continue(()) => dispatch(&inner.fallbacks).break_value(),
+        }
+    }
+}
+
+// === CollectingBehaviorList === //
+
+/// A behavior list for delegates whose return values should be gathered rather than discarded.
+/// Ordering, priorities, and conditions behave exactly as they do in a [`SimpleBehaviorList`].
+#[derive(Debug, Clone)]
+#[derive_where(Default)]
+pub struct CollectingBehaviorList<B>(SimpleBehaviorList<B>);
+
+impl<B: BehaviorSafe> BehaviorList for CollectingBehaviorList<B> {
+    type View<'a> = CollectingBehaviorListView<'a, B>;
+    type Delegate = B;
+
+    fn extend(&mut self, other: Self) {
+        self.0.merge(other.0);
+    }
+
+    fn extend_ref(&mut self, other: &Self) {
+        self.0.merge_ref(&other.0);
+    }
+
+    fn opt_view(me: Option<&Self>) -> Self::View<'_> {
+        CollectingBehaviorListView(me)
+    }
+
+    fn remove(&mut self, id: BehaviorId) -> bool {
+        self.0.remove_id(id)
+    }
+
+    fn remove_spent(&mut self) {
+        self.0.retain_unspent();
+    }
+}
+
+impl<B: BehaviorSafe> ExtendableBehaviorList for CollectingBehaviorList<B> {
+    fn push_cx(&mut self, delegate: Self::Delegate, _meta: ()) -> BehaviorId {
+        self.0.push_new(delegate, 0, None, false)
+    }
+}
+
+impl<B: BehaviorSafe> ExtendableBehaviorList<Prioritized> for CollectingBehaviorList<B> {
+    fn push_cx(
+        &mut self,
+        delegate: Self::Delegate,
+        Prioritized(priority, ()): Prioritized,
+    ) -> BehaviorId {
+        self.0.push_new(delegate, priority, None, false)
+    }
+}
+
+impl<B: BehaviorSafe> ExtendableBehaviorList<Conditional> for CollectingBehaviorList<B> {
+    fn push_cx(
+        &mut self,
+        delegate: Self::Delegate,
+        Conditional(cond, ()): Conditional,
+    ) -> BehaviorId {
+        self.0.push_new(delegate, 0, Some(cond), false)
+    }
+}
+
+impl<B: BehaviorSafe> ExtendableBehaviorList<Conditional<Prioritized>>
+    for CollectingBehaviorList<B>
+{
+    fn push_cx(
+        &mut self,
+        delegate: Self::Delegate,
+        Conditional(cond, Prioritized(priority, ())): Conditional<Prioritized>,
+    ) -> BehaviorId {
+        self.0.push_new(delegate, priority, Some(cond), false)
+    }
+}
+
+impl<B: BehaviorSafe> ExtendableBehaviorList<RunOnce> for CollectingBehaviorList<B> {
+    fn push_cx(&mut self, delegate: Self::Delegate, _meta: RunOnce) -> BehaviorId {
+        self.0.push_new(delegate, 0, None, true)
+    }
+}
+
+#[derive(Debug)]
+#[derive_where(Clone, Copy)]
+pub struct CollectingBehaviorListView<'a, B>(Option<&'a CollectingBehaviorList<B>>);
+
+impl<'a, B> CollectingBehaviorListView<'a, B> {
+    /// Calls `handler` on every behavior in dispatch order and collects the results.
+    pub fn collect<R>(&self, mut handler: impl FnMut(&'a B) -> R) -> Vec<R> {
+        let mut out = Vec::new();
+        if let Some(list) = self.0 {
+            list.0.drive(|bhv| out.push(handler(bhv)));
+        }
+        out
+    }
+
+    /// Calls `handler` on every behavior in dispatch order, threading an accumulator through each
+    /// call.
+    pub fn fold<A>(&self, init: A, mut handler: impl FnMut(A, &'a B) -> A) -> A {
+        let mut acc = Some(init);
+        if let Some(list) = self.0 {
+            list.0.drive(|bhv| {
+                acc = Some(handler(acc.take().unwrap(), bhv));
+            });
+        }
+        acc.unwrap()
+    }
+}
+
+// === Tests === //
+
+#[cfg(test)]
+mod tests {
+    use std::{iter, sync::Mutex};
+
+    use super::*;
+
+    behavior! {
+        fn TestLog(log: &Mutex<Vec<u32>>)
+    }
+
+    fn logger(id: u32) -> TestLog {
+        TestLog::new(move |_bhv, log| log.lock().unwrap().push(id))
+    }
+
+    fn dispatch(registry: &BehaviorRegistry) -> Vec<u32> {
+        let log = Mutex::new(Vec::new());
+        registry.get::<TestLog>().call(registry, &log);
+        log.into_inner().unwrap()
+    }
+
+    #[test]
+    fn unregister_removes_only_the_given_behavior() {
+        let mut registry = BehaviorRegistry::new();
+        let first = registry.register(logger(1));
+        let second = registry.register(logger(2));
+        assert_ne!(first, second);
+        assert_eq!(dispatch(&registry), [1, 2]);
+
+        assert!(registry.unregister(second));
+        assert!(!registry.unregister(second));
+        assert_eq!(dispatch(&registry), [1]);
+
+        // Registering a clone of the same delegate yields a distinct handle.
+        let first_again = registry.register(logger(1));
+        assert_eq!(dispatch(&registry), [1, 1]);
+        assert!(registry.unregister(first));
+        assert_eq!(dispatch(&registry), [1]);
+        assert!(registry.unregister(first_again));
+        assert!(dispatch(&registry).is_empty());
+    }
+
+    #[test]
+    fn register_once_runs_only_on_the_first_dispatch() {
+        let mut registry = BehaviorRegistry::new();
+        registry.register(logger(1));
+        let once = registry.register_once(logger(2));
+
+        assert_eq!(dispatch(&registry), [1, 2]);
+        assert_eq!(dispatch(&registry), [1]);
+        assert_eq!(dispatch(&registry), [1]);
+
+        // The spent behavior is dropped on the next modification and is no longer unregistrable.
+        assert_eq!(registry.get_list::<TestLog>().unwrap().len(), 2);
+        registry.remove_spent();
+        assert_eq!(registry.get_list::<TestLog>().unwrap().len(), 1);
+        assert!(!registry.unregister(once));
+
+        // Unregistering a one-shot behavior before it runs prevents it from ever running.
+        let once = registry.register_once(logger(3));
+        assert!(registry.unregister(once));
+        assert_eq!(dispatch(&registry), [1]);
+    }
+
+    #[test]
+    fn simple_list_dispatches_by_priority() {
+        let mut registry = BehaviorRegistry::new();
+
+        for (priority, id) in [(5, 1), (-1, 2), (5, 3), (0, 4), (-1, 5)] {
+            registry.register_prioritized(priority, logger(id));
+        }
+
+        // Ascending priority, stable within a band.
+        assert_eq!(dispatch(&registry), [2, 5, 4, 1, 3]);
+        assert_eq!(registry.get_list::<TestLog>().unwrap().len(), 5);
+
+        // Merged registrations are slotted in by priority as well.
+        let mut other = BehaviorRegistry::new();
+        other.register_prioritized(0, logger(6));
+        other.register_prioritized(-1, logger(7));
+        registry.register_from(&other);
+
+        assert_eq!(dispatch(&registry), [2, 5, 7, 4, 6, 1, 3]);
+    }
+
+    #[test]
+    fn conditional_behaviors_follow_their_predicate() {
+        let enabled = Arc::new(AtomicBool::new(false));
+
+        let mut registry = BehaviorRegistry::new();
+        registry.register(logger(1));
+        let gated = registry.register_if(
+            {
+                let enabled = enabled.clone();
+                move || enabled.load(Relaxed)
+            },
+            logger(2),
+        );
+        registry.register_prioritized(1, logger(3));
+        registry.register_once(logger(4));
+
+        assert_eq!(dispatch(&registry), [1, 4, 3]);
+
+        enabled.store(true, Relaxed);
+        assert_eq!(dispatch(&registry), [1, 2, 3]);
+
+        // Conditions are carried over when registries are merged.
+        let mut merged = BehaviorRegistry::new();
+        merged.register(logger(0));
+        merged.register_from(&registry);
+        assert_eq!(dispatch(&merged), [0, 1, 2, 3]);
+
+        enabled.store(false, Relaxed);
+        assert_eq!(dispatch(&merged), [0, 1, 3]);
+
+        // Removing a conditional behavior removes its condition alongside it.
+        assert!(registry.unregister(gated));
+        enabled.store(true, Relaxed);
+        assert_eq!(dispatch(&registry), [1, 3]);
+    }
+
+    #[test]
+    fn initializer_phases_run_in_order() {
+        #[derive(Debug)]
+        struct Loaded;
+
+        crate::util::testing::run_on_main_thread(|| {
+            let mut list = InitializerBehaviorList::<u32>::default();
+            let no_deps = || iter::empty::<TypeId>();
+            let loaded = || [TypeId::of::<Loaded>()];
+
+            // Registered out of order so that neither registration order nor dependencies alone
+            // would produce the expected order.
+            list.push_cx(3, Phased(1, no_deps()));
+            list.push_cx(2, Phased(0, loaded()));
+            list.push_cx(4, Phased(1, loaded()));
+            list.push_cx(1, Phased(0, no_deps()));
+
+            let target = crate::entity::OwnedEntity::new();
+            let mut log = Vec::new();
+            list.execute(
+                |&id, entity| {
+                    log.push(id);
+                    if id == 1 {
+                        entity.add(Loaded);
+                    }
+                },
+                target.entity(),
+            );
+
+            assert_eq!(log, [1, 2, 3, 4]);
+        });
+    }
+
+    #[test]
+    fn fallback_list_only_falls_back_when_unhandled() {
+        let mut list = FallbackBehaviorList::<u32>::default();
+        list.push_cx(1, ());
+        list.push_cx(9, Fallback);
+        list.push_cx(2, ());
+
+        let run = |list: &FallbackBehaviorList<u32>, handles: u32| {
+            let mut log = Vec::new();
+            let handled = FallbackBehaviorList::opt_view(Some(list)).dispatch(|&id| {
+                log.push(id);
+                if id == handles {
+                    ControlFlow::Break(id)
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+            (handled, log)
+        };
+
+        // A regular handler handled the dispatch so the fallback is skipped.
+        assert_eq!(run(&list, 2), (Some(2), vec![1, 2]));
+        assert_eq!(run(&list, 1), (Some(1), vec![1]));
+
+        // Nobody handled the dispatch so the fallback runs.
+        assert_eq!(run(&list, 9), (Some(9), vec![1, 2, 9]));
+        assert_eq!(run(&list, 0), (None, vec![1, 2, 9]));
+
+        // A list without regular handlers always falls back.
+        let mut only_fallback = FallbackBehaviorList::<u32>::default();
+        only_fallback.push_cx(9, Fallback);
+        assert_eq!(run(&only_fallback, 9), (Some(9), vec![9]));
+    }
+
+    #[test]
+    fn collecting_list_gathers_return_values_in_dispatch_order() {
+        let mut list = CollectingBehaviorList::<u32>::default();
+        list.push_cx(10, Prioritized(1, ()));
+        list.push_cx(20, ());
+        list.push_cx(30, Prioritized(-1, ()));
+        list.push_cx(40, Conditional(BehaviorCondition::new(|| false), ()));
+
+        let view = CollectingBehaviorList::opt_view(Some(&list));
+        assert_eq!(view.collect(|&v| v + 1), [31, 21, 11]);
+        assert_eq!(
+            view.fold(Vec::new(), |mut acc, &v| {
+                acc.push(v);
+                acc
+            }),
+            [30, 20, 10]
+        );
+
+        // An absent list collects nothing.
+        let empty = CollectingBehaviorList::<u32>::opt_view(None);
+        assert!(empty.collect(|&v| v).is_empty());
+        assert_eq!(empty.fold(5, |acc, &v| acc + v), 5);
+    }
+
+    #[test]
+    #[cfg(feature = "tracing")]
+    fn dispatch_records_tracing_spans() {
+        use std::any::type_name;
+
+        use crate::util::testing::record_spans;
+
+        let mut registry = BehaviorRegistry::new();
+        registry.register(logger(1));
+        registry.register(logger(2));
+
+        let (log, spans) = record_spans(|| dispatch(&registry));
+        assert_eq!(log, [1, 2]);
+
+        let span = spans
+            .iter()
+            .find(|span| span.name == "bort::behavior")
+            .unwrap();
+        assert_eq!(
+            span.field("behavior"),
+            Some(format!("{:?}", type_name::<TestLog>()).as_str())
+        );
+        assert_eq!(span.field("delegates"), Some("2"));
+    }
+}
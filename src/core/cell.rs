@@ -5,7 +5,7 @@ use std::{
     fmt,
     marker::PhantomData,
     mem::{self, MaybeUninit},
-    ops::{Deref, DerefMut},
+    ops::{Deref, DerefMut, Range},
     ptr::NonNull,
 };
 
@@ -155,6 +155,109 @@ cfgenius::cond! {
     }
 }
 
+// === Version tracker === //
+
+cfgenius::define!(pub tracks_component_versions = cfg(feature = "component-versions"));
+
+cfgenius::cond! {
+    if macro(tracks_component_versions) {
+        use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+
+        struct VersionTracker {
+            versions: [Cell<u64>; MultiRefCellIndex::COUNT],
+            shared_version: Cell<u64>,
+        }
+
+        impl VersionTracker {
+            pub fn new() -> Self {
+                Self {
+                    versions: std::array::from_fn(|_| Cell::new(0)),
+                    shared_version: Cell::new(0),
+                }
+            }
+
+            pub fn get(&self, i: MultiRefCellIndex) -> u64 {
+                // Borrowing every cell at once only advances `shared_version` so that bulk borrows
+                // stay cheap. The observed version of a cell is the sum of both counters.
+                self.versions[i as usize]
+                    .get()
+                    .wrapping_add(self.shared_version.get())
+            }
+
+            fn set(&self, i: MultiRefCellIndex, version: u64) {
+                self.versions[i as usize].set(version.wrapping_sub(self.shared_version.get()));
+            }
+
+            #[inline(always)]
+            pub fn bump(&self, i: MultiRefCellIndex) {
+                let version = &self.versions[i as usize];
+                version.set(version.get().wrapping_add(1));
+            }
+
+            #[inline(always)]
+            pub fn bump_all(&self) {
+                self.shared_version
+                    .set(self.shared_version.get().wrapping_add(1));
+            }
+
+            pub fn reset(&self, i: MultiRefCellIndex) {
+                // Newly-inserted values take their version from a global counter's upper bits so
+                // that a value moving into a recycled slot can never observe a version it
+                // previously reported.
+                static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1 << 32);
+
+                self.set(i, NEXT_GENERATION.fetch_add(1 << 32, Relaxed));
+            }
+
+            pub fn swap(
+                &self,
+                i_me: MultiRefCellIndex,
+                other: &Self,
+                i_other: MultiRefCellIndex,
+                swap_values: impl FnOnce(),
+            ) {
+                let version_from_me = self.get(i_me);
+                let version_from_other = other.get(i_other);
+
+                // Moving the values around bumps and resets their versions so we restore them
+                // afterwards.
+                swap_values();
+
+                self.set(i_me, version_from_other);
+                other.set(i_other, version_from_me);
+            }
+        }
+    } else {
+        struct VersionTracker(());
+
+        impl VersionTracker {
+            pub const fn new() -> Self {
+                Self(())
+            }
+
+            #[inline(always)]
+            pub fn bump(&self, _i: MultiRefCellIndex) {}
+
+            #[inline(always)]
+            pub fn bump_all(&self) {}
+
+            #[inline(always)]
+            pub fn reset(&self, _i: MultiRefCellIndex) {}
+
+            #[inline(always)]
+            pub fn swap(
+                &self,
+                _i_me: MultiRefCellIndex,
+                _other: &Self,
+                _i_other: MultiRefCellIndex,
+                swap_values: impl FnOnce(),
+            ) {
+                swap_values();
+            }
+        }
+    }
+}
+
 // === Borrow error === //
 
 // Public
@@ -172,6 +275,14 @@ impl fmt::Display for BorrowError {
 #[derive(Debug, Clone)]
 pub struct BorrowMutError(CommonBorrowError<true>);
 
+impl BorrowMutError {
+    /// Returns the number of immutable borrows which prevented the cell from being borrowed
+    /// mutably. This is zero if the cell was instead blocked by a mutable borrow.
+    pub fn reader_count(&self) -> usize {
+        self.0.state.saturating_sub(NEUTRAL) as usize
+    }
+}
+
 impl Error for BorrowMutError {}
 
 impl fmt::Display for BorrowMutError {
@@ -195,9 +306,9 @@ fn fmt_borrow_error_prefix(f: &mut fmt::Formatter, state: u8, mutably: bool) ->
         // to be in, which would imply that the borrow failed because we have too many guards of
         // the same type.
         let blockers = if mutably {
-            NEUTRAL.checked_sub(state)
-        } else {
             state.checked_sub(NEUTRAL)
+        } else {
+            NEUTRAL.checked_sub(state)
         };
 
         if let Some(blockers) = blockers {
@@ -774,6 +885,7 @@ impl MultiRefCellIndex {
 pub struct MultiOptRefCell<T> {
     states: Cell<u128>,
     borrowed_ats: [BorrowTracker; MultiRefCellIndex::COUNT],
+    versions: VersionTracker,
     values: [UnsafeCell<MaybeUninit<T>>; MultiRefCellIndex::COUNT],
 }
 
@@ -784,6 +896,7 @@ impl<T> MultiOptRefCell<T> {
         Self {
             states: Cell::new(repeat_byte(EMPTY)),
             borrowed_ats: std::array::from_fn(|_| BorrowTracker::new()),
+            versions: VersionTracker::new(),
             values: std::array::from_fn(|_| UnsafeCell::new(MaybeUninit::uninit())),
         }
     }
@@ -829,6 +942,11 @@ impl<T> MultiOptRefCell<T> {
         wide_cell_to_byte_cell(&self.states)[i as usize].get() == EMPTY
     }
 
+    #[cfg(feature = "component-versions")]
+    pub fn version(&self, i: MultiRefCellIndex) -> u64 {
+        self.versions.get(i)
+    }
+
     pub fn set(&mut self, i: MultiRefCellIndex, value: Option<T>) -> Option<T> {
         self.undo_leak();
         self.replace(i, value)
@@ -945,6 +1063,7 @@ impl<T> MultiOptRefCell<T> {
         let value = &self.values[i as usize];
 
         if let Some(borrow) = CellBorrowMut::acquire(state, borrowed_at) {
+            self.versions.bump(i);
             Ok(Some(OptRefMut {
                 value: NonNull::from(unsafe { (*value.get()).assume_init_mut() }),
                 autoken: loaner.loan(),
@@ -970,6 +1089,7 @@ impl<T> MultiOptRefCell<T> {
         let value = &self.values[i as usize];
 
         if let Some(borrow) = CellBorrowMut::acquire(state, borrowed_at) {
+            self.versions.bump(i);
             Some(OptRefMut {
                 value: NonNull::from(unsafe { (*value.get()).assume_init_mut() }),
                 autoken: loaner.loan(),
@@ -991,6 +1111,7 @@ impl<T> MultiOptRefCell<T> {
         let value = &self.values[i as usize];
 
         if let Some(borrow) = CellBorrowMut::acquire(state, borrowed_at) {
+            self.versions.bump(i);
             OptRefMut {
                 value: NonNull::from(unsafe { (*value.get()).assume_init_mut() }),
                 autoken: MutableBorrow::new(),
@@ -1082,6 +1203,7 @@ impl<T> MultiOptRefCell<T> {
                 } else {
                     debug_assert_eq!(state.get(), EMPTY);
                     state.set(NEUTRAL);
+                    self.versions.reset(i);
                     unsafe { &mut *value_ptr.get() }.write(value);
 
                     Ok(None)
@@ -1144,9 +1266,12 @@ impl<T> MultiOptRefCell<T> {
             return;
         }
 
-        let value_from_me = self.take(i_me);
-        let value_from_other = other.replace(i_other, value_from_me);
-        self.replace(i_me, value_from_other);
+        // Versions follow the values they describe.
+        self.versions.swap(i_me, &other.versions, i_other, || {
+            let value_from_me = self.take(i_me);
+            let value_from_other = other.replace(i_other, value_from_me);
+            self.replace(i_me, value_from_other);
+        });
     }
 
     // === Multi-Borrows === //
@@ -1169,6 +1294,30 @@ impl<T> MultiOptRefCell<T> {
         })
     }
 
+    pub fn try_borrow_range<'l>(
+        &'l self,
+        range: Range<usize>,
+        loaner: &'l PotentialImmutableBorrow<T>,
+    ) -> Option<MultiOptRangeRef<'l, T>> {
+        let mut mask = [0; MultiRefCellIndex::COUNT];
+        mask[range.clone()].fill(1);
+        let mask = u128::from_ne_bytes(mask);
+
+        let new_states = self.states.get() + mask;
+        if new_states & (mask * IMMUTABLE_MASK as u128) != mask * IMMUTABLE_MASK as u128 {
+            return None;
+        }
+        self.states.set(new_states);
+
+        let _ = loaner;
+        Some(MultiOptRangeRef {
+            _ty: PhantomData,
+            state: &self.states,
+            mask,
+            values: NonNull::new(&self.values[range] as *const [_] as *mut [T]).unwrap(),
+        })
+    }
+
     pub fn try_borrow_all_mut<'l>(
         &'l self,
         loaner: &'l mut PotentialMutableBorrow<T>,
@@ -1176,7 +1325,9 @@ impl<T> MultiOptRefCell<T> {
         if self.states.get() != repeat_byte(NEUTRAL) {
             return None;
         }
-        self.states.set(repeat_byte(NEUTRAL + 1));
+        self.states.set(repeat_byte(NEUTRAL - 1));
+
+        self.versions.bump_all();
 
         let _ = loaner;
         Some(MultiOptRefMut {
@@ -1247,6 +1398,35 @@ impl<T> Drop for MultiOptRef<'_, T> {
     }
 }
 
+// === MultiOptRangeRef === //
+
+pub struct MultiOptRangeRef<'b, T> {
+    _ty: PhantomData<&'b T>,
+    state: &'b Cell<u128>,
+    mask: u128,
+    values: NonNull<[T]>,
+}
+
+impl<'b, T> Deref for MultiOptRangeRef<'b, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.values.as_ref() }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MultiOptRangeRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T> Drop for MultiOptRangeRef<'_, T> {
+    fn drop(&mut self) {
+        self.state.set(self.state.get() - self.mask);
+    }
+}
+
 // === MultiOptRefMut === //
 
 pub struct MultiOptRefMut<'b, T> {
@@ -1487,3 +1667,82 @@ impl<T: ?Sized + fmt::Display, B: ?Sized> fmt::Display for OptRefMut<'_, T, B> {
         fmt::Display::fmt(&**self, f)
     }
 }
+
+// === Tests === //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borrow_error_reports_blockers() {
+        let cell = OptRefCell::new_full(1u32);
+
+        {
+            let _a = cell.borrow();
+            let _b = cell.borrow();
+            let err = cell
+                .try_borrow_mut(&mut PotentialMutableBorrow::new())
+                .unwrap_err();
+
+            assert!(err
+                .to_string()
+                .starts_with("failed to borrow cell mutably: cell is borrowed by 2 readers"));
+        }
+
+        {
+            let _a = cell.borrow_mut();
+            let err = cell
+                .try_borrow(&PotentialImmutableBorrow::new())
+                .unwrap_err();
+
+            assert!(err
+                .to_string()
+                .starts_with("failed to borrow cell immutably: cell is borrowed by 1 writer"));
+        }
+    }
+
+    #[test]
+    fn borrow_mut_error_counts_readers() {
+        let cell = OptRefCell::new_full(1u32);
+        let try_borrow_mut = || {
+            cell.try_borrow_mut(&mut PotentialMutableBorrow::new())
+                .unwrap_err()
+        };
+
+        {
+            let _a = cell.borrow();
+            assert_eq!(try_borrow_mut().reader_count(), 1);
+
+            let _b = cell.borrow();
+            let _c = cell.borrow();
+            assert_eq!(try_borrow_mut().reader_count(), 3);
+        }
+
+        {
+            let _a = cell.borrow_mut();
+            assert_eq!(try_borrow_mut().reader_count(), 0);
+        }
+    }
+
+    #[test]
+    fn borrow_all_mut_excludes_readers() {
+        let mut cell = MultiOptRefCell::new();
+
+        for i in MultiRefCellIndex::iter() {
+            cell.set(i, Some(i as u32));
+        }
+
+        let mut loaner = PotentialMutableBorrow::new();
+        let _guard = cell.try_borrow_all_mut(&mut loaner).unwrap();
+
+        for i in MultiRefCellIndex::iter() {
+            assert!(cell
+                .try_borrow(i, &PotentialImmutableBorrow::new())
+                .is_err());
+        }
+        assert!(cell
+            .try_borrow_all(&PotentialImmutableBorrow::new())
+            .is_none());
+    }
+}
@@ -1,15 +1,19 @@
 use std::{
+    any::TypeId,
     fmt,
     marker::PhantomData,
     ptr::{null_mut, NonNull},
     sync::atomic::{AtomicU64, Ordering::Relaxed},
 };
 
-use autoken::{ImmutableBorrow, MutableBorrow, Nothing};
+use autoken::{
+    ImmutableBorrow, MutableBorrow, Nothing, PotentialImmutableBorrow, PotentialMutableBorrow,
+};
 use derive_where::derive_where;
 
 use crate::{
     database::InertEntity,
+    debug::audit_borrow,
     entity::Entity,
     util::{
         hash_map::{FxHashBuilder, FxHashMap},
@@ -18,7 +22,7 @@ use crate::{
 };
 
 use super::{
-    cell::{MultiRefCellIndex, OptRef, OptRefMut},
+    cell::{BorrowError, BorrowMutError, MultiRefCellIndex, OptRef, OptRefMut},
     random_iter::{
         RandomAccessMap, RandomAccessMapper, RandomAccessSliceRef, RandomAccessZip,
         UntiedRandomAccessIter,
@@ -476,12 +480,30 @@ impl<'a, T: 'static> DirectSlot<'a, T> {
         .get(token, self.heap_index)
     }
 
+    #[track_caller]
+    pub fn try_borrow<'b, 'l>(
+        self,
+        token: &'b impl BorrowToken<T>,
+        loaner: &'l PotentialImmutableBorrow<T>,
+    ) -> Result<Option<OptRef<'b, T, Nothing<'l>>>, BorrowError> {
+        audit_borrow::<T>(false);
+
+        unsafe {
+            // Safety: is this function succeeds, it will return an `OptRef` to its contents, which
+            // precludes deletion until the reference expires.
+            self.heap_value_prolonged()
+        }
+        .try_borrow(token, self.heap_index, loaner)
+    }
+
     #[track_caller]
     pub fn borrow_or_none<'b, 'l>(
         self,
         token: &'b impl BorrowToken<T>,
         loaner: &'l ImmutableBorrow<T>,
     ) -> Option<OptRef<'b, T, Nothing<'l>>> {
+        audit_borrow::<T>(false);
+
         unsafe {
             // Safety: is this function succeeds, it will return an `OptRef` to its contents, which
             // precludes deletion until the reference expires.
@@ -492,6 +514,8 @@ impl<'a, T: 'static> DirectSlot<'a, T> {
 
     #[track_caller]
     pub fn borrow(self, token: &impl BorrowToken<T>) -> OptRef<T, T> {
+        audit_borrow::<T>(false);
+
         unsafe {
             // Safety: is this function succeeds, it will return an `OptRef` to its contents, which
             // precludes deletion until the reference expires.
@@ -506,6 +530,8 @@ impl<'a, T: 'static> DirectSlot<'a, T> {
         token: &'b impl BorrowToken<T>,
         loaner: &'l ImmutableBorrow<T>,
     ) -> OptRef<'b, T, Nothing<'l>> {
+        audit_borrow::<T>(false);
+
         unsafe {
             // Safety: is this function succeeds, it will return an `OptRef` to its contents, which
             // precludes deletion until the reference expires.
@@ -514,12 +540,30 @@ impl<'a, T: 'static> DirectSlot<'a, T> {
         .borrow_on_loan(token, self.heap_index, loaner)
     }
 
+    #[track_caller]
+    pub fn try_borrow_mut<'b, 'l>(
+        self,
+        token: &'b impl BorrowMutToken<T>,
+        loaner: &'l mut PotentialMutableBorrow<T>,
+    ) -> Result<Option<OptRefMut<'b, T, Nothing<'l>>>, BorrowMutError> {
+        audit_borrow::<T>(true);
+
+        unsafe {
+            // Safety: is this function succeeds, it will return an `OptRef` to its contents, which
+            // precludes deletion until the reference expires.
+            self.heap_value_prolonged()
+        }
+        .try_borrow_mut(token, self.heap_index, loaner)
+    }
+
     #[track_caller]
     pub fn borrow_mut_or_none<'b, 'l>(
         self,
         token: &'b impl BorrowMutToken<T>,
         loaner: &'l mut MutableBorrow<T>,
     ) -> Option<OptRefMut<'b, T, Nothing<'l>>> {
+        audit_borrow::<T>(true);
+
         unsafe {
             // Safety: is this function succeeds, it will return an `OptRef` to its contents, which
             // precludes deletion until the reference expires.
@@ -530,6 +574,8 @@ impl<'a, T: 'static> DirectSlot<'a, T> {
 
     #[track_caller]
     pub fn borrow_mut(self, token: &impl BorrowMutToken<T>) -> OptRefMut<T, T> {
+        audit_borrow::<T>(true);
+
         unsafe {
             // Safety: is this function succeeds, it will return an `OptRef` to its contents, which
             // precludes deletion until the reference expires.
@@ -544,6 +590,8 @@ impl<'a, T: 'static> DirectSlot<'a, T> {
         token: &'b impl BorrowMutToken<T>,
         loaner: &'l mut MutableBorrow<T>,
     ) -> OptRefMut<'b, T, Nothing<'l>> {
+        audit_borrow::<T>(true);
+
         unsafe {
             // Safety: is this function succeeds, it will return an `OptRef` to its contents, which
             // precludes deletion until the reference expires.
@@ -565,6 +613,11 @@ impl<'a, T: 'static> DirectSlot<'a, T> {
     pub fn is_empty(self, token: &impl TokenFor<T>) -> bool {
         self.heap_value.is_empty(token, self.heap_index)
     }
+
+    #[cfg(feature = "component-versions")]
+    pub fn version(self, token: &impl TokenFor<T>) -> u64 {
+        self.heap_value.version(token, self.heap_index)
+    }
 }
 
 impl<T> From<DirectSlot<'_, T>> for Slot<T> {
@@ -674,6 +727,20 @@ impl<T> Slot<T> {
         }
     }
 
+    #[track_caller]
+    pub fn try_borrow<'b, 'l>(
+        self,
+        token: &'b impl BorrowToken<T>,
+        loaner: &'l PotentialImmutableBorrow<T>,
+    ) -> Result<Option<OptRef<'b, T, Nothing<'l>>>, BorrowError> {
+        unsafe {
+            // Safety: we only use the `DirectSlot` until the function returns, and we know the
+            // direct slot cannot be invalidated until then because we never call something which
+            // could potentially destroy the heap.
+            self.direct_slot(token).try_borrow(token, loaner)
+        }
+    }
+
     #[track_caller]
     pub fn borrow_or_none<'b, 'l>(
         self,
@@ -712,6 +779,20 @@ impl<T> Slot<T> {
         }
     }
 
+    #[track_caller]
+    pub fn try_borrow_mut<'b, 'l>(
+        self,
+        token: &'b impl BorrowMutToken<T>,
+        loaner: &'l mut PotentialMutableBorrow<T>,
+    ) -> Result<Option<OptRefMut<'b, T, Nothing<'l>>>, BorrowMutError> {
+        unsafe {
+            // Safety: we only use the `DirectSlot` until the function returns, and we know the
+            // direct slot cannot be invalidated until then because we never call something which
+            // could potentially destroy the heap.
+            self.direct_slot(token).try_borrow_mut(token, loaner)
+        }
+    }
+
     #[track_caller]
     pub fn borrow_mut_or_none<'b, 'l>(
         self,
@@ -768,4 +849,60 @@ impl<T> Slot<T> {
             self.direct_slot(token).is_empty(token)
         }
     }
+
+    #[cfg(feature = "component-versions")]
+    pub fn version(&self, token: &impl TokenFor<T>) -> u64 {
+        unsafe {
+            // Safety: we only use the `DirectSlot` until the function returns, and we know the
+            // direct slot cannot be invalidated until then because we never call something which
+            // could potentially destroy the heap.
+            self.direct_slot(token).version(token)
+        }
+    }
+}
+
+impl<T> Slot<T> {
+    pub fn erase(self) -> ErasedSlot {
+        ErasedSlot {
+            ty: NamedTypeId::of::<T>(),
+            indirector: self.indirector,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct ErasedSlot {
+    ty: NamedTypeId,
+    indirector: &'static Indirector,
+}
+
+impl fmt::Debug for ErasedSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErasedSlot")
+            .field("ty", &self.ty)
+            .field("indirector", &(self.indirector as *const Indirector))
+            .finish_non_exhaustive()
+    }
+}
+
+impl ErasedSlot {
+    pub fn type_id(self) -> TypeId {
+        self.ty.raw()
+    }
+
+    pub fn owner(self, token: &impl Token) -> Option<Entity> {
+        self.indirector
+            .owner
+            .get(token)
+            .map(|ent| ent.into_dangerous_entity())
+    }
+
+    pub fn downcast<T: 'static>(self) -> Option<Slot<T>> {
+        // N.B. the indirector is only valid to interpret as a `Slot<T>` for the `T` it was created
+        // with.
+        (self.type_id() == TypeId::of::<T>()).then_some(Slot {
+            _ty: PhantomData,
+            indirector: self.indirector,
+        })
+    }
 }
@@ -320,10 +320,23 @@ thread_local! {
     static IS_MAIN_THREAD: Cell<bool> = const { Cell::new(false) };
 }
 
+/// Returns whether the current thread has become the main thread. A thread becomes the main
+/// thread the first time it acquires a [`MainThreadToken`] while no other thread has, so this
+/// returns `false` on every thread until some thread does so.
 pub fn is_main_thread() -> bool {
     IS_MAIN_THREAD.with(|v| v.get())
 }
 
+/// Asserts that the current thread is the main thread, making it the main thread if no thread has
+/// been blessed yet.
+///
+/// This panics with the same message as [`MainThreadToken::acquire_fmt`] when called from any other
+/// thread. `attempted_verb` completes the sentence "Attempted to ... on non-main thread".
+#[track_caller]
+pub fn assert_main_thread(attempted_verb: &str) {
+    let _ = MainThreadToken::acquire_fmt(attempted_verb);
+}
+
 #[must_use]
 fn try_become_main_thread() -> bool {
     if is_main_thread() {
@@ -365,6 +378,7 @@ impl MainThreadToken {
         }
     }
 
+    #[track_caller]
     pub fn acquire_fmt(attempted_verb: &str) -> &'static Self {
         assert!(
             try_become_main_thread(),
@@ -625,3 +639,33 @@ unsafe impl<T: ?Sized + 'static> TokenFor<T> for TypeSharedToken<'_, T> {
 }
 
 impl<T: ?Sized + 'static> SharedTokenHint<T> for TypeSharedToken<'_, T> {}
+
+// === Tests === //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::testing::run_on_main_thread;
+
+    #[test]
+    fn only_the_main_thread_passes_main_thread_assertions() {
+        run_on_main_thread(|| {
+            assert_main_thread("run a main-thread test");
+            assert!(is_main_thread());
+
+            let worker = thread::spawn(|| {
+                assert!(!is_main_thread());
+                assert_main_thread("run a main-thread test from a worker");
+            });
+
+            let payload = worker.join().unwrap_err();
+            let message = payload.downcast_ref::<String>().unwrap();
+            assert!(message.starts_with(
+                "Attempted to run a main-thread test from a worker on non-main thread."
+            ));
+
+            // The failed assertion didn't steal the main thread away.
+            assert!(is_main_thread());
+        });
+    }
+}
@@ -1,4 +1,4 @@
-use std::{cell::Cell, fmt};
+use std::{cell::Cell, fmt, ops::Range};
 
 use autoken::{
     ImmutableBorrow, MutableBorrow, Nothing, PotentialImmutableBorrow, PotentialMutableBorrow,
@@ -8,8 +8,8 @@ use crate::util::misc::NOT_ON_MAIN_THREAD_MSG;
 
 use super::{
     cell::{
-        BorrowError, BorrowMutError, MultiOptRef, MultiOptRefCell, MultiOptRefMut,
-        MultiRefCellIndex, OptRef, OptRefCell, OptRefMut,
+        BorrowError, BorrowMutError, MultiOptRangeRef, MultiOptRef, MultiOptRefCell,
+        MultiOptRefMut, MultiRefCellIndex, OptRef, OptRefCell, OptRefMut,
     },
     token::{
         is_main_thread, BorrowMutToken, BorrowToken, GetToken, MainThreadToken, ThreadAccess,
@@ -499,6 +499,15 @@ impl<T> NMultiOptRefCell<T> {
         self.value.is_empty(i)
     }
 
+    #[cfg(feature = "component-versions")]
+    pub fn version(&self, token: &impl TokenFor<T>, i: MultiRefCellIndex) -> u64 {
+        self.assert_accessible_by(token, None);
+
+        // Safety: versions are only bumped by mutable borrows, which cannot happen concurrently
+        // with this token.
+        self.value.version(i)
+    }
+
     pub fn is_empty_mut(&mut self, i: MultiRefCellIndex) -> bool {
         // Safety: this is a method that takes exclusive access to the object. Hence, it is
         // not impacted by our potentially dangerous `Sync` impl.
@@ -695,6 +704,18 @@ impl<T> NMultiOptRefCell<T> {
         self.value.try_borrow_all(loaner)
     }
 
+    pub fn try_borrow_range<'a>(
+        &'a self,
+        token: &'a impl BorrowToken<T>,
+        range: Range<usize>,
+        loaner: &'a PotentialImmutableBorrow<T>,
+    ) -> Option<MultiOptRangeRef<'a, T>> {
+        self.assert_accessible_by(token, Some(ThreadAccess::Exclusive));
+
+        // Safety: see `try_borrow`.
+        self.value.try_borrow_range(range, loaner)
+    }
+
     pub fn try_borrow_all_mut<'a>(
         &'a self,
         token: &'a impl BorrowMutToken<T>,
@@ -19,7 +19,7 @@ use crate::{
         token::{MainThreadToken, TrivialUnjailToken},
         token_cell::{NMainCell, NOptRefCell},
     },
-    debug::DebugLabel,
+    debug::{DebugLabel, StorageStat},
     entity::Entity,
     query::{ArchetypeId, RawTag},
     util::{
@@ -28,7 +28,7 @@ use crate::{
         hash_map::{ConstSafeBuildHasherDefault, FxHashMap, FxHashSet, NopHashMap},
         iter::{filter_duplicates, merge_iters},
         misc::{const_new_nz_u64, leak, unpoison, xorshift64, AnyDowncastExt, NamedTypeId, RawFmt},
-        set_map::{SetMap, SetMapAbaPtr, SetMapArena, SetMapCheckedPtr},
+        set_map::{SetMap, SetMapAbaPtr, SetMapArena, SetMapCheckedPtr, SetMapStats},
     },
 };
 
@@ -36,12 +36,26 @@ use crate::{
 
 const POSSIBLY_A_PLACEHOLDER: RawFmt = RawFmt("<possibly a placeholder>");
 
+const MIN_ENTITY_HEAP_CAP: usize = 128;
+
+// The seed from which entity IDs are generated in deterministic mode. Both modes walk the same
+// xorshift sequence so deterministic IDs may well coincide with IDs handed out before the mode was
+// enabled, which is why enabling it requires every entity to have been destroyed and flushed.
+const DETERMINISTIC_ENTITY_UID_SEED: NonZeroU64 = const_new_nz_u64(0x9E37_79B9_7F4A_7C15);
+
 // === Root === //
 
 #[derive(Debug)]
 pub struct DbRoot {
-    // The last unique ID to have been generated.
-    uid_gen: NonZeroU64,
+    // The last unique entity ID to have been generated.
+    entity_uid_gen: NonZeroU64,
+
+    // The last unique tag ID to have been generated. Tags get their own generator so that lazily
+    // creating a global tag doesn't shift the IDs of every entity spawned afterwards.
+    tag_uid_gen: NonZeroU64,
+
+    // The state of `entity_uid_gen` before deterministic IDs were enabled, if they are enabled.
+    nondeterministic_entity_uid_gen: Option<NonZeroU64>,
 
     // A map from alive entity ID to its state.
     alive_entities: NopHashMap<InertEntity, DbEntity>,
@@ -72,6 +86,10 @@ pub struct DbRoot {
     // The number of flushes performed on this database.
     total_flush_count: u64,
 
+    // Whether flushes should close the gaps left in archetypes by shifting entities down rather
+    // than by swap-removing them so that entities stay in the order they entered the archetype.
+    preserve_archetype_order: bool,
+
     // A guard to protect against flushing while querying. This doesn't prevent panics but it does
     // prevent nasty concurrent modification surprises.
     query_guard: &'static NOptRefCell<RecursiveQueryGuardTy>,
@@ -138,16 +156,28 @@ trait DbAnyStorage: fmt::Debug + Sync {
         heap_count: usize,
     );
 
+    fn warm_archetype_heap(
+        &self,
+        token: &'static MainThreadToken,
+        arch: DbArchetypeAbaPtr,
+        heap_len: usize,
+    );
+
     fn contains_entity(&self, storage: &'static MainThreadToken, entity: InertEntity) -> bool;
+
+    fn debug_stats(&self, token: &'static MainThreadToken) -> StorageStat;
 }
 
 pub type DbStorage<T> = NOptRefCell<DbStorageInner<T>>;
 
+pub type EntityHeap = Arc<[NMainCell<InertEntity>]>;
+
 #[derive_where(Debug)]
 pub struct DbStorageInner<T: 'static> {
     anon_block_alloc: BlockAllocator<Heap<T>>,
     mappings: NopHashMap<InertEntity, DbEntityMapping<T>>,
     heaps: FxHashMap<DbArchetypeAbaPtr, Vec<Arc<Heap<T>>>>,
+    on_remove: Vec<fn(Entity, &mut T)>,
 }
 
 struct DbEntityMapping<T: 'static> {
@@ -196,21 +226,29 @@ impl DbComponentType {
             token: &'static MainThreadToken,
             entity: InertEntity,
         ) {
-            let comp = {
+            let (comp, hooks) = {
                 let mut db = DbRoot::get(token);
                 let storage = db.get_storage::<T>(token);
+                let hooks = DbRoot::get_on_remove_hooks(&storage.borrow(token));
 
                 // FIXME: AuToken doesn't really know how to handle the interaction between option
                 // pattern matching and its destructor and falsely reports that this function could
                 // drop components. Fixing each individual instance of this would be pretty involved
                 // so we're just ignoring the entire function for now and waiting for AuToken to fix
                 // this issue.
-                autoken::assume_black_box(|| {
+                let comp = autoken::assume_black_box(|| {
                     db.remove_component(token, &mut storage.borrow_mut(token), entity)
-                })
+                });
+
+                (comp, hooks)
             };
             debug_assert!(comp.is_ok());
-            drop(comp);
+
+            if let Ok(Some(mut comp)) = comp {
+                for hook in hooks {
+                    hook(entity.into_dangerous_entity(), &mut comp);
+                }
+            }
         }
 
         Self {
@@ -258,8 +296,10 @@ struct DbArchetype {
     managed: FxHashSet<NamedTypeId>,
     managed_sorted: Box<[NamedTypeId]>,
     entity_heaps: Vec<Arc<[NMainCell<InertEntity>]>>,
+    spare_entity_heap: Option<Arc<[NMainCell<InertEntity>]>>,
     last_heap_len: usize,
     virtual_count: u64,
+    is_pinned: bool,
 }
 
 impl DbArchetype {
@@ -276,8 +316,10 @@ impl DbArchetype {
             managed: FxHashSet::from_iter(managed_sorted.iter().copied()),
             managed_sorted,
             entity_heaps: Vec::new(),
+            spare_entity_heap: None,
             last_heap_len: 0,
             virtual_count: 0,
+            is_pinned: false,
         }
     }
 }
@@ -364,7 +406,9 @@ impl InertArchetypeId {
 impl Default for DbRoot {
     fn default() -> Self {
         Self {
-            uid_gen: NonZeroU64::new(1).unwrap(),
+            entity_uid_gen: NonZeroU64::new(1).unwrap(),
+            tag_uid_gen: NonZeroU64::new(1).unwrap(),
+            nondeterministic_entity_uid_gen: None,
             alive_entities: NopHashMap::default(),
             comp_list_map: SetMap::default(),
             arch_map: SetMap::new(DbArchetype::new(&[])),
@@ -374,6 +418,7 @@ impl Default for DbRoot {
             dead_dirty_entities: Vec::new(),
             debug_total_spawns: 0,
             total_flush_count: 0,
+            preserve_archetype_order: false,
             query_guard: leak(NOptRefCell::new_full(
                 &TrivialUnjailToken,
                 RecursiveQueryGuardTy,
@@ -394,16 +439,12 @@ impl DbRoot {
         DB.borrow_mut(token)
     }
 
-    fn new_uid(&mut self) -> NonZeroU64 {
-        self.uid_gen = xorshift64(self.uid_gen);
-        self.uid_gen
-    }
-
     // === Entity management === //
 
     pub fn spawn_entity(&mut self) -> InertEntity {
         // Allocate a slot
-        let me = InertEntity(self.new_uid());
+        self.entity_uid_gen = xorshift64(self.entity_uid_gen);
+        let me = InertEntity(self.entity_uid_gen);
 
         // Register our slot in the alive set
         self.alive_entities.insert(
@@ -454,6 +495,7 @@ impl DbRoot {
                 Self::rec_remove_stepping_stone_arches(
                     &mut self.arch_map,
                     &mut self.tag_map,
+                    &mut FxHashSet::default(),
                     entity_info.virtual_arch,
                 );
             }
@@ -466,6 +508,11 @@ impl DbRoot {
         self.alive_entities.contains_key(&entity)
     }
 
+    pub fn find_alive_entity(&self, id: u64) -> Option<InertEntity> {
+        let entity = InertEntity(NonZeroU64::new(id)?);
+        self.is_entity_alive(entity).then_some(entity)
+    }
+
     pub fn get_entity_physical_and_virtual_arches(
         &self,
         entity: InertEntity,
@@ -478,9 +525,52 @@ impl DbRoot {
         })
     }
 
+    pub fn get_entity_virtual_tags(
+        &self,
+        entity: InertEntity,
+    ) -> Result<Vec<InertTag>, EntityDeadError> {
+        let Some(info) = self.alive_entities.get(&entity) else {
+            return Err(EntityDeadError);
+        };
+
+        Ok(self
+            .arch_map
+            .arena()
+            .get_aba(&info.virtual_arch)
+            .keys()
+            .to_vec())
+    }
+
+    pub fn set_deterministic_entity_ids(
+        &mut self,
+        enabled: bool,
+    ) -> Result<(), DeterministicIdsBlockedError> {
+        if enabled {
+            let pending_flush = !self.probably_alive_dirty_entities.is_empty()
+                || !self.dead_dirty_entities.is_empty();
+
+            if !self.alive_entities.is_empty() || pending_flush {
+                return Err(DeterministicIdsBlockedError {
+                    alive: self.alive_entities.len(),
+                    pending_flush,
+                });
+            }
+
+            self.nondeterministic_entity_uid_gen
+                .get_or_insert(self.entity_uid_gen);
+            self.entity_uid_gen = DETERMINISTIC_ENTITY_UID_SEED;
+        } else if let Some(uid_gen) = self.nondeterministic_entity_uid_gen.take() {
+            self.entity_uid_gen = uid_gen;
+        }
+
+        Ok(())
+    }
+
     pub fn spawn_tag(&mut self, ty: NamedTypeId) -> InertTag {
+        self.tag_uid_gen = xorshift64(self.tag_uid_gen);
+
         InertTag {
-            id: self.new_uid(),
+            id: self.tag_uid_gen,
             ty,
         }
     }
@@ -490,7 +580,7 @@ impl DbRoot {
     fn tag_common(
         &mut self,
         entity: InertEntity,
-        tag: InertTag,
+        tags: impl IntoIterator<Item = InertTag>,
         is_add: bool,
     ) -> Result<(), EntityDeadError> {
         // Fetch the entity info
@@ -511,7 +601,7 @@ impl DbRoot {
         }
 
         // Update the list
-        let post_ctor = |arena: &mut DbArchetypeArena, target_ptr: &DbArchetypeAbaPtr| {
+        let mut post_ctor = |arena: &mut DbArchetypeArena, target_ptr: &DbArchetypeAbaPtr| {
             let target = arena.get_aba(target_ptr);
 
             for tag in target.keys() {
@@ -525,21 +615,23 @@ impl DbRoot {
 
         let old_virtual_arch = entity_info.virtual_arch;
 
-        entity_info.virtual_arch = if is_add {
-            self.arch_map.lookup_extension(
-                Some(&entity_info.virtual_arch),
-                tag,
-                DbArchetype::new,
-                post_ctor,
-            )
-        } else {
-            self.arch_map.lookup_de_extension(
-                &entity_info.virtual_arch,
-                tag,
-                DbArchetype::new,
-                post_ctor,
-            )
-        };
+        for tag in tags {
+            entity_info.virtual_arch = if is_add {
+                self.arch_map.lookup_extension(
+                    Some(&entity_info.virtual_arch),
+                    tag,
+                    DbArchetype::new,
+                    &mut post_ctor,
+                )
+            } else {
+                self.arch_map.lookup_de_extension(
+                    &entity_info.virtual_arch,
+                    tag,
+                    DbArchetype::new,
+                    &mut post_ctor,
+                )
+            };
+        }
 
         // Increment new virtual counter
         if &entity_info.virtual_arch != self.arch_map.root() {
@@ -555,6 +647,7 @@ impl DbRoot {
             Self::rec_remove_stepping_stone_arches(
                 &mut self.arch_map,
                 &mut self.tag_map,
+                &mut FxHashSet::default(),
                 old_virtual_arch,
             );
         }
@@ -572,12 +665,77 @@ impl DbRoot {
         Ok(())
     }
 
+    pub fn warm_archetype(
+        &mut self,
+        token: &'static MainThreadToken,
+        tags: impl IntoIterator<Item = InertTag>,
+    ) {
+        // Find or create the archetype.
+        let mut arch_id = *self.arch_map.root();
+
+        for tag in tags {
+            arch_id = self.arch_map.lookup_extension(
+                Some(&arch_id),
+                tag,
+                DbArchetype::new,
+                |arena, target_ptr| {
+                    for tag in arena.get_aba(target_ptr).keys() {
+                        let tag_state = self.tag_map.entry(*tag).or_insert_with(Default::default);
+
+                        debug_assert!(!tag_state.sorted_containers.contains(target_ptr));
+                        tag_state.sorted_containers.push(*target_ptr);
+                        tag_state.are_sorted_containers_sorted = false;
+                    }
+                },
+            );
+        }
+
+        // The root archetype doesn't manage any heaps.
+        if arch_id == *self.arch_map.root() {
+            return;
+        }
+
+        let arch = self.arch_map.arena_mut().get_aba_mut(&arch_id).value_mut();
+        arch.is_pinned = true;
+
+        // Archetypes which already have entities in them have nothing to warm up.
+        if !arch.entity_heaps.is_empty() {
+            return;
+        }
+
+        // Reserve the first entity heap...
+        arch.spare_entity_heap.get_or_insert_with(|| {
+            Arc::from_iter(
+                (0..MIN_ENTITY_HEAP_CAP).map(|_| NMainCell::new(InertEntity::PLACEHOLDER)),
+            )
+        });
+
+        // ...and the first heap of every component it manages.
+        for managed_ty in &arch.managed {
+            let Some(storage) = self.storages.get(managed_ty) else {
+                // We can only reserve heaps for components whose storage already exists.
+                continue;
+            };
+
+            storage.warm_archetype_heap(token, arch_id, MIN_ENTITY_HEAP_CAP);
+        }
+    }
+
     pub fn tag_entity(
         &mut self,
         entity: InertEntity,
         tag: InertTag,
     ) -> Result<(), EntityDeadError> {
-        self.tag_common(entity, tag, true)
+        self.tag_common(entity, [tag], true)
+    }
+
+    /// Adds every tag in `tags` to `entity` as a single archetype transition.
+    pub fn tag_entity_many(
+        &mut self,
+        entity: InertEntity,
+        tags: impl IntoIterator<Item = InertTag>,
+    ) -> Result<(), EntityDeadError> {
+        self.tag_common(entity, tags, true)
     }
 
     pub fn untag_entity(
@@ -585,7 +743,7 @@ impl DbRoot {
         entity: InertEntity,
         tag: InertTag,
     ) -> Result<(), EntityDeadError> {
-        self.tag_common(entity, tag, false)
+        self.tag_common(entity, [tag], false)
     }
 
     pub fn is_entity_tagged_virtual(
@@ -604,6 +762,26 @@ impl DbRoot {
             .has_key(&tag))
     }
 
+    pub fn is_entity_tagged_virtual_many(
+        &self,
+        entity: InertEntity,
+        tags: impl IntoIterator<Item = InertTag>,
+        require_all: bool,
+    ) -> Result<bool, EntityDeadError> {
+        let Some(entity_info) = self.alive_entities.get(&entity) else {
+            return Err(EntityDeadError);
+        };
+
+        let arch = self.arch_map.arena().get_aba(&entity_info.virtual_arch);
+        let mut tags = tags.into_iter();
+
+        Ok(if require_all {
+            tags.all(|tag| arch.has_key(&tag))
+        } else {
+            tags.any(|tag| arch.has_key(&tag))
+        })
+    }
+
     pub fn is_entity_tagged_physical(
         &self,
         entity: InertEntity,
@@ -626,6 +804,10 @@ impl DbRoot {
         self.total_flush_count
     }
 
+    pub fn set_preserve_archetype_order(&mut self, enabled: bool) {
+        self.preserve_archetype_order = enabled;
+    }
+
     pub fn borrow_query_guard(
         &self,
         token: &'static MainThreadToken,
@@ -724,19 +906,31 @@ impl DbRoot {
         let _guard = self
             .query_guard
             .try_borrow_mut(token, &mut guard_loaner)
-            .map_err(|_| ConcurrentFlushError)?;
+            .map_err(|err| ConcurrentFlushError {
+                active_queries: err.reader_count(),
+            })?;
 
         self.total_flush_count += 1;
 
         let mut may_need_truncation = FxHashSet::default();
         let mut may_need_arch_deletion = FxHashSet::default();
 
+        // When preserving archetype order, vacated slots are left in place until every entity has
+        // been moved and are then closed up in a single pass per archetype.
+        let preserve_order = self.preserve_archetype_order;
+        let mut needs_compaction = FxHashSet::default();
+
         // Begin by removing dead entities.
         'delete_dead: for info in mem::take(&mut self.dead_dirty_entities) {
             // We know this won't happen because we check for it before adding the entity to the
             // `dead_dirty_entities` list.
             debug_assert_ne!(info.physical_arch, *self.arch_map.root());
 
+            if preserve_order {
+                needs_compaction.insert(info.physical_arch);
+                continue;
+            }
+
             // Determine the archetype we'll be working on.
             let arch_id = info.physical_arch;
             let arch = self.arch_map.arena_mut().get_aba_mut(&arch_id).value_mut();
@@ -888,10 +1082,17 @@ impl DbRoot {
                     let last_heap_cap = dst_arch.entity_heaps.last().map_or(0, |heap| heap.len());
 
                     if dst_arch.last_heap_len == last_heap_cap {
-                        let sub_heap_cap = (last_heap_cap * 2).max(128);
-                        let sub_heap = Arc::from_iter(
-                            (0..sub_heap_cap).map(|_| NMainCell::new(InertEntity::PLACEHOLDER)),
-                        );
+                        let sub_heap_cap = (last_heap_cap * 2).max(MIN_ENTITY_HEAP_CAP);
+                        let sub_heap = dst_arch
+                            .spare_entity_heap
+                            .take()
+                            .filter(|heap| heap.len() == sub_heap_cap)
+                            .unwrap_or_else(|| {
+                                Arc::from_iter(
+                                    (0..sub_heap_cap)
+                                        .map(|_| NMainCell::new(InertEntity::PLACEHOLDER)),
+                                )
+                            });
                         sub_heap[0].set(token, target);
 
                         dst_arch.entity_heaps.push(sub_heap);
@@ -948,7 +1149,9 @@ impl DbRoot {
                 let root_arch_id = *self.arch_map.root();
 
                 // The root archetype doesn't manage any heaps so don't have to manage anything in it.
-                if src_arch_id != root_arch_id {
+                if src_arch_id != root_arch_id && preserve_order {
+                    needs_compaction.insert(src_arch_id);
+                } else if src_arch_id != root_arch_id {
                     let src_arch = self
                         .arch_map
                         .arena_mut()
@@ -1020,6 +1223,16 @@ impl DbRoot {
             }
         }
 
+        // Close the gaps left in order-preserving archetypes.
+        for arch_id in needs_compaction {
+            self.compact_archetype(
+                token,
+                arch_id,
+                &mut may_need_truncation,
+                &mut may_need_arch_deletion,
+            );
+        }
+
         // Truncate storages which need it
         for arch_id in may_need_truncation {
             let arch = self.arch_map.arena().get_aba(&arch_id).value();
@@ -1033,20 +1246,128 @@ impl DbRoot {
         }
 
         // Destroy archetypes which no longer exist.
-        for arch_id in may_need_arch_deletion {
+        while let Some(&arch_id) = may_need_arch_deletion.iter().next() {
+            may_need_arch_deletion.remove(&arch_id);
             debug_assert_ne!(&arch_id, self.arch_map.root());
 
             if !Self::can_remove_archetype(&self.arch_map, arch_id) {
                 continue;
             }
 
-            // Remove the archetype from the map
-            Self::rec_remove_stepping_stone_arches(&mut self.arch_map, &mut self.tag_map, arch_id);
+            // Remove the archetype from the map. Stepping stones removed along the way are dropped
+            // from the set so we don't visit their dead slots later.
+            Self::rec_remove_stepping_stone_arches(
+                &mut self.arch_map,
+                &mut self.tag_map,
+                &mut may_need_arch_deletion,
+                arch_id,
+            );
         }
 
         Ok(())
     }
 
+    fn compact_archetype(
+        &mut self,
+        token: &'static MainThreadToken,
+        arch_id: DbArchetypeAbaPtr,
+        may_need_truncation: &mut FxHashSet<DbArchetypeAbaPtr>,
+        may_need_arch_deletion: &mut FxHashSet<DbArchetypeAbaPtr>,
+    ) {
+        let arch = self.arch_map.arena_mut().get_aba_mut(&arch_id).value_mut();
+        let heap_count = arch.entity_heaps.len();
+
+        // The position of the next slot to be filled.
+        let mut write_heap = 0;
+        let mut write_slot = 0;
+
+        for read_heap in 0..heap_count {
+            let read_len = if read_heap == heap_count - 1 {
+                arch.last_heap_len
+            } else {
+                arch.entity_heaps[read_heap].len()
+            };
+
+            for read_slot in 0..read_len {
+                let entity = arch.entity_heaps[read_heap][read_slot].get(token);
+
+                // Skip over dead entities and entities which have since moved to another archetype.
+                let Some(entity_info) = self.alive_entities.get_mut(&entity) else {
+                    continue;
+                };
+
+                if entity_info.physical_arch != arch_id
+                    || entity_info.heap_index != read_heap
+                    || entity_info.slot_index != read_slot
+                {
+                    continue;
+                }
+
+                // Shift the entity down into the earliest vacant slot.
+                if (write_heap, write_slot) != (read_heap, read_slot) {
+                    arch.entity_heaps[write_heap][write_slot].set(token, entity);
+                    entity_info.heap_index = write_heap;
+                    entity_info.slot_index = write_slot;
+
+                    for &managed_ty in &arch.managed {
+                        let Some(storage) = self.storages.get(&managed_ty) else {
+                            continue;
+                        };
+
+                        storage.move_entity_into_empty_never_truncate(
+                            token,
+                            entity,
+                            entity_info,
+                            arch_id,
+                            arch,
+                        );
+                    }
+                }
+
+                write_slot += 1;
+
+                if write_slot == arch.entity_heaps[write_heap].len() {
+                    write_heap += 1;
+                    write_slot = 0;
+                }
+            }
+        }
+
+        // Truncate the now-vacant tail of the archetype.
+        #[cfg(debug_assertions)]
+        {
+            let mut start = write_slot;
+
+            for heap in arch.entity_heaps.iter().skip(write_heap) {
+                for cell in &heap[start..] {
+                    cell.set(token, InertEntity::PLACEHOLDER);
+                }
+                start = 0;
+            }
+        }
+
+        let new_heap_count = if write_slot == 0 {
+            write_heap
+        } else {
+            write_heap + 1
+        };
+
+        if new_heap_count < heap_count {
+            arch.entity_heaps.truncate(new_heap_count);
+            may_need_truncation.insert(arch_id);
+        }
+
+        arch.last_heap_len = match arch.entity_heaps.last() {
+            Some(last_heap) if write_slot == 0 => last_heap.len(),
+            Some(_) => write_slot,
+            None => 0,
+        };
+
+        if arch.entity_heaps.is_empty() {
+            may_need_arch_deletion.insert(arch_id);
+        }
+    }
+
     fn can_remove_archetype(arch_map: &DbArchetypeMap, arch_id: DbArchetypeAbaPtr) -> bool {
         // We can't remove the root.
         if &arch_id == arch_map.root() {
@@ -1061,6 +1382,11 @@ impl DbRoot {
             return false;
         }
 
+        // We can't remove archetypes which have been warmed up by the user.
+        if arch.is_pinned {
+            return false;
+        }
+
         // We shouldn't remove archetypes who are used as stepping stones to other archetypes.
         if arch_entry
             .extensions()
@@ -1077,11 +1403,13 @@ impl DbRoot {
     fn rec_remove_stepping_stone_arches(
         arch_map: &mut DbArchetypeMap,
         tag_map: &mut NopHashMap<InertTag, DbTag>,
+        pending: &mut FxHashSet<DbArchetypeAbaPtr>,
         arch_id: DbArchetypeAbaPtr,
     ) {
         debug_assert!(Self::can_remove_archetype(arch_map, arch_id));
 
         let arch = arch_map.remove(arch_id);
+        pending.remove(&arch_id);
 
         for tag in arch.value().tags.iter().copied() {
             let HmEntry::Occupied(mut entry) = tag_map.entry(tag) else {
@@ -1097,7 +1425,7 @@ impl DbRoot {
 
         for src in arch.de_extensions().values() {
             if *src != arch_id && Self::can_remove_archetype(arch_map, *src) {
-                Self::rec_remove_stepping_stone_arches(arch_map, tag_map, *src);
+                Self::rec_remove_stepping_stone_arches(arch_map, tag_map, pending, *src);
             }
         }
     }
@@ -1117,6 +1445,7 @@ impl DbRoot {
                         anon_block_alloc: BlockAllocator::default(),
                         mappings: NopHashMap::default(),
                         heaps: FxHashMap::default(),
+                        on_remove: Vec::new(),
                     },
                 ))
             })
@@ -1272,6 +1601,70 @@ impl DbRoot {
         storage.mappings.get(&entity).map(|mapping| mapping.slot)
     }
 
+    pub fn component_count<T: 'static>(storage: &DbStorageInner<T>) -> usize {
+        storage.mappings.len()
+    }
+
+    pub fn reserve_components<T: 'static>(
+        token: &'static MainThreadToken,
+        storage: &mut DbStorageInner<T>,
+        additional: usize,
+    ) {
+        storage
+            .anon_block_alloc
+            .reserve(additional, |sz| Heap::new(token, sz));
+    }
+
+    pub fn shrink_components<T: 'static>(storage: &mut DbStorageInner<T>) {
+        storage.anon_block_alloc.shrink_to_fit(drop);
+        storage.mappings.shrink_to_fit();
+        storage.heaps.shrink_to_fit();
+    }
+
+    pub fn component_heaps<T: 'static>(
+        storage: &DbStorageInner<T>,
+    ) -> impl Iterator<Item = &Heap<T>> + '_ {
+        storage
+            .anon_block_alloc
+            .blocks()
+            .chain(storage.heaps.values().flatten().map(|heap| &**heap))
+    }
+
+    pub fn anonymous_component_heaps<T: 'static>(
+        storage: &DbStorageInner<T>,
+    ) -> impl Iterator<Item = &Heap<T>> + '_ {
+        storage.anon_block_alloc.blocks()
+    }
+
+    pub fn archetype_component_heaps<T: 'static>(
+        &self,
+        storage: &DbStorageInner<T>,
+    ) -> Vec<(Arc<Heap<T>>, EntityHeap)> {
+        let arena = self.arch_map.arena();
+
+        storage
+            .heaps
+            .iter()
+            .flat_map(|(arch, heaps)| {
+                let arch = arena.get_aba(arch).value();
+                heaps.iter().cloned().zip(arch.entity_heaps.iter().cloned())
+            })
+            .collect()
+    }
+
+    pub fn add_on_remove_hook<T: 'static>(
+        storage: &mut DbStorageInner<T>,
+        hook: fn(Entity, &mut T),
+    ) {
+        storage.on_remove.push(hook);
+    }
+
+    pub fn get_on_remove_hooks<T: 'static>(storage: &DbStorageInner<T>) -> Vec<fn(Entity, &mut T)> {
+        // N.B. hooks are returned by value so that they can be run once the storage is no longer
+        // borrowed.
+        storage.on_remove.clone()
+    }
+
     pub fn entity_has_component_dyn(
         &self,
         token: &'static MainThreadToken,
@@ -1297,6 +1690,46 @@ impl DbRoot {
         self.arch_map.len() as u64
     }
 
+    pub fn debug_archetype_graph_stats(&self) -> SetMapStats {
+        self.arch_map.stats()
+    }
+
+    pub fn debug_archetypes(&self) -> Vec<DebugArchetypeInfo> {
+        let mut archetypes = FxHashMap::<DbArchetypeAbaPtr, DebugArchetypeInfo>::default();
+
+        for entity_info in self.alive_entities.values() {
+            let arch = archetypes
+                .entry(entity_info.virtual_arch)
+                .or_insert_with(|| DebugArchetypeInfo {
+                    tags: self
+                        .arch_map
+                        .arena()
+                        .get_aba(&entity_info.virtual_arch)
+                        .keys()
+                        .to_vec(),
+                    entity_count: 0,
+                    components: Vec::new(),
+                });
+
+            arch.entity_count += 1;
+
+            for comp in entity_info.comp_list.direct_borrow().keys().iter() {
+                if !arch.components.contains(&comp.name) {
+                    arch.components.push(comp.name);
+                }
+            }
+        }
+
+        archetypes.into_values().collect()
+    }
+
+    pub fn debug_storage_stats(&self, token: &'static MainThreadToken) -> Vec<StorageStat> {
+        self.storages
+            .values()
+            .map(|storage| storage.debug_stats(token))
+            .collect()
+    }
+
     pub fn debug_format_entity(
         &mut self,
         f: &mut fmt::Formatter,
@@ -1470,15 +1903,43 @@ impl<T: 'static> DbAnyStorage for DbStorage<T> {
         }
     }
 
+    fn warm_archetype_heap(
+        &self,
+        token: &'static MainThreadToken,
+        arch: DbArchetypeAbaPtr,
+        heap_len: usize,
+    ) {
+        self.borrow_mut(token)
+            .heaps
+            .entry(arch)
+            .or_insert_with(|| vec![Arc::new(Heap::new(token, heap_len))]);
+    }
+
     fn contains_entity(&self, token: &'static MainThreadToken, entity: InertEntity) -> bool {
         self.borrow(token).mappings.contains_key(&entity)
     }
+
+    fn debug_stats(&self, token: &'static MainThreadToken) -> StorageStat {
+        let storage = self.borrow(token);
+
+        let capacity = DbRoot::component_heaps(&storage)
+            .map(Heap::len)
+            .sum::<usize>();
+
+        StorageStat {
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+            live_slots: storage.mappings.len(),
+            capacity,
+            bytes: mem::size_of::<T>() * capacity,
+        }
+    }
 }
 
-pub fn get_global_tag(id: NamedTypeId, managed_ty: NamedTypeId) -> RawTag {
-    static TAGS: Mutex<FxHashMap<NamedTypeId, RawTag>> =
-        Mutex::new(FxHashMap::with_hasher(ConstSafeBuildHasherDefault::new()));
+static GLOBAL_TAGS: Mutex<FxHashMap<NamedTypeId, (&'static str, RawTag)>> =
+    Mutex::new(FxHashMap::with_hasher(ConstSafeBuildHasherDefault::new()));
 
+pub fn get_global_tag(id: NamedTypeId, name: &'static str, managed_ty: NamedTypeId) -> RawTag {
     thread_local! {
         static TAG_CACHE: RefCell<FxHashMap<NamedTypeId, RawTag>> = const {
             RefCell::new(FxHashMap::with_hasher(ConstSafeBuildHasherDefault::new()))
@@ -1487,20 +1948,43 @@ pub fn get_global_tag(id: NamedTypeId, managed_ty: NamedTypeId) -> RawTag {
 
     TAG_CACHE.with(|cache| {
         *cache.borrow_mut().entry(id).or_insert_with(|| {
-            *unpoison(TAGS.lock())
+            unpoison(GLOBAL_TAGS.lock())
                 .entry(id)
-                .or_insert_with(|| RawTag::new(managed_ty))
+                .or_insert_with(|| (name, RawTag::new(managed_ty)))
+                .1
         })
     })
 }
 
+pub fn find_global_tag_by_name(name: &str) -> Option<RawTag> {
+    unpoison(GLOBAL_TAGS.lock())
+        .values()
+        .find(|(tag_name, _)| *tag_name == name)
+        .map(|&(_, tag)| tag)
+}
+
+pub fn find_global_tag_name(tag: RawTag) -> Option<&'static str> {
+    unpoison(GLOBAL_TAGS.lock())
+        .values()
+        .find(|&&(_, other)| other == tag)
+        .map(|&(name, _)| name)
+}
+
 // === Public helpers === //
 
 #[derive(Debug)]
 pub struct EntityDeadError;
 
 #[derive(Debug)]
-pub struct ConcurrentFlushError;
+pub struct DeterministicIdsBlockedError {
+    pub alive: usize,
+    pub pending_flush: bool,
+}
+
+#[derive(Debug)]
+pub struct ConcurrentFlushError {
+    pub active_queries: usize,
+}
 
 #[derive(Debug, Clone)]
 pub struct InertArchetypeQueryInfo<'a> {
@@ -1509,6 +1993,13 @@ pub struct InertArchetypeQueryInfo<'a> {
     pub entities: &'a Vec<Arc<[NMainCell<InertEntity>]>>,
 }
 
+#[derive(Debug, Clone)]
+pub struct DebugArchetypeInfo {
+    pub tags: Vec<InertTag>,
+    pub entity_count: usize,
+    pub components: Vec<&'static str>,
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct ReifiedTagList<'a> {
     pub static_tags: &'a [Option<InertTag>],
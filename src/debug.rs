@@ -1,4 +1,13 @@
-use std::{borrow::Cow, fmt, sync::atomic};
+use std::{
+    any::{type_name, TypeId},
+    borrow::Cow,
+    fmt, mem,
+    panic::Location,
+    sync::{
+        atomic::{self, AtomicBool},
+        Mutex,
+    },
+};
 
 use crate::{
     core::{
@@ -7,8 +16,15 @@ use crate::{
     },
     database::{DbRoot, InertEntity},
     entity::Entity,
+    query::{DEBUG_QUERY_COUNTER, DEBUG_QUERY_FALLBACK_COUNTER, DEBUG_QUERY_VISIT_COUNTER},
+    util::{
+        hash_map::{ConstSafeBuildHasherDefault, FxHashSet},
+        misc::{unpoison, MapFmt},
+    },
 };
 
+pub use crate::util::set_map::SetMapStats as ArchetypeGraphStats;
+
 pub fn alive_entity_count() -> usize {
     DbRoot::get(MainThreadToken::acquire_fmt("fetch entity diagnostics"))
         .debug_alive_list()
@@ -26,6 +42,41 @@ pub fn spawned_entity_count() -> u64 {
     DbRoot::get(MainThreadToken::acquire_fmt("fetch entity diagnostics")).debug_total_spawns()
 }
 
+/// Enables or disables deterministic entity IDs. Enabling the mode restarts entity ID generation
+/// from a fixed seed so that the same sequence of spawns and despawns always produces the same IDs
+/// and the same query iteration order, making it suitable for reproducible logs and golden tests.
+/// Enabling it again restarts the sequence once more and disabling it resumes ordinary ID
+/// generation where it left off.
+///
+/// Since restarted IDs can coincide with the IDs of entities from a previous run, the mode can only
+/// be enabled once every entity has been destroyed and the database has been
+/// [flushed](crate::query::flush). Handles to entities from a previous run must not be used
+/// afterwards.
+pub fn set_deterministic_ids(enabled: bool) {
+    let token = MainThreadToken::acquire_fmt("configure entity id generation");
+    let result = DbRoot::get(token).set_deterministic_entity_ids(enabled);
+
+    if let Err(err) = result {
+        if err.alive == 0 {
+            panic!(
+                "Attempted to enable deterministic entity ids before flushing the destruction of \
+                 every entity"
+            );
+        }
+
+        panic!(
+            "Attempted to enable deterministic entity ids while {} entit{} still alive{}",
+            err.alive,
+            if err.alive == 1 { "y was" } else { "ies were" },
+            if err.pending_flush {
+                " and the database had yet to be flushed"
+            } else {
+                ""
+            },
+        );
+    }
+}
+
 pub fn heap_count() -> u64 {
     DEBUG_HEAP_COUNTER.load(atomic::Ordering::Relaxed)
 }
@@ -34,21 +85,319 @@ pub fn slot_count() -> u64 {
     DEBUG_SLOT_COUNTER.load(atomic::Ordering::Relaxed)
 }
 
+/// Cumulative counters describing every query run since the program started.
+///
+/// These counters are only maintained when the `query-metrics` feature is enabled and are always
+/// zero otherwise.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct QueryMetrics {
+    pub queries_run: u64,
+    pub entities_visited: u64,
+    /// The number of complete blocks which could not be borrowed all at once and had to be visited
+    /// one entity at a time.
+    pub slow_path_fallbacks: u64,
+}
+
+pub fn query_metrics() -> QueryMetrics {
+    QueryMetrics {
+        queries_run: DEBUG_QUERY_COUNTER.load(atomic::Ordering::Relaxed),
+        entities_visited: DEBUG_QUERY_VISIT_COUNTER.load(atomic::Ordering::Relaxed),
+        slow_path_fallbacks: DEBUG_QUERY_FALLBACK_COUNTER.load(atomic::Ordering::Relaxed),
+    }
+}
+
 pub fn archetype_count() -> u64 {
     DbRoot::get(MainThreadToken::acquire_fmt("fetch entity diagnostics")).debug_archetype_count()
 }
 
+/// Returns a `(tag count, entity count)` pair for every archetype containing at least one alive
+/// entity, sorted by tag count and then by entity count. Entities are counted in the archetype
+/// matching their current tags, even if the change has yet to be flushed.
+pub fn archetype_histogram() -> Vec<(usize, usize)> {
+    let mut histogram = DbRoot::get(MainThreadToken::acquire_fmt("fetch archetype diagnostics"))
+        .debug_archetypes()
+        .into_iter()
+        .map(|arch| (arch.tags.len(), arch.entity_count))
+        .collect::<Vec<_>>();
+
+    histogram.sort_unstable();
+    histogram
+}
+
+#[derive(Debug, Clone)]
+pub struct StorageStat {
+    pub type_id: TypeId,
+    pub type_name: &'static str,
+    pub live_slots: usize,
+    pub capacity: usize,
+    pub bytes: usize,
+}
+
+pub fn storage_stats() -> Vec<StorageStat> {
+    let token = MainThreadToken::acquire_fmt("fetch storage diagnostics");
+    DbRoot::get(token).debug_storage_stats(token)
+}
+
+pub fn archetype_graph_stats() -> ArchetypeGraphStats {
+    DbRoot::get(MainThreadToken::acquire_fmt("fetch archetype diagnostics"))
+        .debug_archetype_graph_stats()
+}
+
 pub fn force_reset_database() {
     *DbRoot::get(MainThreadToken::acquire_fmt("force reset database")) = DbRoot::default();
 }
 
 pub fn dump_database_state() -> String {
+    // N.B. we collect labels before borrowing the database since fetching them reborrows it.
+    let labels = alive_entities()
+        .into_iter()
+        .filter_map(|entity| Some((entity.inert, entity.debug_label()?.0)))
+        .collect::<Vec<_>>();
+
     format!(
-        "{:#?}",
-        DbRoot::get(MainThreadToken::acquire_fmt("dump the database state"))
+        "{:#?}\nlabels: {:#?}",
+        DbRoot::get(MainThreadToken::acquire_fmt("dump the database state")),
+        MapFmt(labels),
     )
 }
 
+#[cfg(feature = "serde")]
+pub fn dump_database_state_json() -> serde_json::Value {
+    use crate::database::InertTag;
+    use serde_json::json;
+
+    let db = DbRoot::get(MainThreadToken::acquire_fmt("dump the database state"));
+
+    let archetypes = db
+        .debug_archetypes()
+        .into_iter()
+        .map(|arch| {
+            let tags = arch
+                .tags
+                .iter()
+                .map(|tag| {
+                    json!({
+                        "id": tag.id().get(),
+                        "type": tag.ty().name(),
+                        "virtual": tag.ty() == InertTag::inert_ty_id(),
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            json!({
+                "tags": tags,
+                "entity_count": arch.entity_count,
+                "components": arch.components,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "alive_entity_count": db.debug_alive_list().len(),
+        "total_spawns": db.debug_total_spawns(),
+        "archetype_count": db.debug_archetype_count(),
+        "archetypes": archetypes,
+    })
+}
+
+/// The changes between two [`WorldSnapshot`](crate::snapshot::WorldSnapshot)s of the same world,
+/// as computed by [`diff_snapshots`].
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorldDiff {
+    /// The identifiers of the entities which only exist in the newer snapshot, in ascending order.
+    pub spawned: Vec<u64>,
+    /// The identifiers of the entities which only exist in the older snapshot, in ascending order.
+    pub despawned: Vec<u64>,
+    /// The entities which exist in both snapshots but whose components differ, in ascending order
+    /// of identifier.
+    pub changed: Vec<EntityDiff>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntityDiff {
+    pub entity: u64,
+    /// The components which were added or whose saved value changed alongside their new value,
+    /// sorted by name.
+    pub updated: Vec<(String, serde_json::Value)>,
+    /// The components which were removed, sorted by name.
+    pub removed: Vec<String>,
+}
+
+/// Computes the changes between two snapshots taken from the same world, which is useful for
+/// delta-compressing state sent over the network.
+///
+/// Components are compared by their saved value so only [registered](
+/// crate::snapshot::register_snapshot_component) components are considered and components which
+/// save to the same value are omitted.
+#[cfg(feature = "serde")]
+pub fn diff_snapshots(
+    old: &crate::snapshot::WorldSnapshot,
+    new: &crate::snapshot::WorldSnapshot,
+) -> WorldDiff {
+    use crate::snapshot::snapshot_components;
+
+    let (Some(old), Some(new)) = (snapshot_components(old), snapshot_components(new)) else {
+        panic!("Attempted to diff a malformed snapshot");
+    };
+
+    let mut diff = WorldDiff::default();
+
+    for (&entity, new_comps) in &new {
+        let Some(old_comps) = old.get(&entity) else {
+            diff.spawned.push(entity);
+            continue;
+        };
+
+        let mut updated = new_comps
+            .iter()
+            .filter(|&(name, value)| old_comps.get(name) != Some(value))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Vec<_>>();
+
+        let mut removed = old_comps
+            .keys()
+            .filter(|name| !new_comps.contains_key(*name))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if updated.is_empty() && removed.is_empty() {
+            continue;
+        }
+
+        updated.sort_by(|(a, _), (b, _)| a.cmp(b));
+        removed.sort();
+
+        diff.changed.push(EntityDiff {
+            entity,
+            updated,
+            removed,
+        });
+    }
+
+    diff.despawned = old
+        .keys()
+        .filter(|entity| !new.contains_key(*entity))
+        .copied()
+        .collect();
+
+    diff.spawned.sort_unstable();
+    diff.despawned.sort_unstable();
+    diff.changed.sort_by_key(|entity| entity.entity);
+    diff
+}
+
+#[derive(Debug, Clone)]
+pub struct LeakInfo {
+    pub entity: Entity,
+    pub label: Option<DebugLabel>,
+}
+
+/// The set of entities which were alive when [`leak_scope`] was called.
+#[derive(Debug, Clone)]
+pub struct LeakScope {
+    alive_before: FxHashSet<Entity>,
+}
+
+/// Begins a scope, such as the lifetime of a scene, whose entities are all expected to be destroyed
+/// by the time [`leak_report`] is called with it.
+pub fn leak_scope() -> LeakScope {
+    LeakScope {
+        alive_before: alive_entities().into_iter().collect(),
+    }
+}
+
+/// Lists every entity which was spawned after `scope` began and is still alive alongside its
+/// `DebugLabel`, if it has one, in ascending order of [`Entity::id`].
+///
+/// Bort cannot distinguish an `OwnedEntity` which was forgotten from one which is still being held
+/// so this report should be taken once every entity spawned in the scope is expected to have been
+/// destroyed (e.g. after a scene teardown), at which point every survivor is a leak.
+pub fn leak_report(scope: &LeakScope) -> Vec<LeakInfo> {
+    let mut leaks = alive_entities()
+        .into_iter()
+        .filter(|entity| !scope.alive_before.contains(entity))
+        .map(|entity| LeakInfo {
+            entity,
+            label: entity.debug_label(),
+        })
+        .collect::<Vec<_>>();
+
+    leaks.sort_by_key(|leak| leak.entity.id());
+    leaks
+}
+
+static IS_WATCHING_BORROWS: AtomicBool = AtomicBool::new(false);
+
+static WATCHED_BORROW_TYPES: Mutex<FxHashSet<TypeId>> =
+    Mutex::new(FxHashSet::with_hasher(ConstSafeBuildHasherDefault::new()));
+
+static BORROW_LOG: Mutex<Vec<BorrowLogEntry>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone)]
+pub struct BorrowLogEntry {
+    pub type_id: TypeId,
+    pub type_name: &'static str,
+    pub mutable: bool,
+    pub location: &'static Location<'static>,
+}
+
+/// Starts or stops recording every borrow of a `T` component alongside the location which
+/// requested it. Recorded borrows can be fetched with [`take_borrow_log`].
+///
+/// Borrows are recorded before they are attempted so the last entry of a type will be the borrow
+/// which panicked, if any.
+pub fn watch_borrows<T: 'static>(enabled: bool) {
+    let mut watched = unpoison(WATCHED_BORROW_TYPES.lock());
+
+    if enabled {
+        watched.insert(TypeId::of::<T>());
+    } else {
+        watched.remove(&TypeId::of::<T>());
+    }
+
+    IS_WATCHING_BORROWS.store(!watched.is_empty(), atomic::Ordering::Relaxed);
+}
+
+pub fn take_borrow_log() -> Vec<BorrowLogEntry> {
+    mem::take(&mut *unpoison(BORROW_LOG.lock()))
+}
+
+#[track_caller]
+pub(crate) fn audit_borrow<T: 'static>(mutable: bool) {
+    if IS_WATCHING_BORROWS.load(atomic::Ordering::Relaxed) {
+        audit_borrow_slow(
+            TypeId::of::<T>(),
+            type_name::<T>(),
+            mutable,
+            Location::caller(),
+        );
+    }
+}
+
+#[cold]
+fn audit_borrow_slow(
+    type_id: TypeId,
+    type_name: &'static str,
+    mutable: bool,
+    location: &'static Location<'static>,
+) {
+    if !unpoison(WATCHED_BORROW_TYPES.lock()).contains(&type_id) {
+        return;
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::trace!(type_name, mutable, %location, "bort::borrow");
+
+    unpoison(BORROW_LOG.lock()).push(BorrowLogEntry {
+        type_id,
+        type_name,
+        mutable,
+        location,
+    });
+}
+
 #[derive(Debug, Clone)]
 pub struct DebugLabel(pub Cow<'static, str>);
 
@@ -89,3 +438,414 @@ impl AsDebugLabel for Cow<'static, str> {
         me
     }
 }
+
+// === Tests === //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::{storage, OwnedEntity},
+        query::{flush, VirtualTag},
+        util::testing::run_on_main_thread,
+    };
+
+    #[test]
+    fn leak_report_lists_survivors_of_the_scope() {
+        run_on_main_thread(|| {
+            let before = OwnedEntity::new().with_debug_label("before");
+            let scope = leak_scope();
+
+            let dropped = OwnedEntity::new().with_debug_label("dropped");
+            let leaked = OwnedEntity::new().with_debug_label("leaked");
+            let leaked_entity = leaked.entity();
+            mem::forget(leaked);
+            drop(dropped);
+
+            let report = leak_report(&scope);
+            assert_eq!(report.len(), 1);
+            assert_eq!(report[0].entity, leaked_entity);
+            assert_eq!(
+                report[0].label.as_ref().map(|label| &*label.0),
+                Some("leaked"),
+            );
+
+            leaked_entity.destroy();
+            assert!(leak_report(&scope).is_empty());
+
+            drop(before);
+            flush();
+        });
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn dump_database_state_json_lists_archetypes() {
+        use crate::query::Tag;
+
+        struct Marker;
+
+        run_on_main_thread(|| {
+            let tag = Tag::<Marker>::new();
+            let tagged = (0..2)
+                .map(|_| OwnedEntity::new().with(Marker).with_tag(tag))
+                .collect::<Vec<_>>();
+            flush();
+
+            let dump = dump_database_state_json();
+            let archetypes = dump["archetypes"].as_array().unwrap();
+
+            let arch = archetypes
+                .iter()
+                .find(|arch| {
+                    arch["tags"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .any(|entry| entry["id"] == tag.raw().0.id().get())
+                })
+                .expect("the tagged archetype should be dumped");
+
+            assert_eq!(arch["entity_count"], 2);
+            assert!(arch["components"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|name| name == type_name::<Marker>()));
+
+            let tags = arch["tags"].as_array().unwrap();
+            assert_eq!(tags.len(), 1);
+            assert_eq!(tags[0]["virtual"], false);
+
+            #[cfg(debug_assertions)]
+            assert_eq!(tags[0]["type"], type_name::<Marker>());
+
+            drop(tagged);
+            flush();
+        });
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn diff_snapshots_lists_exactly_the_changes() {
+        use crate::snapshot::{
+            register_snapshot_component, snapshot, LoadContext, SaveContext, SnapshotComponent,
+        };
+        use serde_json::{json, Value};
+
+        struct Hp(u64);
+
+        impl SnapshotComponent for Hp {
+            fn save(&self, _cx: &SaveContext) -> Value {
+                json!(self.0)
+            }
+
+            fn load(value: &Value, _cx: &LoadContext) -> Option<Self> {
+                value.as_u64().map(Self)
+            }
+        }
+
+        struct Pos(i64);
+
+        impl SnapshotComponent for Pos {
+            fn save(&self, _cx: &SaveContext) -> Value {
+                json!(self.0)
+            }
+
+            fn load(value: &Value, _cx: &LoadContext) -> Option<Self> {
+                value.as_i64().map(Self)
+            }
+        }
+
+        run_on_main_thread(|| {
+            register_snapshot_component::<Hp>("debug_tests::hp");
+            register_snapshot_component::<Pos>("debug_tests::pos");
+
+            let still = OwnedEntity::new().with(Hp(10)).with(Pos(0));
+            let hurt = OwnedEntity::new().with(Hp(10)).with(Pos(0));
+            let moved = OwnedEntity::new().with(Hp(10));
+            let doomed = OwnedEntity::new().with(Hp(1));
+            let doomed_id = doomed.entity().id();
+
+            let before = snapshot();
+            assert_eq!(diff_snapshots(&before, &before), WorldDiff::default());
+
+            *hurt.get_mut::<Hp>() = Hp(7);
+            hurt.remove::<Pos>();
+            moved.insert(Pos(3));
+            *still.get_mut::<Hp>() = Hp(10);
+            drop(doomed);
+            let spawned = OwnedEntity::new().with(Pos(-1));
+
+            let after = snapshot();
+            let diff = diff_snapshots(&before, &after);
+
+            let mut changed = vec![
+                EntityDiff {
+                    entity: hurt.entity().id(),
+                    updated: vec![("debug_tests::hp".to_string(), json!(7))],
+                    removed: vec!["debug_tests::pos".to_string()],
+                },
+                EntityDiff {
+                    entity: moved.entity().id(),
+                    updated: vec![("debug_tests::pos".to_string(), json!(3))],
+                    removed: Vec::new(),
+                },
+            ];
+            changed.sort_by_key(|entity| entity.entity);
+
+            assert_eq!(
+                diff,
+                WorldDiff {
+                    spawned: vec![spawned.entity().id()],
+                    despawned: vec![doomed_id],
+                    changed,
+                },
+            );
+
+            drop((still, hurt, moved, spawned));
+            flush();
+        });
+    }
+
+    #[test]
+    fn deterministic_ids_repeat_across_runs() {
+        fn run_script() -> Vec<u64> {
+            let mut ids = Vec::new();
+            let mut alive = (0..6)
+                .map(|i| OwnedEntity::new().with(i))
+                .inspect(|entity| ids.push(entity.entity().id()))
+                .collect::<Vec<_>>();
+
+            alive.drain(1..4);
+            flush();
+
+            for i in 0..4 {
+                let entity = OwnedEntity::new().with(i as u8);
+                ids.push(entity.entity().id());
+                alive.push(entity);
+            }
+
+            alive.swap_remove(0);
+            flush();
+            ids.extend(alive.iter().map(|entity| entity.entity().id()));
+
+            drop(alive);
+            flush();
+            ids
+        }
+
+        run_on_main_thread(|| {
+            flush();
+
+            set_deterministic_ids(true);
+            let first = run_script();
+
+            set_deterministic_ids(true);
+            let second = run_script();
+
+            set_deterministic_ids(false);
+            let resumed = OwnedEntity::new();
+
+            assert_eq!(first, second);
+            assert_ne!(resumed.entity().id(), first[0]);
+
+            drop(resumed);
+            flush();
+        });
+    }
+
+    #[test]
+    fn deterministic_ids_require_a_flushed_database() {
+        run_on_main_thread(|| {
+            // Only the destruction of an entity which was flushed into an archetype is left pending.
+            let doomed = OwnedEntity::new().with_tag(VirtualTag::new());
+            flush();
+            drop(doomed);
+
+            let err = std::panic::catch_unwind(|| set_deterministic_ids(true)).unwrap_err();
+            let msg = err.downcast_ref::<&str>().unwrap();
+            assert!(msg.contains("before flushing"), "{msg}");
+
+            let entity = OwnedEntity::new();
+            let err = std::panic::catch_unwind(|| set_deterministic_ids(true)).unwrap_err();
+            let msg = err.downcast_ref::<String>().unwrap();
+            assert!(
+                msg.contains("1 entity was still alive and the database"),
+                "{msg}"
+            );
+
+            drop(entity);
+            flush();
+        });
+    }
+
+    #[cfg(feature = "query-metrics")]
+    #[test]
+    fn query_metrics_count_visited_entities() {
+        use crate::query::{query, Tag};
+
+        struct Visited;
+
+        run_on_main_thread(|| {
+            let tag = Tag::<Visited>::new();
+            let entities = (0..100)
+                .map(|_| OwnedEntity::new().with(Visited).with_tag(tag))
+                .collect::<Vec<_>>();
+            flush();
+
+            let before = query_metrics();
+            let mut seen = 0;
+            query! {
+                for (ref _visited in tag) {
+                    seen += 1;
+                }
+            }
+            let after = query_metrics();
+
+            assert_eq!(seen, 100);
+            assert_eq!(after.queries_run - before.queries_run, 1);
+            assert_eq!(after.entities_visited - before.entities_visited, 100);
+            assert_eq!(after.slow_path_fallbacks, before.slow_path_fallbacks);
+
+            drop(entities);
+            flush();
+        });
+    }
+
+    #[cfg(not(feature = "query-metrics"))]
+    #[test]
+    fn query_metrics_are_not_recorded_without_the_feature() {
+        use crate::query::{query, Tag};
+
+        struct Visited;
+
+        run_on_main_thread(|| {
+            let tag = Tag::<Visited>::new();
+            let entity = OwnedEntity::new().with(Visited).with_tag(tag);
+            flush();
+
+            query! {
+                for (ref _visited in tag) {}
+            }
+            assert_eq!(query_metrics(), QueryMetrics::default());
+
+            drop(entity);
+            flush();
+        });
+    }
+
+    #[test]
+    fn storage_stats_report_live_slots_and_capacity() {
+        struct Measured([u64; 2]);
+
+        run_on_main_thread(|| {
+            let stat = || {
+                storage_stats()
+                    .into_iter()
+                    .find(|stat| stat.type_id == TypeId::of::<Measured>())
+            };
+
+            // Storages only show up once they've been created.
+            assert!(stat().is_none());
+
+            let entities = (0..130)
+                .map(|_| OwnedEntity::new().with(Measured([0; 2])))
+                .collect::<Vec<_>>();
+            flush();
+            entities[0].remove::<Measured>();
+
+            let measured = stat().unwrap();
+            assert_eq!(measured.type_name, type_name::<Measured>());
+            assert_eq!(measured.live_slots, 129);
+            assert!(measured.capacity >= 129);
+            assert_eq!(measured.bytes, measured.capacity * 16);
+
+            drop(entities);
+            flush();
+            storage::<Measured>().shrink_to_fit();
+
+            let measured = stat().unwrap();
+            assert_eq!(measured.live_slots, 0);
+            assert_eq!(measured.capacity, 0);
+            assert_eq!(measured.bytes, 0);
+        });
+    }
+
+    #[test]
+    fn archetype_histogram_counts_entities_per_archetype() {
+        run_on_main_thread(|| {
+            let [a, b, c] = [VirtualTag::new(), VirtualTag::new(), VirtualTag::new()];
+
+            // Other tests may leave entities behind so we only look at what we added.
+            let before = archetype_histogram();
+            let added = || {
+                let mut added = archetype_histogram();
+                for entry in &before {
+                    let index = added.iter().position(|other| other == entry).unwrap();
+                    added.remove(index);
+                }
+                added
+            };
+
+            let mut entities = Vec::new();
+            entities.extend((0..3).map(|_| OwnedEntity::new().with_tag(a)));
+            entities.extend((0..2).map(|_| OwnedEntity::new().with_tag(a).with_tag(b)));
+            entities.extend((0..4).map(|_| OwnedEntity::new().with_tag(b).with_tag(c)));
+            entities.push(OwnedEntity::new().with_tag(a).with_tag(b).with_tag(c));
+
+            // Tag changes are reflected right away, without waiting for a flush.
+            assert_eq!(added(), [(1, 3), (2, 2), (2, 4), (3, 1)]);
+            entities[0].tag(b);
+            entities[1].tag(c);
+            assert_eq!(added(), [(1, 1), (2, 1), (2, 3), (2, 4), (3, 1)]);
+
+            // Archetypes which are emptied out disappear from the histogram.
+            entities.truncate(5);
+            assert_eq!(added(), [(1, 1), (2, 1), (2, 3)]);
+
+            drop(entities);
+            flush();
+            assert!(added().is_empty());
+        });
+    }
+
+    #[test]
+    fn watched_borrows_are_logged_with_their_location() {
+        struct Watched(u32);
+
+        struct Unwatched;
+
+        run_on_main_thread(|| {
+            let entity = OwnedEntity::new().with(Watched(1)).with(Unwatched);
+            take_borrow_log();
+
+            watch_borrows::<Watched>(true);
+            let read_line = line!() + 1;
+            let value = entity.get::<Watched>().0;
+            let write_line = line!() + 1;
+            entity.get_mut::<Watched>().0 = value + 1;
+            let _ = entity.get::<Unwatched>();
+
+            let log = take_borrow_log();
+            assert_eq!(log.len(), 2);
+            assert!(log
+                .iter()
+                .all(|entry| entry.type_id == TypeId::of::<Watched>()));
+            assert!(log
+                .iter()
+                .all(|entry| entry.type_name == type_name::<Watched>()));
+            assert!(log.iter().all(|entry| entry.location.file() == file!()));
+            assert_eq!((log[0].mutable, log[0].location.line()), (false, read_line));
+            assert_eq!((log[1].mutable, log[1].location.line()), (true, write_line));
+
+            // Nothing is recorded once the type stops being watched.
+            watch_borrows::<Watched>(false);
+            let _ = entity.get::<Watched>();
+            assert!(take_borrow_log().is_empty());
+
+            drop(entity);
+            flush();
+        });
+    }
+}
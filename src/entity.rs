@@ -1,24 +1,36 @@
 use std::{
-    any::{type_name, TypeId},
-    borrow, fmt, mem,
+    any::{type_name, Any, TypeId},
+    borrow::{self, Cow},
+    error::Error,
+    fmt, iter, mem,
     num::NonZeroU64,
     ops::{Deref, DerefMut},
+    rc::Rc,
+    sync::{Arc, Mutex},
 };
 
-use autoken::{ImmutableBorrow, MutableBorrow, Nothing};
+use autoken::{
+    ImmutableBorrow, MutableBorrow, Nothing, PotentialImmutableBorrow, PotentialMutableBorrow,
+};
 use derive_where::derive_where;
 
 use crate::{
     core::{
-        cell::{OptRef, OptRefMut},
-        heap::Slot,
+        cell::{
+            BorrowError, BorrowMutError, MultiOptRangeRef, MultiRefCellIndex, OptRef, OptRefMut,
+        },
+        heap::{array_chunks, HeapSlotBlock, Slot},
         token::MainThreadToken,
     },
-    database::{DbRoot, DbStorage, EntityDeadError, InertEntity},
-    debug::AsDebugLabel,
+    database::{DbRoot, DbStorage, DbStorageInner, EntityDeadError, InertEntity, InertTag},
+    debug::{AsDebugLabel, DebugLabel},
     obj::{Obj, OwnedObj},
-    query::{ArchetypeId, RawTag},
-    util::misc::RawFmt,
+    query::{borrow_flush_guard, ArchetypeId, RawTag, TagSet},
+    relation::{Children, Relation},
+    util::{
+        hash_map::{ConstSafeBuildHasherDefault, FxHashMap},
+        misc::{unpoison, RawFmt},
+    },
     GlobalTag, HasGlobalManagedTag,
 };
 
@@ -33,6 +45,15 @@ pub fn storage<T: 'static>() -> Storage<T> {
     }
 }
 
+pub fn register_on_remove<T: 'static>(hook: fn(Entity, &mut T)) {
+    storage::<T>().register_on_remove(hook);
+}
+
+/// A handle to the storage of every component of type `T`.
+///
+/// The main thread token is acquired once when the handle is created so, unlike the equivalent
+/// methods on [`Entity`], its accessors don't have to re-acquire it or look up the storage on every
+/// call. Prefer fetching a `Storage` ahead of time in hot loops.
 #[derive_where(Debug, Copy, Clone)]
 pub struct Storage<T: 'static> {
     pub(crate) token: MainThreadToken,
@@ -62,8 +83,54 @@ impl<T: 'static> Storage<T> {
         self.insert_with_obj(entity, value).0
     }
 
+    /// Inserts the component produced by `f` if `entity` doesn't already have one, returning
+    /// whether it did so. `f` is not called if the component is already present.
+    pub fn insert_with(&self, entity: Entity, f: impl FnOnce() -> T) -> bool {
+        if self.has(entity) {
+            return false;
+        }
+
+        self.insert(entity, f());
+        true
+    }
+
+    /// Mutably borrows the component of `entity`, first inserting the value produced by `f` if it
+    /// doesn't have one yet. Newly inserted components can be borrowed immediately; they do not
+    /// have to wait for a [`flush`](crate::query::flush).
+    #[track_caller]
+    pub fn get_or_insert_with(
+        &self,
+        entity: Entity,
+        f: impl FnOnce() -> T,
+    ) -> CompMut<'static, T, T> {
+        self.insert_with(entity, f);
+        self.get_mut(entity)
+    }
+
+    /// Ensures that at least `additional` more components can be inserted into this storage
+    /// without allocating new heaps. Components are moved into their archetype's heaps upon
+    /// flushing so this only affects insertions made between flushes.
+    pub fn reserve(&self, additional: usize) {
+        DbRoot::reserve_components(
+            self.token.make_ref(),
+            &mut self.inner.borrow_mut(self.token.make_ref()),
+            additional,
+        );
+    }
+
+    /// Releases the memory of heaps which no longer contain any components. Live components are
+    /// never moved by this operation so existing [`Slot`] and [`Obj`] handles remain valid.
+    ///
+    /// Archetype heaps are already truncated when the database is flushed so users should [`flush`]
+    /// after a mass despawn to reclaim as much memory as possible.
+    ///
+    /// [`flush`]: crate::query::flush
+    pub fn shrink_to_fit(&self) {
+        DbRoot::shrink_components(&mut self.inner.borrow_mut(self.token.make_ref()));
+    }
+
     pub fn remove(&self, entity: Entity) -> Option<T> {
-        match DbRoot::get(self.token.make_ref()).remove_component(
+        let removed = match DbRoot::get(self.token.make_ref()).remove_component(
             self.token.make_ref(),
             &mut self.inner.borrow_mut(self.token.make_ref()),
             entity.inert,
@@ -72,7 +139,22 @@ impl<T: 'static> Storage<T> {
             Err(EntityDeadError) => {
                 panic!("Attempted to remove component from dead entity {entity:?}")
             }
-        }
+        };
+
+        removed.map(|mut removed| {
+            for hook in DbRoot::get_on_remove_hooks(&self.inner.borrow(self.token.make_ref())) {
+                hook(entity, &mut removed);
+            }
+
+            removed
+        })
+    }
+
+    /// Registers a hook which is called with the removed value whenever a component of this type
+    /// is removed from an entity, be that through [`Storage::remove`] or through the destruction
+    /// of its owning entity. Hooks run before the value is returned or dropped.
+    pub fn register_on_remove(&self, hook: fn(Entity, &mut T)) {
+        DbRoot::add_on_remove_hook(&mut self.inner.borrow_mut(self.token.make_ref()), hook);
     }
 
     // === Getters === //
@@ -170,9 +252,442 @@ impl<T: 'static> Storage<T> {
         )
     }
 
+    /// Borrows `entity`'s component without panicking. See [`ComponentAccessError`] for the ways
+    /// in which this can fail.
+    #[track_caller]
+    pub fn try_borrow<'l>(
+        &self,
+        entity: Entity,
+        loaner: &'l PotentialImmutableBorrow<T>,
+    ) -> Result<CompRef<'static, T, Nothing<'l>>, ComponentAccessError> {
+        let slot = self.try_get_slot_or_err(entity)?;
+
+        match slot.try_borrow(self.token.make_ref(), loaner) {
+            Ok(Some(value)) => Ok(CompRef::new(Obj::from_raw_parts(entity, slot), value)),
+            Ok(None) => Err(ComponentAccessError::Missing),
+            Err(err) => Err(ComponentAccessError::Borrowed(err)),
+        }
+    }
+
+    /// Mutably borrows `entity`'s component without panicking. See [`ComponentAccessError`] for
+    /// the ways in which this can fail.
+    #[track_caller]
+    pub fn try_borrow_mut<'l>(
+        &self,
+        entity: Entity,
+        loaner: &'l mut PotentialMutableBorrow<T>,
+    ) -> Result<CompMut<'static, T, Nothing<'l>>, ComponentAccessError> {
+        let slot = self.try_get_slot_or_err(entity)?;
+
+        match slot.try_borrow_mut(self.token.make_ref(), loaner) {
+            Ok(Some(value)) => Ok(CompMut::new(Obj::from_raw_parts(entity, slot), value)),
+            Ok(None) => Err(ComponentAccessError::Missing),
+            Err(err) => Err(ComponentAccessError::BorrowedMut(err)),
+        }
+    }
+
+    fn try_get_slot_or_err(&self, entity: Entity) -> Result<Slot<T>, ComponentAccessError> {
+        self.try_get_slot(entity).ok_or_else(|| {
+            if entity.is_alive() {
+                ComponentAccessError::Missing
+            } else {
+                ComponentAccessError::EntityDead
+            }
+        })
+    }
+
+    /// Returns a counter which advances every time `entity`'s component is mutably borrowed or
+    /// replaced, or `None` if the entity has no such component. Comparing it against an older
+    /// version tells whether the component may have changed since then.
+    ///
+    /// Tracking versions costs every component cell a counter and every mutable borrow an
+    /// increment so it is only available with the `component-versions` feature.
+    #[cfg(feature = "component-versions")]
+    pub fn version(&self, entity: Entity) -> Option<u64> {
+        self.try_get_slot(entity)
+            .map(|slot| slot.version(self.token.make_ref()))
+    }
+
     pub fn has(&self, entity: Entity) -> bool {
         self.try_get_slot(entity).is_some()
     }
+
+    pub fn len(&self) -> usize {
+        DbRoot::component_count(&self.inner.borrow(self.token.make_ref()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Calls `f` on every live component in this storage in an unspecified order, borrowing each
+    /// of them mutably.
+    ///
+    /// Components are borrowed a whole heap block at a time so `f` must not borrow other
+    /// components of this type, nor insert or remove them. The database cannot be flushed while
+    /// this runs.
+    #[track_caller]
+    pub fn for_each_mut(&self, mut f: impl FnMut(Entity, &mut T)) {
+        let token = self.token.make_ref();
+        let _guard = borrow_flush_guard();
+        let storage = self.inner.borrow(token);
+        let archetype_heaps = DbRoot::get(token).archetype_component_heaps(&storage);
+        let mut loaner = MutableBorrow::<T>::new();
+
+        // Components in archetype heaps line up with their archetype's entity heaps.
+        for (heap, entities) in &archetype_heaps {
+            let entities = array_chunks::<_, { MultiRefCellIndex::COUNT }>(entities);
+
+            for (block, entities) in heap.blocks(token).zip(entities) {
+                // Fast path: the entire block is live and can be borrowed in one go.
+                if let Some(mut values) = block
+                    .values()
+                    .try_borrow_all_mut(token, loaner.downgrade_mut())
+                {
+                    for (entity, value) in entities.iter().zip(values.iter_mut()) {
+                        f(entity.get(token).into_dangerous_entity(), value);
+                    }
+                    continue;
+                }
+
+                // Slow path: borrow every live slot individually.
+                for (entity, slot) in entities.iter().zip(block.slots()) {
+                    if let Some(mut value) = slot.borrow_mut_or_none(token, &mut loaner) {
+                        f(entity.get(token).into_dangerous_entity(), &mut value);
+                    }
+                }
+            }
+        }
+
+        // Anonymous heaps are sparse so we have to ask each slot for its owner.
+        for block in DbRoot::anonymous_component_heaps(&storage).flat_map(|heap| heap.blocks(token))
+        {
+            for slot in block.slots() {
+                let Some(owner) = slot.owner(token) else {
+                    continue;
+                };
+
+                if let Some(mut value) = slot.borrow_mut_or_none(token, &mut loaner) {
+                    f(owner, &mut value);
+                }
+            }
+        }
+    }
+
+    /// Borrows every component in this storage for read-only bulk access. See [`ColumnChunks`]
+    /// for details.
+    #[track_caller]
+    pub fn column_chunks(&self) -> ColumnChunks<T> {
+        ColumnChunks {
+            token: self.token.make_ref(),
+            loaner: PotentialImmutableBorrow::new(),
+            storage: self.inner.borrow(self.token.make_ref()),
+        }
+    }
+}
+
+impl<T: 'static> Storage<Shared<T>> {
+    /// Inserts a component which may be shared with other entities, returning the previously
+    /// shared value if there was one.
+    pub fn insert_shared(&self, entity: Entity, value: Arc<T>) -> Option<Arc<T>> {
+        self.insert(entity, Shared::new(value))
+            .map(Shared::into_arc)
+    }
+}
+
+// === Shared === //
+
+/// A copy-on-write component which several entities can share. Reads go through the shared
+/// allocation while mutable access clones the value first if it is still shared, so a plain
+/// [`Entity::get_mut`] only ever mutates the calling entity's copy.
+///
+/// Like [`Arc`], the accessors are associated functions so that they don't shadow methods of `T`.
+#[derive(Debug, Default)]
+#[derive_where(Clone)]
+pub struct Shared<T> {
+    value: Arc<T>,
+}
+
+impl<T> Shared<T> {
+    pub fn new(value: Arc<T>) -> Self {
+        Self { value }
+    }
+
+    pub fn as_arc(this: &Self) -> &Arc<T> {
+        &this.value
+    }
+
+    pub fn into_arc(this: Self) -> Arc<T> {
+        this.value
+    }
+
+    pub fn is_shared(this: &Self) -> bool {
+        Arc::strong_count(&this.value) > 1
+    }
+}
+
+impl<T> From<Arc<T>> for Shared<T> {
+    fn from(value: Arc<T>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T: Clone> DerefMut for Shared<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        Arc::make_mut(&mut self.value)
+    }
+}
+
+// === ComponentAccessError === //
+
+/// The reason for which a fallible accessor such as [`Entity::try_borrow`] or [`Obj::try_borrow`]
+/// failed to borrow a component.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum ComponentAccessError {
+    /// The entity was dead, either because it was destroyed or because it never existed.
+    EntityDead,
+    /// The entity was alive but had no component of the requested type. An [`Obj`] reports this
+    /// when its component was removed from an entity which is still alive.
+    Missing,
+    /// The component could not be borrowed immutably because it was already borrowed mutably.
+    Borrowed(BorrowError),
+    /// The component could not be borrowed mutably because it was already borrowed.
+    BorrowedMut(BorrowMutError),
+}
+
+impl Error for ComponentAccessError {}
+
+impl fmt::Display for ComponentAccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EntityDead => f.write_str("the entity is dead"),
+            Self::Missing => f.write_str("the entity does not have a component of this type"),
+            Self::Borrowed(err) => err.fmt(f),
+            Self::BorrowedMut(err) => err.fmt(f),
+        }
+    }
+}
+
+// === ColumnChunks === //
+
+/// A read-only view over every component in a [`Storage`], produced by [`Storage::column_chunks`].
+///
+/// The storage's bookkeeping stays borrowed until this view is dropped so inserting or removing
+/// components of this type in the meantime will panic.
+pub struct ColumnChunks<T: 'static> {
+    token: &'static MainThreadToken,
+    loaner: PotentialImmutableBorrow<T>,
+    storage: OptRef<'static, DbStorageInner<T>>,
+}
+
+impl<T: 'static> ColumnChunks<T> {
+    /// Iterates over contiguous runs of live components in an unspecified order. Runs never span
+    /// more than one heap block so each chunk holds at most [`MultiRefCellIndex::COUNT`]
+    /// components.
+    ///
+    /// Each chunk immutably borrows the components it covers until it is dropped. This panics if
+    /// any of those components are already borrowed mutably.
+    pub fn iter(&self) -> impl Iterator<Item = MultiOptRangeRef<'_, T>> + '_ {
+        DbRoot::component_heaps(&self.storage)
+            .flat_map(|heap| heap.blocks(self.token))
+            .flat_map(|block| self.block_chunks(block))
+    }
+
+    fn block_chunks<'a>(
+        &'a self,
+        block: HeapSlotBlock<'a, T, MainThreadToken>,
+    ) -> impl Iterator<Item = MultiOptRangeRef<'a, T>> + 'a {
+        let values = block.values();
+        let is_live = move |i| !values.is_empty(self.token, MultiRefCellIndex::from_index(i));
+
+        // Fast path: the entire block is live and can be borrowed in one go.
+        let full = values.try_borrow_range(self.token, 0..MultiRefCellIndex::COUNT, &self.loaner);
+
+        // Slow path: split the block into runs of live components and borrow each of them.
+        let mut start = if full.is_some() {
+            MultiRefCellIndex::COUNT
+        } else {
+            0
+        };
+
+        let runs = iter::from_fn(move || {
+            while start < MultiRefCellIndex::COUNT && !is_live(start) {
+                start += 1;
+            }
+
+            if start == MultiRefCellIndex::COUNT {
+                return None;
+            }
+
+            let mut end = start + 1;
+            while end < MultiRefCellIndex::COUNT && is_live(end) {
+                end += 1;
+            }
+
+            let run = values
+                .try_borrow_range(self.token, start..end, &self.loaner)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "failed to borrow a run of {} components: at least one of them is borrowed \
+                         mutably",
+                        type_name::<T>(),
+                    )
+                });
+
+            start = end;
+            Some(run)
+        });
+
+        full.into_iter().chain(runs)
+    }
+}
+
+impl<T: 'static> fmt::Debug for ColumnChunks<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ColumnChunks").finish_non_exhaustive()
+    }
+}
+
+// === Named Components === //
+
+pub type AnyCompRef = CompRef<'static, dyn Any, Nothing<'static>, Entity>;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ComponentNameError {
+    /// No component type was registered under `name`.
+    UnknownName { name: String },
+    /// The value passed to [`Entity::insert_by_name`] was not of the component type registered
+    /// under `name`.
+    WrongType {
+        name: String,
+        expected: &'static str,
+    },
+}
+
+impl Error for ComponentNameError {}
+
+impl fmt::Display for ComponentNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownName { name } => {
+                write!(f, "no component type is registered under the name {name:?}")
+            }
+            Self::WrongType { name, expected } => write!(
+                f,
+                "attempted to insert a value of the wrong type into the component named {name:?}; \
+                 expected {expected}",
+            ),
+        }
+    }
+}
+
+type BoxedComp = Box<dyn Any>;
+
+#[derive(Copy, Clone)]
+struct NamedComponentType {
+    id: TypeId,
+    name: &'static str,
+    insert: fn(Entity, BoxedComp) -> Result<Option<BoxedComp>, BoxedComp>,
+    get: fn(Entity) -> Option<AnyCompRef>,
+}
+
+impl NamedComponentType {
+    fn of<T: 'static>() -> Self {
+        fn insert<T: 'static>(
+            entity: Entity,
+            value: BoxedComp,
+        ) -> Result<Option<BoxedComp>, BoxedComp> {
+            let value = value.downcast::<T>()?;
+
+            Ok(entity
+                .insert(*value)
+                .map(|replaced| Box::new(replaced) as BoxedComp))
+        }
+
+        fn get<T: 'static>(entity: Entity) -> Option<AnyCompRef> {
+            let storage = storage::<T>();
+            let slot = storage.try_get_slot(entity)?;
+
+            Some(CompRef::new(
+                entity,
+                OptRef::strip_lifetime_analysis(OptRef::map(
+                    slot.borrow(storage.token.make_ref()),
+                    |value| value as &dyn Any,
+                )),
+            ))
+        }
+
+        Self {
+            id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+            insert: insert::<T>,
+            get: get::<T>,
+        }
+    }
+}
+
+static NAMED_COMPONENTS: Mutex<FxHashMap<Cow<'static, str>, NamedComponentType>> =
+    Mutex::new(FxHashMap::with_hasher(ConstSafeBuildHasherDefault::new()));
+
+pub fn register_component_by_name<T: 'static>(name: impl Into<Cow<'static, str>>) {
+    let name = name.into();
+    let mut named = unpoison(NAMED_COMPONENTS.lock());
+
+    if let Some(existing) = named.get(&name) {
+        assert_eq!(
+            existing.id,
+            TypeId::of::<T>(),
+            "component name {name:?} is already registered to {}",
+            existing.name,
+        );
+        return;
+    }
+
+    named.insert(name, NamedComponentType::of::<T>());
+}
+
+fn named_component_type(name: &str) -> Result<NamedComponentType, ComponentNameError> {
+    unpoison(NAMED_COMPONENTS.lock())
+        .get(name)
+        .copied()
+        .ok_or_else(|| ComponentNameError::UnknownName {
+            name: name.to_string(),
+        })
+}
+
+// === Cloneable Components === //
+
+type CloneComponentFn = fn(Entity, Entity);
+
+static CLONEABLE_COMPONENTS: Mutex<FxHashMap<TypeId, CloneComponentFn>> =
+    Mutex::new(FxHashMap::with_hasher(ConstSafeBuildHasherDefault::new()));
+
+/// Registers `T` as a component which should be cloned into the copies produced by
+/// [`Entity::duplicate`]. Components of unregistered types are silently left out of the copy.
+pub fn register_cloneable<T: 'static + Clone>() {
+    fn clone_into<T: 'static + Clone>(src: Entity, dst: Entity) {
+        let value = T::clone(&src.get::<T>());
+        dst.insert(value);
+    }
+
+    unpoison(CLONEABLE_COMPONENTS.lock()).insert(TypeId::of::<T>(), clone_into::<T>);
+}
+
+fn cloneable_components() -> Vec<(TypeId, CloneComponentFn)> {
+    unpoison(CLONEABLE_COMPONENTS.lock())
+        .iter()
+        .map(|(&ty, &clone)| (ty, clone))
+        .collect()
 }
 
 // === Entity === //
@@ -217,11 +732,24 @@ impl Entity {
     }
 
     pub fn with_debug_label<L: AsDebugLabel>(self, label: L) -> Self {
+        self.set_debug_label(label);
+        self
+    }
+
+    /// Attaches a [`DebugLabel`] to this entity, replacing any previous label.
+    ///
+    /// Labels are only stored in builds with `debug_assertions` enabled. In release builds, this is
+    /// a no-op and [`debug_label`](Self::debug_label) always returns `None`.
+    pub fn set_debug_label<L: AsDebugLabel>(self, label: L) {
         #[cfg(debug_assertions)]
-        self.with(crate::debug::DebugLabel::from(label));
+        self.insert(DebugLabel::from(label));
         #[cfg(not(debug_assertions))]
         let _ = label;
-        self
+    }
+
+    pub fn debug_label(self) -> Option<DebugLabel> {
+        self.has::<DebugLabel>()
+            .then(|| self.get::<DebugLabel>().clone())
     }
 
     pub fn insert_with_obj<T: 'static>(self, comp: T) -> (Option<T>, Obj<T>) {
@@ -232,6 +760,10 @@ impl Entity {
         storage::<T>().insert(self, comp)
     }
 
+    pub fn insert_with<T: 'static>(self, f: impl FnOnce() -> T) -> bool {
+        storage::<T>().insert_with(self, f)
+    }
+
     pub fn remove<T: 'static>(self) -> Option<T> {
         storage::<T>().remove(self)
     }
@@ -257,6 +789,22 @@ impl Entity {
         storage::<T>().try_get_mut(self, loaner)
     }
 
+    #[track_caller]
+    pub fn try_borrow<T: 'static>(
+        self,
+        loaner: &PotentialImmutableBorrow<T>,
+    ) -> Result<CompRef<'static, T, Nothing<'_>>, ComponentAccessError> {
+        storage::<T>().try_borrow(self, loaner)
+    }
+
+    #[track_caller]
+    pub fn try_borrow_mut<T: 'static>(
+        self,
+        loaner: &mut PotentialMutableBorrow<T>,
+    ) -> Result<CompMut<'static, T, Nothing<'_>>, ComponentAccessError> {
+        storage::<T>().try_borrow_mut(self, loaner)
+    }
+
     pub fn get_slot<T: 'static>(self) -> Slot<T> {
         storage::<T>().get_slot(self)
     }
@@ -287,15 +835,80 @@ impl Entity {
         storage::<T>().get_mut_on_loan(self, loaner)
     }
 
+    pub fn insert_shared<T: 'static>(self, value: Arc<T>) -> Option<Arc<T>> {
+        storage::<Shared<T>>().insert_shared(self, value)
+    }
+
+    /// Mutably borrows two components of different types on this entity at once, panicking if
+    /// either of them is missing.
+    #[track_caller]
+    pub fn get_two_mut<A: 'static, B: 'static>(
+        self,
+    ) -> (CompMut<'static, A, A>, CompMut<'static, B, B>) {
+        assert!(
+            TypeId::of::<A>() != TypeId::of::<B>(),
+            "Attempted to mutably borrow component {} of {self:?} twice",
+            type_name::<A>(),
+        );
+
+        (self.get_mut(), self.get_mut())
+    }
+
     pub fn has<T: 'static>(self) -> bool {
         storage::<T>().has(self)
     }
 
+    #[cfg(feature = "component-versions")]
+    pub fn version<T: 'static>(self) -> Option<u64> {
+        storage::<T>().version(self)
+    }
+
+    pub fn insert_by_name(
+        self,
+        name: &str,
+        comp: Box<dyn Any>,
+    ) -> Result<Option<Box<dyn Any>>, ComponentNameError> {
+        let ty = named_component_type(name)?;
+
+        (ty.insert)(self, comp).map_err(|_| ComponentNameError::WrongType {
+            name: name.to_string(),
+            expected: ty.name,
+        })
+    }
+
+    pub fn get_by_name(self, name: &str) -> Result<Option<AnyCompRef>, ComponentNameError> {
+        Ok((named_component_type(name)?.get)(self))
+    }
+
     pub fn has_dyn(self, ty: TypeId) -> bool {
         let token = MainThreadToken::acquire_fmt("check the component list of an entity");
         DbRoot::get(token).entity_has_component_dyn(token, self.inert, ty)
     }
 
+    /// Spawns an independent copy of this entity carrying its tags and a clone of each of its
+    /// components [registered](register_cloneable) as cloneable.
+    ///
+    /// Components whose types were never registered are silently left out of the copy.
+    pub fn duplicate(self) -> OwnedEntity {
+        let tags = match DbRoot::get(MainThreadToken::acquire_fmt("duplicate an entity"))
+            .get_entity_virtual_tags(self.inert)
+        {
+            Ok(tags) => tags,
+            Err(EntityDeadError) => panic!("Attempted to duplicate dead entity {self:?}"),
+        };
+
+        let copy = OwnedEntity::new();
+
+        for (ty, clone) in cloneable_components() {
+            if self.has_dyn(ty) {
+                clone(self, copy.entity());
+            }
+        }
+
+        copy.tag_many(tags.into_iter().map(InertTag::into_dangerous_tag));
+        copy
+    }
+
     pub fn obj<T: 'static>(self) -> Obj<T> {
         Obj::wrap(self)
     }
@@ -314,6 +927,16 @@ impl Entity {
         }
     }
 
+    /// Adds every tag in `tags` to the entity as a single archetype transition.
+    pub fn tag_many(self, tags: impl IntoIterator<Item = RawTag>) {
+        match DbRoot::get(MainThreadToken::acquire_fmt("tag an entity"))
+            .tag_entity_many(self.inert, tags.into_iter().map(|tag| tag.0))
+        {
+            Ok(()) => { /* no-op */ }
+            Err(EntityDeadError) => panic!("Attempted to add tags to dead entity {self:?}"),
+        }
+    }
+
     pub fn untag(self, tag: impl Into<RawTag>) {
         let tag = tag.into().0;
         match DbRoot::get(MainThreadToken::acquire_fmt("untag an entity"))
@@ -338,6 +961,13 @@ impl Entity {
         self
     }
 
+    /// Inserts every component of the `bundle` tuple and tags the entity with each of their global
+    /// tags in a single archetype transition.
+    pub fn with_tagged_many(self, bundle: impl TaggedBundle) -> Self {
+        bundle.insert_and_tag(self);
+        self
+    }
+
     pub fn is_tagged_virtual(self, tag: impl Into<RawTag>) -> bool {
         let tag = tag.into().0;
         let is_tagged = DbRoot::get(MainThreadToken::acquire_fmt("query entity tags"))
@@ -349,6 +979,33 @@ impl Entity {
         }
     }
 
+    /// Returns whether the entity has every one of the specified tags. Like [`is_tagged_virtual`],
+    /// this takes into account tags which have not yet been flushed.
+    ///
+    /// [`is_tagged_virtual`]: Entity::is_tagged_virtual
+    pub fn has_all_tags(self, tags: impl IntoIterator<Item = RawTag>) -> bool {
+        self.is_tagged_virtual_many(tags, true)
+    }
+
+    /// Returns whether the entity has at least one of the specified tags. Like
+    /// [`is_tagged_virtual`], this takes into account tags which have not yet been flushed.
+    ///
+    /// [`is_tagged_virtual`]: Entity::is_tagged_virtual
+    pub fn has_any_tag(self, tags: impl IntoIterator<Item = RawTag>) -> bool {
+        self.is_tagged_virtual_many(tags, false)
+    }
+
+    fn is_tagged_virtual_many(self, tags: impl IntoIterator<Item = RawTag>, all: bool) -> bool {
+        let tags = tags.into_iter().map(|tag| tag.0).collect::<Vec<_>>();
+        let is_tagged = DbRoot::get(MainThreadToken::acquire_fmt("query entity tags"))
+            .is_entity_tagged_virtual_many(self.inert, tags, all);
+
+        match is_tagged {
+            Ok(result) => result,
+            Err(EntityDeadError) => panic!("Attempted to query tags of dead entity {self:?}"),
+        }
+    }
+
     pub fn is_tagged_physical(self, tag: impl Into<RawTag>) -> bool {
         let tag = tag.into().0;
         let is_tagged = DbRoot::get(MainThreadToken::acquire_fmt("query entity tags"))
@@ -370,6 +1027,45 @@ impl Entity {
             })
     }
 
+    /// Makes `child` a child of this entity, detaching it from its previous parent if it had one.
+    /// The link is removed automatically once either entity is destroyed.
+    pub fn add_child(self, child: Entity) {
+        if let Some(parent) = child.parent() {
+            Relation::<Children>::unlink(parent, child);
+        }
+
+        Relation::<Children>::link(self, child);
+    }
+
+    pub fn remove_child(self, child: Entity) -> bool {
+        Relation::<Children>::unlink(self, child)
+    }
+
+    pub fn children(self) -> Vec<Entity> {
+        Relation::<Children>::targets_of(self)
+    }
+
+    pub fn parent(self) -> Option<Entity> {
+        Relation::<Children>::sources_of(self).first().copied()
+    }
+
+    /// Returns the numeric identifier of this entity. Identifiers are unique for the lifetime of
+    /// the process and are never reused, even after the entity is destroyed, but they are not
+    /// stable across processes. [Deterministic IDs](crate::debug::set_deterministic_ids) relax the
+    /// uniqueness guarantee to make IDs reproducible.
+    pub fn id(self) -> u64 {
+        self.inert.id().get()
+    }
+
+    /// Resolves an identifier obtained from [`Entity::id`] back into an entity handle, returning
+    /// `None` if the entity has since been destroyed or if the identifier never belonged to an
+    /// entity.
+    pub fn from_id(id: u64) -> Option<Self> {
+        DbRoot::get(MainThreadToken::acquire_fmt("resolve an entity id"))
+            .find_alive_entity(id)
+            .map(InertEntity::into_dangerous_entity)
+    }
+
     pub fn is_alive(self) -> bool {
         DbRoot::get(MainThreadToken::acquire_fmt(
             "check the liveness state of an entity",
@@ -377,6 +1073,19 @@ impl Entity {
         .is_entity_alive(self.inert)
     }
 
+    /// Returns the subset of `entities` which are still alive, preserving their relative order.
+    pub fn filter_alive(entities: &[Entity]) -> Vec<Entity> {
+        let db = DbRoot::get(MainThreadToken::acquire_fmt(
+            "check the liveness state of several entities",
+        ));
+
+        entities
+            .iter()
+            .copied()
+            .filter(|entity| db.is_entity_alive(entity.inert))
+            .collect()
+    }
+
     pub fn destroy(self) {
         let token = MainThreadToken::acquire_fmt("destroy entity");
         let components = DbRoot::get(token)
@@ -477,6 +1186,14 @@ impl OwnedEntity {
         self
     }
 
+    pub fn set_debug_label<L: AsDebugLabel>(&self, label: L) {
+        self.entity.set_debug_label(label);
+    }
+
+    pub fn debug_label(&self) -> Option<DebugLabel> {
+        self.entity.debug_label()
+    }
+
     pub fn insert_with_obj<T: 'static>(&self, comp: T) -> (Option<T>, Obj<T>) {
         self.entity.insert_with_obj(comp)
     }
@@ -485,6 +1202,10 @@ impl OwnedEntity {
         self.entity.insert(comp)
     }
 
+    pub fn insert_with<T: 'static>(&self, f: impl FnOnce() -> T) -> bool {
+        self.entity.insert_with(f)
+    }
+
     pub fn remove<T: 'static>(&self) -> Option<T> {
         self.entity.remove()
     }
@@ -509,6 +1230,22 @@ impl OwnedEntity {
         self.entity.try_get_mut(loaner)
     }
 
+    #[track_caller]
+    pub fn try_borrow<'l, T: 'static>(
+        &self,
+        loaner: &'l PotentialImmutableBorrow<T>,
+    ) -> Result<CompRef<'static, T, Nothing<'l>>, ComponentAccessError> {
+        self.entity.try_borrow(loaner)
+    }
+
+    #[track_caller]
+    pub fn try_borrow_mut<'l, T: 'static>(
+        &self,
+        loaner: &'l mut PotentialMutableBorrow<T>,
+    ) -> Result<CompMut<'static, T, Nothing<'l>>, ComponentAccessError> {
+        self.entity.try_borrow_mut(loaner)
+    }
+
     pub fn get_slot<T: 'static>(&self) -> Slot<T> {
         self.entity.get_slot()
     }
@@ -539,13 +1276,45 @@ impl OwnedEntity {
         self.entity.get_mut_on_loan(loaner)
     }
 
-    pub fn has<T: 'static>(&self) -> bool {
-        self.entity.has::<T>()
+    pub fn insert_shared<T: 'static>(&self, value: Arc<T>) -> Option<Arc<T>> {
+        self.entity.insert_shared(value)
     }
 
-    pub fn has_dyn(self, ty: TypeId) -> bool {
-        self.entity.has_dyn(ty)
-    }
+    #[track_caller]
+    pub fn get_two_mut<A: 'static, B: 'static>(
+        &self,
+    ) -> (CompMut<'static, A, A>, CompMut<'static, B, B>) {
+        self.entity.get_two_mut()
+    }
+
+    pub fn has<T: 'static>(&self) -> bool {
+        self.entity.has::<T>()
+    }
+
+    #[cfg(feature = "component-versions")]
+    pub fn version<T: 'static>(&self) -> Option<u64> {
+        self.entity.version::<T>()
+    }
+
+    pub fn insert_by_name(
+        &self,
+        name: &str,
+        comp: Box<dyn Any>,
+    ) -> Result<Option<Box<dyn Any>>, ComponentNameError> {
+        self.entity.insert_by_name(name, comp)
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Result<Option<AnyCompRef>, ComponentNameError> {
+        self.entity.get_by_name(name)
+    }
+
+    pub fn has_dyn(self, ty: TypeId) -> bool {
+        self.entity.has_dyn(ty)
+    }
+
+    pub fn duplicate(&self) -> OwnedEntity {
+        self.entity.duplicate()
+    }
 
     pub fn obj<T: 'static>(&self) -> Obj<T> {
         self.entity.obj()
@@ -559,6 +1328,10 @@ impl OwnedEntity {
         self.entity.tag(tag)
     }
 
+    pub fn tag_many(&self, tags: impl IntoIterator<Item = RawTag>) {
+        self.entity.tag_many(tags)
+    }
+
     pub fn untag(&self, tag: impl Into<RawTag>) {
         self.entity.untag(tag)
     }
@@ -577,10 +1350,23 @@ impl OwnedEntity {
         self
     }
 
+    pub fn with_tagged_many(self, bundle: impl TaggedBundle) -> Self {
+        self.entity.with_tagged_many(bundle);
+        self
+    }
+
     pub fn is_tagged_virtual(&self, tag: impl Into<RawTag>) -> bool {
         self.entity.is_tagged_virtual(tag)
     }
 
+    pub fn has_all_tags(&self, tags: impl IntoIterator<Item = RawTag>) -> bool {
+        self.entity.has_all_tags(tags)
+    }
+
+    pub fn has_any_tag(&self, tags: impl IntoIterator<Item = RawTag>) -> bool {
+        self.entity.has_any_tag(tags)
+    }
+
     pub fn is_tagged_physical(self, tag: impl Into<RawTag>) -> bool {
         self.entity.is_tagged_physical(tag)
     }
@@ -589,6 +1375,26 @@ impl OwnedEntity {
         self.entity.archetypes()
     }
 
+    pub fn add_child(&self, child: Entity) {
+        self.entity.add_child(child)
+    }
+
+    pub fn remove_child(&self, child: Entity) -> bool {
+        self.entity.remove_child(child)
+    }
+
+    pub fn children(&self) -> Vec<Entity> {
+        self.entity.children()
+    }
+
+    pub fn parent(&self) -> Option<Entity> {
+        self.entity.parent()
+    }
+
+    pub fn id(&self) -> u64 {
+        self.entity.id()
+    }
+
     pub fn is_alive(&self) -> bool {
         self.entity.is_alive()
     }
@@ -616,6 +1422,203 @@ impl Drop for OwnedEntity {
     }
 }
 
+// === EntityCommands === //
+
+/// A buffer of structural changes to entities which can be recorded while a query is running and
+/// applied once it has finished.
+///
+/// Commands targeting an entity which has died by the time they are [`apply`](Self::apply)'d are
+/// ignored so that, e.g., several systems may safely queue the despawn of the same entity.
+/// Commands which are still queued when the buffer is dropped are discarded.
+#[derive(Default)]
+#[must_use = "queued commands only run once they are applied"]
+pub struct EntityCommands {
+    commands: Vec<Box<dyn FnOnce()>>,
+}
+
+impl fmt::Debug for EntityCommands {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EntityCommands")
+            .field("len", &self.commands.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl EntityCommands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn push(&mut self, command: impl 'static + FnOnce()) {
+        self.commands.push(Box::new(command));
+    }
+
+    fn push_for(&mut self, entity: Entity, command: impl 'static + FnOnce(Entity)) {
+        self.push(move || {
+            if entity.is_alive() {
+                command(entity);
+            }
+        });
+    }
+
+    pub fn despawn(&mut self, entity: Entity) {
+        self.push_for(entity, Entity::destroy);
+    }
+
+    pub fn insert<T: 'static>(&mut self, entity: Entity, comp: T) {
+        self.push_for(entity, move |entity| {
+            entity.insert(comp);
+        });
+    }
+
+    pub fn remove<T: 'static>(&mut self, entity: Entity) {
+        self.push_for(entity, |entity| {
+            entity.remove::<T>();
+        });
+    }
+
+    pub fn tag(&mut self, entity: Entity, tag: impl Into<RawTag>) {
+        let tag = tag.into();
+        self.push_for(entity, move |entity| entity.tag(tag));
+    }
+
+    pub fn untag(&mut self, entity: Entity, tag: impl Into<RawTag>) {
+        let tag = tag.into();
+        self.push_for(entity, move |entity| entity.untag(tag));
+    }
+
+    /// Runs every queued command in the order in which it was queued. Archetype changes made by
+    /// these commands only become visible to queries after the next [`flush`](crate::query::flush).
+    pub fn apply(&mut self) {
+        for command in mem::take(&mut self.commands) {
+            command();
+        }
+    }
+}
+
+// === Prefab === //
+
+/// A reusable template of components and tags from which any number of identical entities can be
+/// [`instantiate`](Self::instantiate)'d.
+#[derive(Clone, Default)]
+pub struct Prefab {
+    components: Vec<(TypeId, PrefabCtor)>,
+    tags: TagSet,
+}
+
+type PrefabCtor = Rc<dyn Fn(Entity)>;
+
+impl fmt::Debug for Prefab {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Prefab")
+            .field("components", &self.components.len())
+            .field("tags", &self.tags)
+            .finish()
+    }
+}
+
+impl Prefab {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a component which is cloned into every instance. Adding a component of a type which
+    /// the prefab already has replaces the old value.
+    pub fn with<T: 'static + Clone>(mut self, comp: T) -> Self {
+        let ctor: PrefabCtor = Rc::new(move |entity| {
+            entity.insert(comp.clone());
+        });
+
+        match self
+            .components
+            .iter_mut()
+            .find(|(ty, _)| *ty == TypeId::of::<T>())
+        {
+            Some((_, old_ctor)) => *old_ctor = ctor,
+            None => self.components.push((TypeId::of::<T>(), ctor)),
+        }
+
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<RawTag>) -> Self {
+        self.tags.add(tag);
+        self
+    }
+
+    pub fn with_tagged<T>(self, comp: T) -> Self
+    where
+        T: 'static + Clone + HasGlobalManagedTag<Component = T>,
+    {
+        self.with(comp).with_tag(GlobalTag::<T>)
+    }
+
+    pub fn has<T: 'static>(&self) -> bool {
+        self.components
+            .iter()
+            .any(|(ty, _)| *ty == TypeId::of::<T>())
+    }
+
+    pub fn tags(&self) -> &TagSet {
+        &self.tags
+    }
+
+    /// Spawns a fresh entity with a clone of every component in this prefab and all of its tags.
+    pub fn instantiate(&self) -> OwnedEntity {
+        let entity = OwnedEntity::new();
+
+        for (_, ctor) in &self.components {
+            ctor(entity.entity());
+        }
+
+        entity.tag_many(&self.tags);
+        entity
+    }
+}
+
+// === TaggedBundle === //
+
+/// A tuple of components with [global managed tags](HasGlobalManagedTag) which can be attached to an
+/// entity all at once using [`Entity::with_tagged_many`].
+pub trait TaggedBundle: 'static {
+    fn insert_and_tag(self, entity: Entity);
+}
+
+macro_rules! impl_tagged_bundle {
+    ($($para:ident:$field:tt),*) => {
+        impl<$($para),*> TaggedBundle for ($($para,)*)
+        where
+            $($para: HasGlobalManagedTag<Component = $para>,)*
+        {
+            fn insert_and_tag(self, entity: Entity) {
+                $(entity.insert(self.$field);)*
+                entity.tag_many([$(RawTag::from(GlobalTag::<$para>)),*]);
+            }
+        }
+    };
+}
+
+impl_tagged_bundle!(A:0);
+impl_tagged_bundle!(A:0, B:1);
+impl_tagged_bundle!(A:0, B:1, C:2);
+impl_tagged_bundle!(A:0, B:1, C:2, D:3);
+impl_tagged_bundle!(A:0, B:1, C:2, D:3, E:4);
+impl_tagged_bundle!(A:0, B:1, C:2, D:3, E:4, F:5);
+impl_tagged_bundle!(A:0, B:1, C:2, D:3, E:4, F:5, G:6);
+impl_tagged_bundle!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7);
+impl_tagged_bundle!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8);
+impl_tagged_bundle!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9);
+impl_tagged_bundle!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10);
+impl_tagged_bundle!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11);
+
 // === `CompRef` and `CompMut` === //
 
 pub struct CompRef<'b, T: ?Sized, B: ?Sized = T, O: Copy = Obj<T>> {
@@ -853,3 +1856,939 @@ impl<T: ?Sized + fmt::Display, B: ?Sized, O: Copy> fmt::Display for CompMut<'_,
         fmt::Display::fmt(&**self, f)
     }
 }
+
+// === Tests === //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        query::flush,
+        util::{hash_map::FxHashSet, testing::run_on_main_thread},
+    };
+
+    #[test]
+    fn components_by_name() {
+        #[derive(Debug, Eq, PartialEq)]
+        struct Health(u32);
+
+        #[derive(Debug, Eq, PartialEq)]
+        struct Label(&'static str);
+
+        run_on_main_thread(|| {
+            register_component_by_name::<Health>("test_by_name::health");
+            register_component_by_name::<Label>("test_by_name::label");
+
+            let entity = OwnedEntity::new();
+
+            assert!(entity
+                .insert_by_name("test_by_name::health", Box::new(Health(3)))
+                .unwrap()
+                .is_none());
+            assert!(entity
+                .insert_by_name("test_by_name::label", Box::new(Label("player")))
+                .unwrap()
+                .is_none());
+
+            let replaced = entity
+                .insert_by_name("test_by_name::health", Box::new(Health(4)))
+                .unwrap()
+                .unwrap();
+            assert_eq!(replaced.downcast_ref::<Health>(), Some(&Health(3)));
+
+            let health = entity.get_by_name("test_by_name::health").unwrap().unwrap();
+            assert_eq!(health.downcast_ref::<Health>(), Some(&Health(4)));
+            assert!(health.downcast_ref::<Label>().is_none());
+            drop(health);
+
+            let label = entity.get_by_name("test_by_name::label").unwrap().unwrap();
+            assert_eq!(label.downcast_ref::<Label>(), Some(&Label("player")));
+            drop(label);
+
+            assert_eq!(*entity.get::<Health>(), Health(4));
+        });
+    }
+
+    #[test]
+    fn shared_components_clone_on_write() {
+        #[derive(Debug, Clone, Eq, PartialEq)]
+        struct Mesh(Vec<u32>);
+
+        run_on_main_thread(|| {
+            let mesh = Arc::new(Mesh(vec![1, 2, 3]));
+            let first = OwnedEntity::new();
+            let second = OwnedEntity::new();
+
+            assert!(first.insert_shared(mesh.clone()).is_none());
+            assert!(second.insert_shared(mesh.clone()).is_none());
+
+            // Reads share the same allocation.
+            assert!(Arc::ptr_eq(
+                Shared::as_arc(&first.get::<Shared<Mesh>>()),
+                &mesh
+            ));
+            assert!(Arc::ptr_eq(
+                Shared::as_arc(&second.get::<Shared<Mesh>>()),
+                &mesh
+            ));
+            assert_eq!(first.get::<Shared<Mesh>>().0, [1, 2, 3]);
+            drop(mesh);
+
+            // Mutating a shared component through `get_mut` clones it first...
+            first.get_mut::<Shared<Mesh>>().0.push(4);
+            assert_eq!(**first.get::<Shared<Mesh>>(), Mesh(vec![1, 2, 3, 4]));
+            assert_eq!(**second.get::<Shared<Mesh>>(), Mesh(vec![1, 2, 3]));
+            assert!(!Arc::ptr_eq(
+                Shared::as_arc(&first.get::<Shared<Mesh>>()),
+                Shared::as_arc(&second.get::<Shared<Mesh>>()),
+            ));
+
+            // ...but a component which is no longer shared is mutated in place.
+            assert!(!Shared::is_shared(&second.get::<Shared<Mesh>>()));
+            let before = Arc::as_ptr(Shared::as_arc(&second.get::<Shared<Mesh>>()));
+            second.get_mut::<Shared<Mesh>>().0.clear();
+            assert_eq!(
+                Arc::as_ptr(Shared::as_arc(&second.get::<Shared<Mesh>>())),
+                before
+            );
+            assert_eq!(**second.get::<Shared<Mesh>>(), Mesh(Vec::new()));
+        });
+    }
+
+    #[test]
+    fn components_by_name_errors() {
+        struct Mana;
+
+        run_on_main_thread(|| {
+            register_component_by_name::<Mana>("test_by_name_errors::mana");
+
+            let entity = OwnedEntity::new();
+
+            assert_eq!(
+                entity.get_by_name("test_by_name_errors::missing").err(),
+                Some(ComponentNameError::UnknownName {
+                    name: "test_by_name_errors::missing".to_string(),
+                }),
+            );
+            assert!(matches!(
+                entity.insert_by_name("test_by_name_errors::missing", Box::new(Mana)),
+                Err(ComponentNameError::UnknownName { .. }),
+            ));
+
+            let err = entity
+                .insert_by_name("test_by_name_errors::mana", Box::new(1u32))
+                .unwrap_err();
+            assert!(matches!(err, ComponentNameError::WrongType { .. }));
+            assert!(err.to_string().contains("test_by_name_errors::mana"));
+            assert!(!entity.has::<Mana>());
+
+            // Registered components which are absent are not an error.
+            assert!(entity
+                .get_by_name("test_by_name_errors::mana")
+                .unwrap()
+                .is_none());
+        });
+    }
+
+    #[test]
+    fn on_remove_hooks_run_once_per_removal() {
+        use std::sync::Mutex;
+
+        #[derive(Debug)]
+        struct Socket(u32);
+
+        static CLOSED: Mutex<Vec<(Entity, u32)>> = Mutex::new(Vec::new());
+
+        fn take_closed() -> Vec<(Entity, u32)> {
+            std::mem::take(&mut *CLOSED.lock().unwrap())
+        }
+
+        run_on_main_thread(|| {
+            register_on_remove::<Socket>(|entity, socket| {
+                CLOSED.lock().unwrap().push((entity, socket.0));
+                socket.0 += 100;
+            });
+
+            let removed = OwnedEntity::new().with(Socket(1));
+            let despawned = OwnedEntity::new().with(Socket(2));
+            let bystander = OwnedEntity::new().with(Socket(3));
+            assert!(take_closed().is_empty());
+
+            // Removing through the storage runs the hooks before handing back the value.
+            let socket = removed.remove::<Socket>().unwrap();
+            assert_eq!(socket.0, 101);
+            assert_eq!(take_closed(), [(removed.entity(), 1)]);
+
+            // Removing an absent component doesn't run anything.
+            assert!(removed.remove::<Socket>().is_none());
+            assert!(take_closed().is_empty());
+
+            // Neither does replacing a component.
+            bystander.insert(Socket(4));
+            assert!(take_closed().is_empty());
+
+            // Despawning runs the hooks for the entity's components.
+            let despawned_id = despawned.entity();
+            drop(despawned);
+            assert_eq!(take_closed(), [(despawned_id, 2)]);
+
+            let bystander_id = bystander.entity();
+            drop((removed, bystander));
+            assert_eq!(take_closed(), [(bystander_id, 4)]);
+        });
+    }
+
+    #[test]
+    fn for_each_mut_visits_every_component() {
+        #[derive(Debug)]
+        struct Counter(u32);
+
+        run_on_main_thread(|| {
+            // More than a block's worth of flushed components...
+            let mut entities = (0..100)
+                .map(|i| OwnedEntity::new().with(Counter(i)))
+                .collect::<Vec<_>>();
+            flush();
+
+            // ...some still living in anonymous heaps...
+            entities.extend((100..105).map(|i| OwnedEntity::new().with(Counter(i))));
+
+            // ...and a few which were removed or never given one.
+            entities[7].remove::<Counter>();
+            entities[103].remove::<Counter>();
+            let bare = OwnedEntity::new();
+
+            let mut visited = FxHashMap::default();
+            storage::<Counter>().for_each_mut(|entity, counter| {
+                assert!(visited.insert(entity, counter.0).is_none());
+                counter.0 += 1000;
+            });
+
+            assert!(!visited.contains_key(&bare.entity()));
+            for (i, entity) in entities.iter().enumerate() {
+                if i == 7 || i == 103 {
+                    assert!(!visited.contains_key(&entity.entity()));
+                    assert!(!entity.has::<Counter>());
+                } else {
+                    assert_eq!(visited.get(&entity.entity()), Some(&(i as u32)));
+                    assert_eq!(entity.get::<Counter>().0, i as u32 + 1000);
+                }
+            }
+            assert_eq!(visited.len(), entities.len() - 2);
+
+            drop((entities, bare));
+            flush();
+        });
+    }
+
+    #[test]
+    fn try_borrow_reports_missing_and_dead_entities() {
+        #[derive(Debug)]
+        struct Ammo(u32);
+
+        struct Fuel;
+
+        macro_rules! assert_err {
+            ($result:expr, $variant:ident) => {
+                match $result {
+                    Err(ComponentAccessError::$variant) => {}
+                    Err(err) => panic!("expected {}, got {err:?}", stringify!($variant)),
+                    Ok(_) => panic!("expected {}, got a borrow", stringify!($variant)),
+                }
+            };
+        }
+
+        run_on_main_thread(|| {
+            let loaner = PotentialImmutableBorrow::new();
+            let mut loaner_mut = PotentialMutableBorrow::new();
+            let mut fuel_loaner_mut = PotentialMutableBorrow::<Fuel>::new();
+
+            let owned = OwnedEntity::new().with(Ammo(3));
+            let entity = owned.entity();
+            let obj = owned.obj::<Ammo>();
+
+            // Live entities with the component can be borrowed through every accessor.
+            assert_eq!(storage::<Ammo>().try_borrow(entity, &loaner).unwrap().0, 3);
+            assert_eq!(entity.try_borrow::<Ammo>(&loaner).unwrap().0, 3);
+            assert_eq!(owned.try_borrow::<Ammo>(&loaner).unwrap().0, 3);
+            assert_eq!(obj.try_borrow(&loaner).unwrap().0, 3);
+            obj.try_borrow_mut(&mut loaner_mut).unwrap().0 += 1;
+            assert_eq!(owned.try_borrow_mut::<Ammo>(&mut loaner_mut).unwrap().0, 4);
+
+            // Live entities without the component report it as missing.
+            let fuel_loaner = PotentialImmutableBorrow::<Fuel>::new();
+            assert_err!(storage::<Fuel>().try_borrow(entity, &fuel_loaner), Missing);
+            assert_err!(
+                storage::<Fuel>().try_borrow_mut(entity, &mut fuel_loaner_mut),
+                Missing
+            );
+            assert_err!(entity.try_borrow::<Fuel>(&fuel_loaner), Missing);
+            assert_err!(entity.try_borrow_mut::<Fuel>(&mut fuel_loaner_mut), Missing);
+            assert_err!(owned.try_borrow::<Fuel>(&fuel_loaner), Missing);
+            assert_err!(owned.try_borrow_mut::<Fuel>(&mut fuel_loaner_mut), Missing);
+
+            // ...as do `Obj`s whose component was removed from a live entity.
+            owned.remove::<Ammo>();
+            assert_err!(obj.try_borrow(&loaner), Missing);
+            assert_err!(obj.try_borrow_mut(&mut loaner_mut), Missing);
+
+            // Dead entities report themselves as dead, whether or not they had the component.
+            owned.insert(Ammo(5));
+            let obj = owned.obj::<Ammo>();
+            drop(owned);
+
+            assert_err!(storage::<Ammo>().try_borrow(entity, &loaner), EntityDead);
+            assert_err!(
+                storage::<Ammo>().try_borrow_mut(entity, &mut loaner_mut),
+                EntityDead
+            );
+            assert_err!(entity.try_borrow::<Ammo>(&loaner), EntityDead);
+            assert_err!(entity.try_borrow_mut::<Ammo>(&mut loaner_mut), EntityDead);
+            assert_err!(entity.try_borrow::<Fuel>(&fuel_loaner), EntityDead);
+            assert_err!(obj.try_borrow(&loaner), EntityDead);
+            assert_err!(obj.try_borrow_mut(&mut loaner_mut), EntityDead);
+
+            flush();
+        });
+    }
+
+    #[cfg(feature = "component-versions")]
+    #[test]
+    fn versions_advance_on_mutation_only() {
+        #[derive(Debug, Clone)]
+        struct Position(i32);
+
+        run_on_main_thread(|| {
+            let entity = OwnedEntity::new()
+                .with(Position(0))
+                .with_tag(crate::query::Tag::<Position>::new());
+            let bare = OwnedEntity::new();
+            assert_eq!(bare.version::<Position>(), None);
+
+            // Reads leave the version alone.
+            let v0 = entity.version::<Position>().unwrap();
+            assert_eq!(entity.get::<Position>().0, 0);
+            assert_eq!(storage::<Position>().get(entity.entity()).0, 0);
+            assert_eq!(entity.obj::<Position>().get().0, 0);
+            assert_eq!(entity.version::<Position>(), Some(v0));
+
+            // Mutable borrows and replacements advance it.
+            entity.get_mut::<Position>().0 += 1;
+            let v1 = entity.version::<Position>().unwrap();
+            assert_ne!(v1, v0);
+
+            entity.insert(Position(5));
+            let v2 = entity.version::<Position>().unwrap();
+            assert_ne!(v2, v1);
+
+            // Moving the component into its archetype heap carries the version along...
+            flush();
+            assert_eq!(entity.version::<Position>(), Some(v2));
+
+            // ...and bulk mutable borrows still advance it.
+            storage::<Position>().for_each_mut(|_, pos| pos.0 += 1);
+            let v3 = entity.version::<Position>().unwrap();
+            assert_ne!(v3, v2);
+
+            // A re-inserted component never reports a version it had before.
+            entity.remove::<Position>();
+            assert_eq!(entity.version::<Position>(), None);
+            entity.insert(Position(0));
+            let v4 = entity.version::<Position>().unwrap();
+            assert!(![v0, v1, v2, v3].contains(&v4));
+
+            drop((entity, bare));
+            flush();
+        });
+    }
+
+    #[test]
+    fn get_two_mut_borrows_both_components() {
+        #[derive(Debug)]
+        struct Pos(i32);
+
+        #[derive(Debug)]
+        struct Vel(i32);
+
+        run_on_main_thread(|| {
+            let entity = OwnedEntity::new().with(Pos(1)).with(Vel(3));
+
+            let (mut pos, mut vel) = entity.get_two_mut::<Pos, Vel>();
+            pos.0 += vel.0;
+            vel.0 = -vel.0;
+            drop((pos, vel));
+
+            assert_eq!(entity.get::<Pos>().0, 4);
+            assert_eq!(entity.get::<Vel>().0, -3);
+
+            // Both borrows are released once the guards are dropped.
+            let (vel, pos) = entity.entity().get_two_mut::<Vel, Pos>();
+            assert_eq!((vel.0, pos.0), (-3, 4));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "failed to find component of type")]
+    fn get_two_mut_panics_on_missing_component() {
+        struct Present;
+        struct Absent;
+
+        run_on_main_thread(|| {
+            let entity = OwnedEntity::new().with(Present);
+            let _ = entity.get_two_mut::<Present, Absent>();
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "twice")]
+    fn get_two_mut_panics_on_identical_types() {
+        struct Twice;
+
+        run_on_main_thread(|| {
+            let entity = OwnedEntity::new().with(Twice);
+            let _ = entity.get_two_mut::<Twice, Twice>();
+        });
+    }
+
+    #[test]
+    fn entity_commands_apply_after_iteration() {
+        use crate::query::{query, Tag};
+
+        #[derive(Debug)]
+        struct Hp(i32);
+
+        struct Dead;
+
+        run_on_main_thread(|| {
+            let tag = Tag::<Hp>::new();
+            let dead_tag = Tag::<Dead>::new();
+            let entities =
+                [5, -1, 3, -7].map(|hp| OwnedEntity::new().with(Hp(hp)).with_tag(tag).unmanage());
+            flush();
+
+            let mut commands = EntityCommands::new();
+            query! {
+                for (entity entity, ref hp in tag) {
+                    if hp.0 <= 0 {
+                        commands.insert(entity, Dead);
+                        commands.tag(entity, dead_tag);
+                        commands.despawn(entity);
+                    } else {
+                        commands.remove::<Hp>(entity);
+                    }
+                }
+            }
+
+            // Nothing happens until the commands are applied.
+            assert_eq!(commands.len(), 8);
+            assert!(entities.iter().all(|e| e.is_alive() && e.has::<Hp>()));
+
+            commands.apply();
+            assert!(commands.is_empty());
+            flush();
+
+            assert!(entities[0].is_alive() && !entities[0].has::<Hp>());
+            assert!(!entities[1].is_alive());
+            assert!(entities[2].is_alive() && !entities[2].has::<Hp>());
+            assert!(!entities[3].is_alive());
+
+            // Commands targeting dead entities are ignored.
+            commands.insert(entities[1], Hp(1));
+            commands.apply();
+            assert!(!entities[1].is_alive());
+
+            entities[0].destroy();
+            entities[2].destroy();
+            flush();
+        });
+    }
+
+    #[test]
+    fn prefab_instances_get_their_own_components_and_tags() {
+        use crate::query::{Tag, VirtualTag};
+
+        #[derive(Debug, Clone, Eq, PartialEq)]
+        struct Stats(Vec<u32>);
+
+        #[derive(Debug, Clone, Eq, PartialEq)]
+        struct Name(&'static str);
+
+        run_on_main_thread(|| {
+            let stats_tag = Tag::<Stats>::new();
+            let enemy = VirtualTag::new();
+
+            let prefab = Prefab::new()
+                .with(Stats(vec![1, 2]))
+                .with(Name("goblin"))
+                .with(Name("orc"))
+                .with_tag(stats_tag)
+                .with_tag(enemy);
+            assert!(prefab.has::<Stats>() && prefab.has::<Name>());
+
+            let instances = (0..3).map(|_| prefab.instantiate()).collect::<Vec<_>>();
+
+            // Each instance receives its own copy of every component and every tag.
+            instances[0].get_mut::<Stats>().0.push(3);
+            assert_eq!(instances[0].get::<Stats>().0, [1, 2, 3]);
+
+            for instance in &instances[1..] {
+                assert_eq!(*instance.get::<Stats>(), Stats(vec![1, 2]));
+            }
+
+            flush();
+            for instance in &instances {
+                assert_eq!(*instance.get::<Name>(), Name("orc"));
+                assert!(instance.entity().is_tagged_physical(stats_tag));
+                assert!(instance.is_tagged_virtual(enemy));
+            }
+
+            drop(instances);
+            flush();
+        });
+    }
+
+    #[test]
+    fn with_tagged_many_inserts_and_tags_every_component() {
+        #[derive(Debug, Eq, PartialEq)]
+        struct Pos(i32);
+
+        #[derive(Debug, Eq, PartialEq)]
+        struct Vel(i32);
+
+        #[derive(Debug, Eq, PartialEq)]
+        struct Mass(u32);
+
+        impl HasGlobalManagedTag for Pos {
+            type Component = Self;
+        }
+
+        impl HasGlobalManagedTag for Vel {
+            type Component = Self;
+        }
+
+        impl HasGlobalManagedTag for Mass {
+            type Component = Self;
+        }
+
+        run_on_main_thread(|| {
+            let entity = OwnedEntity::new().with_tagged_many((Pos(1), Vel(2), Mass(3)));
+
+            assert_eq!(*entity.get::<Pos>(), Pos(1));
+            assert_eq!(*entity.get::<Vel>(), Vel(2));
+            assert_eq!(*entity.get::<Mass>(), Mass(3));
+
+            flush();
+            assert!(entity.entity().is_tagged_physical(GlobalTag::<Pos>));
+            assert!(entity.entity().is_tagged_physical(GlobalTag::<Vel>));
+            assert!(entity.entity().is_tagged_physical(GlobalTag::<Mass>));
+
+            drop(entity);
+            flush();
+        });
+    }
+
+    #[test]
+    fn duplicates_are_independent() {
+        use crate::query::VirtualTag;
+
+        #[derive(Debug, Clone, Eq, PartialEq)]
+        struct Inventory(Vec<u32>);
+
+        #[derive(Debug, Clone, Eq, PartialEq)]
+        struct Unregistered;
+
+        run_on_main_thread(|| {
+            register_cloneable::<Inventory>();
+            let tag = VirtualTag::new();
+
+            let original = OwnedEntity::new()
+                .with(Inventory(vec![1]))
+                .with(Unregistered)
+                .with_tag(tag);
+            let copy = original.duplicate();
+
+            assert_ne!(copy.entity(), original.entity());
+            assert_eq!(*copy.get::<Inventory>(), Inventory(vec![1]));
+            assert!(copy.is_tagged_virtual(tag));
+            assert!(!copy.has::<Unregistered>());
+
+            copy.get_mut::<Inventory>().0.push(2);
+            assert_eq!(*original.get::<Inventory>(), Inventory(vec![1]));
+
+            // Both are alive independently of one another.
+            let copy_entity = copy.entity();
+            drop(original);
+            assert!(copy_entity.is_alive());
+            drop(copy);
+            assert!(!copy_entity.is_alive());
+
+            // Objects always clone their own component, even if it isn't registered.
+            let obj = OwnedObj::new(Unregistered);
+            let obj_copy = obj.duplicate();
+            assert_ne!(obj_copy.entity(), obj.entity());
+            assert_eq!(*obj_copy.get(), Unregistered);
+
+            drop((obj, obj_copy));
+            flush();
+        });
+    }
+
+    #[test]
+    fn ids_are_distinct_and_never_reused() {
+        run_on_main_thread(|| {
+            let a = OwnedEntity::new();
+            let b = OwnedEntity::new();
+            assert_ne!(a.id(), b.id());
+            assert_eq!(a.id(), a.entity().id());
+
+            let old_id = a.id();
+            drop(a);
+            flush();
+
+            let respawned = OwnedEntity::new();
+            assert_ne!(respawned.id(), old_id);
+            assert_ne!(respawned.id(), b.id());
+
+            drop((b, respawned));
+            flush();
+        });
+    }
+
+    #[test]
+    fn from_id_round_trips_alive_entities_only() {
+        run_on_main_thread(|| {
+            let alive = OwnedEntity::new();
+            let doomed = OwnedEntity::new();
+            let doomed_entity = doomed.entity();
+
+            assert_eq!(Entity::from_id(alive.id()), Some(alive.entity()));
+            assert_eq!(Entity::from_id(doomed.id()), Some(doomed_entity));
+
+            drop(doomed);
+            assert_eq!(Entity::from_id(doomed_entity.id()), None);
+            flush();
+            assert_eq!(Entity::from_id(doomed_entity.id()), None);
+
+            // Ids which never belonged to an entity resolve to nothing.
+            assert_eq!(Entity::from_id(u64::MAX), None);
+
+            drop(alive);
+            flush();
+        });
+    }
+
+    #[test]
+    fn filter_alive_preserves_the_order_of_survivors() {
+        run_on_main_thread(|| {
+            let mut entities = (0..6).map(|_| OwnedEntity::new()).collect::<Vec<_>>();
+            let handles = entities.iter().map(OwnedEntity::entity).collect::<Vec<_>>();
+
+            // Despawn a mix of entities, some of which are later flushed.
+            drop(entities.remove(4));
+            drop(entities.remove(1));
+            flush();
+            drop(entities.remove(0));
+
+            let mut shuffled = handles.clone();
+            shuffled.reverse();
+
+            assert_eq!(
+                Entity::filter_alive(&handles),
+                [handles[2], handles[3], handles[5]]
+            );
+            assert_eq!(
+                Entity::filter_alive(&shuffled),
+                [handles[5], handles[3], handles[2]]
+            );
+            assert!(Entity::filter_alive(&[]).is_empty());
+
+            drop(entities);
+            assert!(Entity::filter_alive(&handles).is_empty());
+            flush();
+        });
+    }
+
+    #[test]
+    fn debug_labels_can_be_set_and_overwritten() {
+        run_on_main_thread(|| {
+            let entity = OwnedEntity::new();
+            assert!(entity.debug_label().is_none());
+
+            entity.set_debug_label("first");
+            let label = entity.debug_label().map(|label| label.0.into_owned());
+
+            #[cfg(debug_assertions)]
+            assert_eq!(label.as_deref(), Some("first"));
+            #[cfg(not(debug_assertions))]
+            assert_eq!(label, None);
+
+            entity.set_debug_label(format!("second {}", 2));
+            let label = entity.debug_label().map(|label| label.0.into_owned());
+
+            #[cfg(debug_assertions)]
+            {
+                assert_eq!(label.as_deref(), Some("second 2"));
+                assert!(format!("{:?}", entity.entity()).contains("second 2"));
+            }
+            #[cfg(not(debug_assertions))]
+            assert_eq!(label, None);
+        });
+    }
+
+    #[test]
+    fn has_all_and_any_tags() {
+        use crate::query::VirtualTag;
+
+        run_on_main_thread(|| {
+            let enemy = VirtualTag::new();
+            let flying = VirtualTag::new();
+            let boss = VirtualTag::new();
+            let entity = OwnedEntity::new().with_tag(enemy);
+            flush();
+
+            // Pending tags are taken into account.
+            entity.tag(flying);
+
+            assert!(entity.entity().has_all_tags([enemy.raw(), flying.raw()]));
+            assert!(!entity.entity().has_all_tags([enemy.raw(), boss.raw()]));
+            assert!(entity.entity().has_any_tag([boss.raw(), flying.raw()]));
+            assert!(!entity.entity().has_any_tag([boss.raw()]));
+
+            // Every tag of the empty set is present while none of them is.
+            assert!(entity.entity().has_all_tags([]));
+            assert!(!entity.entity().has_any_tag([]));
+
+            flush();
+            entity.untag(enemy);
+            assert!(!entity.entity().has_all_tags([enemy.raw(), flying.raw()]));
+            assert!(entity.entity().has_any_tag([enemy.raw(), flying.raw()]));
+
+            drop(entity);
+            flush();
+        });
+    }
+
+    #[test]
+    fn storage_len_tracks_inserts_and_removals() {
+        struct Marker;
+
+        run_on_main_thread(|| {
+            let markers = storage::<Marker>();
+            assert_eq!(markers.len(), 0);
+            assert!(markers.is_empty());
+
+            let entities = (0..5)
+                .map(|_| OwnedEntity::new().with(Marker))
+                .collect::<Vec<_>>();
+            assert_eq!(markers.len(), 5);
+            assert!(!markers.is_empty());
+
+            // Moving components into their archetype heaps doesn't change the count...
+            flush();
+            assert_eq!(markers.len(), 5);
+
+            // ...and removals are reflected both before and after flushing.
+            entities[0].remove::<Marker>();
+            entities[1].remove::<Marker>();
+            assert_eq!(markers.len(), 3);
+            flush();
+            assert_eq!(markers.len(), 3);
+
+            drop(entities);
+            flush();
+            assert_eq!(markers.len(), 0);
+            assert!(markers.is_empty());
+        });
+    }
+
+    #[test]
+    fn reserve_preallocates_heaps_without_inserting() {
+        struct Reserved(u32);
+
+        run_on_main_thread(|| {
+            let reserved = storage::<Reserved>();
+            let heaps_before = crate::debug::heap_count();
+
+            reserved.reserve(200);
+            let heaps_reserved = crate::debug::heap_count();
+            assert!(heaps_reserved > heaps_before);
+            assert_eq!(reserved.len(), 0);
+
+            // Reserving capacity which already exists is a no-op.
+            reserved.reserve(200);
+            assert_eq!(crate::debug::heap_count(), heaps_reserved);
+
+            // Inserting up to the reservation doesn't allocate any more heaps.
+            let entities = (0..200)
+                .map(|i| OwnedEntity::new().with(Reserved(i)))
+                .collect::<Vec<_>>();
+            assert_eq!(crate::debug::heap_count(), heaps_reserved);
+            assert_eq!(reserved.len(), 200);
+
+            for (i, entity) in entities.iter().enumerate() {
+                assert_eq!(entity.get::<Reserved>().0, i as u32);
+            }
+
+            drop(entities);
+            flush();
+        });
+    }
+
+    #[test]
+    fn shrink_to_fit_releases_empty_heaps_and_keeps_survivors() {
+        struct Shrunk(u32);
+
+        run_on_main_thread(|| {
+            let token = MainThreadToken::acquire();
+            let shrunk = storage::<Shrunk>();
+            let heaps_before = crate::debug::heap_count();
+
+            let mut entities = (0..200)
+                .map(|i| OwnedEntity::new().with(Shrunk(i)))
+                .collect::<Vec<_>>();
+            let survivors = entities.split_off(195);
+            let slots = survivors
+                .iter()
+                .map(|entity| shrunk.get_slot(entity.entity()))
+                .collect::<Vec<_>>();
+
+            // Despawn most of the components and reserve some capacity we'll never use.
+            drop(entities);
+            shrunk.reserve(300);
+            let heaps_bloated = crate::debug::heap_count();
+
+            shrunk.shrink_to_fit();
+            let heaps_shrunk = crate::debug::heap_count();
+            assert!(heaps_shrunk < heaps_bloated);
+            assert!(heaps_shrunk > heaps_before);
+
+            // The survivors weren't moved so their slots are still valid.
+            for (i, (entity, slot)) in survivors.iter().zip(&slots).enumerate() {
+                assert_eq!(slot.borrow(token).0, 195 + i as u32);
+                assert_eq!(entity.get::<Shrunk>().0, 195 + i as u32);
+            }
+            assert_eq!(shrunk.len(), 5);
+
+            // Once everything is gone, shrinking releases every heap this storage allocated.
+            drop(survivors);
+            flush();
+            shrunk.shrink_to_fit();
+            assert_eq!(crate::debug::heap_count(), heaps_before);
+        });
+    }
+
+    #[test]
+    fn column_chunks_cover_every_component_once() {
+        #[derive(Debug)]
+        struct Column(u32);
+
+        run_on_main_thread(|| {
+            // More than a block's worth of flushed components...
+            let mut entities = (0..200)
+                .map(|i| OwnedEntity::new().with(Column(i)))
+                .collect::<Vec<_>>();
+            flush();
+
+            // ...some still living in anonymous heaps...
+            entities.extend((200..210).map(|i| OwnedEntity::new().with(Column(i))));
+
+            // ...and holes in both of them.
+            for i in [3, 4, 130, 205] {
+                entities[i].remove::<Column>();
+            }
+
+            let chunks = storage::<Column>().column_chunks();
+            let mut seen = FxHashSet::default();
+            let mut chunk_count = 0;
+
+            for chunk in chunks.iter() {
+                assert!(!chunk.is_empty());
+                assert!(chunk.len() <= MultiRefCellIndex::COUNT);
+                chunk_count += 1;
+
+                for column in chunk.iter() {
+                    assert!(seen.insert(column.0), "{column:?} was visited twice");
+                }
+            }
+            drop(chunks);
+
+            let expected = (0..210)
+                .filter(|i| ![3, 4, 130, 205].contains(i))
+                .collect::<FxHashSet<_>>();
+            assert_eq!(seen, expected);
+
+            // The holes split their blocks into several runs.
+            assert!(chunk_count >= 4);
+
+            drop(entities);
+            flush();
+        });
+    }
+
+    #[test]
+    fn insert_with_only_runs_on_a_miss() {
+        #[derive(Debug, Eq, PartialEq)]
+        struct Lazy(u32);
+
+        run_on_main_thread(|| {
+            let entity = OwnedEntity::new();
+            let mut calls = 0;
+
+            assert!(entity.insert_with(|| {
+                calls += 1;
+                Lazy(1)
+            }));
+            assert_eq!(calls, 1);
+            assert_eq!(*entity.get::<Lazy>(), Lazy(1));
+
+            // The component is already present so the closure must not run.
+            assert!(!entity.insert_with(|| {
+                calls += 1;
+                Lazy(2)
+            }));
+            assert_eq!(calls, 1);
+            assert_eq!(*entity.get::<Lazy>(), Lazy(1));
+
+            // The same holds once the component has been flushed into its archetype.
+            flush();
+            assert!(!storage::<Lazy>().insert_with(entity.entity(), || unreachable!()));
+            assert_eq!(*entity.get::<Lazy>(), Lazy(1));
+
+            drop(entity);
+            flush();
+        });
+    }
+
+    #[test]
+    fn get_or_insert_with_borrows_new_and_existing_components() {
+        #[derive(Debug, Eq, PartialEq)]
+        struct Counter(u32);
+
+        run_on_main_thread(|| {
+            let counters = storage::<Counter>();
+            let entity = OwnedEntity::new();
+
+            // On a miss, the new component is inserted and can be borrowed right away.
+            counters
+                .get_or_insert_with(entity.entity(), || Counter(0))
+                .0 += 1;
+            assert_eq!(*entity.get::<Counter>(), Counter(1));
+
+            // On a hit, the closure is skipped and the existing component is borrowed.
+            counters
+                .get_or_insert_with(entity.entity(), || unreachable!())
+                .0 += 1;
+            assert_eq!(*entity.get::<Counter>(), Counter(2));
+
+            flush();
+            counters
+                .get_or_insert_with(entity.entity(), || unreachable!())
+                .0 += 1;
+            assert_eq!(*entity.get::<Counter>(), Counter(3));
+            assert_eq!(counters.len(), 1);
+
+            drop(entity);
+            flush();
+        });
+    }
+}
@@ -5,21 +5,25 @@ use std::{
     marker::PhantomData,
     mem,
     ops::{ControlFlow, Deref, DerefMut},
+    sync::{mpsc, Mutex},
+    time::Instant,
 };
 
 use derive_where::derive_where;
 
 use crate::{
+    core::token::assert_main_thread,
     entity::{Entity, OwnedEntity},
     query::{
         ArchetypeId, ArchetypeQueryInfo, DriverArchIterInfo, DriverBlockIterInfo,
         DriverHeapIterInfo, MultiDriverItem, MultiQueryDriver, MultiQueryDriverTypes,
         QueryBlockElementHandler, QueryBlockHandler, QueryDriver, QueryDriverEntryHandler,
         QueryDriverTarget, QueryDriverTypes, QueryHeapHandler, QueryKey, QueryVersionMap, RawTag,
+        Tag,
     },
     util::{
         hash_map::{FxHashMap, FxHashSet},
-        misc::{IsUnit, Truthy},
+        misc::{unpoison, IsUnit, Truthy},
     },
 };
 
@@ -70,6 +74,10 @@ pub trait ClearableEvent {
     fn clear(&mut self);
 }
 
+pub trait CountableEvent {
+    fn event_count(&self) -> usize;
+}
+
 // === VecEventList === //
 
 #[derive(Debug)]
@@ -77,18 +85,16 @@ pub trait ClearableEvent {
 pub struct VecEventList<T> {
     gen: u64,
     process_list: RefCell<QueryVersionMap<usize>>,
-    events: Vec<(Entity, T)>,
-    owned: Vec<OwnedEntity>,
+    events: Vec<(Entity, T, Option<OwnedEntity>)>,
 }
 
 impl<T> EventTarget<T> for VecEventList<T> {
     fn fire_cx(&mut self, target: Entity, event: T, _context: ()) {
-        self.events.push((target, event));
+        self.events.push((target, event, None));
     }
 
     fn fire_owned_cx(&mut self, target: OwnedEntity, event: T, _context: ()) {
-        self.fire(target.entity(), event);
-        self.owned.push(target);
+        self.events.push((target.entity(), event, Some(target)));
     }
 }
 
@@ -110,7 +116,12 @@ impl<T> ClearableEvent for VecEventList<T> {
         self.gen += 1;
         self.process_list.get_mut().clear();
         self.events.clear();
-        self.owned.clear();
+    }
+}
+
+impl<T> CountableEvent for VecEventList<T> {
+    fn event_count(&self) -> usize {
+        self.events.len()
     }
 }
 
@@ -135,42 +146,234 @@ impl<'a, T> QueryDriverTypes<'a> for VecEventList<T> {
     type BlockIterInfo = ();
 }
 
-impl<T> QueryDriver for VecEventList<T> {
-    fn drive_query<B>(
+impl<T> VecEventList<T> {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            events: Vec::with_capacity(capacity),
+            ..Default::default()
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.events.capacity()
+    }
+
+    /// Ensures that at least `additional` more events can be fired without reallocating.
+    pub fn reserve(&mut self, additional: usize) {
+        self.events.reserve(additional);
+    }
+
+    /// Releases the memory of events which have been cleared or processed out of the list.
+    pub fn shrink_to_fit(&mut self) {
+        self.events.shrink_to_fit();
+    }
+
+    pub fn process_rev(&self) -> VecEventListRev<'_, T> {
+        VecEventListRev(self)
+    }
+
+    pub fn drain_into(&mut self, other: &mut impl EventTarget<T>) {
+        for (target, event, owner) in self.events.drain(..) {
+            match owner {
+                Some(owner) => other.fire_owned(owner, event),
+                None => other.fire(target, event),
+            }
+        }
+
+        self.clear();
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(Entity, &T) -> bool) {
+        let Self {
+            gen,
+            process_list,
+            events,
+        } = self;
+
+        // Maps old event indices to new ones so that we can fix up the query cursors.
+        let mut kept_before = Vec::with_capacity(events.len() + 1);
+        let mut kept = 0;
+
+        events.retain(|(target, event, _owner)| {
+            kept_before.push(kept);
+
+            let keep = f(*target, event);
+            kept += keep as usize;
+            keep
+        });
+
+        kept_before.push(kept);
+
+        for cursor in process_list.get_mut().values_mut() {
+            *cursor = kept_before[*cursor];
+        }
+
+        // Bump the generation so that removing and re-adding events doesn't produce a stale
+        // version.
+        *gen += 1;
+    }
+
+    /// Removes and handles at most `max` of the oldest events, leaving the remainder queued in
+    /// their original order. Returns the number of events handled.
+    ///
+    /// Owned entities attached to handled events are dropped right after their event is handled.
+    pub fn process_with_budget(&mut self, max: usize, mut handler: impl FnMut(Entity, T)) -> usize {
+        let count = max.min(self.events.len());
+
+        for (target, event, owner) in self.events.drain(..count) {
+            handler(target, event);
+            drop(owner);
+        }
+
+        for cursor in self.process_list.get_mut().values_mut() {
+            *cursor = cursor.saturating_sub(count);
+        }
+
+        // See `retain` for why we bump the generation.
+        self.gen += 1;
+
+        count
+    }
+
+    /// Returns a predicate which accepts the event targets which were tagged with every tag in
+    /// `tags` as of the last flush. An empty tag list accepts every target.
+    fn tag_filter(tags: impl IntoIterator<Item = RawTag>) -> impl Fn(Entity) -> bool {
+        let archetypes = ArchetypeId::in_intersection(tags, false).map(|archetypes| {
+            archetypes
+                .into_iter()
+                .map(|v| v.archetype())
+                .collect::<FxHashSet<_>>()
+        });
+
+        move |entity| match &archetypes {
+            Some(archetypes) => archetypes.contains(
+                &entity
+                    .archetypes()
+                    .expect("VecEventList has dead entity")
+                    .physical,
+            ),
+            None => true,
+        }
+    }
+
+    fn drive_query_ordered<D, B>(
         &self,
         query_key: impl QueryKey,
         tags: impl IntoIterator<Item = RawTag>,
-        _include_entities: bool,
-        mut handler: impl QueryDriverEntryHandler<Self, B>,
-    ) -> ControlFlow<B> {
+        reversed: bool,
+        mut handler: impl QueryDriverEntryHandler<D, B>,
+    ) -> ControlFlow<B>
+    where
+        D: QueryDriver + for<'a> QueryDriverTypes<'a, Item = &'a T>,
+    {
         let start = mem::replace(
             self.process_list.borrow_mut().entry(query_key, || 0),
             self.events.len(),
         );
 
-        if let Some(archetypes) = ArchetypeId::in_intersection(tags, false) {
-            let archetypes = archetypes
-                .into_iter()
-                .map(|v| v.archetype())
-                .collect::<FxHashSet<_>>();
-
-            for (entity, item) in &self.events[start..] {
-                if archetypes.contains(
-                    &entity
-                        .archetypes()
-                        .expect("VecEventList has dead entity")
-                        .physical,
-                ) {
-                    handler.process_arbitrary(*entity, item)?;
-                }
+        let filter = Self::tag_filter(tags);
+
+        let mut process = |(entity, item, _owner): &(Entity, T, Option<OwnedEntity>)| {
+            if !filter(*entity) {
+                return ControlFlow::Continue(());
             }
+
+            handler.process_arbitrary(*entity, item)
+        };
+
+        let events = &self.events[start..];
+
+        if reversed {
+            events.iter().rev().try_for_each(&mut process)
         } else {
-            for (entity, item) in &self.events[start..] {
-                handler.process_arbitrary(*entity, item)?;
-            }
+            events.iter().try_for_each(&mut process)
         }
+    }
+}
 
-        ControlFlow::Continue(())
+impl<T> QueryDriver for VecEventList<T> {
+    fn drive_query<B>(
+        &self,
+        query_key: impl QueryKey,
+        tags: impl IntoIterator<Item = RawTag>,
+        _include_entities: bool,
+        handler: impl QueryDriverEntryHandler<Self, B>,
+    ) -> ControlFlow<B> {
+        self.drive_query_ordered(query_key, tags, false, handler)
+    }
+
+    fn foreach_heap<B>(
+        &self,
+        _arch: &ArchetypeQueryInfo,
+        _arch_userdata: &mut DriverArchIterInfo<'_, Self>,
+        _handler: impl QueryHeapHandler<Self, B>,
+    ) -> ControlFlow<B> {
+        unimplemented!()
+    }
+
+    fn foreach_block<B>(
+        &self,
+        _heap_idx: usize,
+        _heap_len: usize,
+        _heap_userdata: &mut DriverHeapIterInfo<'_, Self>,
+        _handler: impl QueryBlockHandler<Self, B>,
+    ) -> ControlFlow<B> {
+        unimplemented!()
+    }
+
+    fn foreach_element_in_full_block<B>(
+        &self,
+        _block: usize,
+        _block_userdata: &mut DriverBlockIterInfo<'_, Self>,
+        _handler: impl QueryBlockElementHandler<Self, B>,
+    ) -> ControlFlow<B> {
+        unimplemented!()
+    }
+
+    fn foreach_element_in_semi_block<B>(
+        &self,
+        _block: usize,
+        _block_userdata: &mut DriverBlockIterInfo<'_, Self>,
+        _handler: impl QueryBlockElementHandler<Self, B>,
+    ) -> ControlFlow<B> {
+        unimplemented!()
+    }
+}
+
+#[derive(Debug)]
+#[derive_where(Copy, Clone)]
+pub struct VecEventListRev<'a, T>(&'a VecEventList<T>);
+
+impl<'a, 'b, T> MultiQueryDriverTypes<'a> for VecEventListRev<'b, T> {
+    type Item = &'a T;
+}
+
+impl<T> MultiQueryDriver for VecEventListRev<'_, T> {
+    fn drive_multi_query<T2: QueryDriverTarget, B>(
+        &self,
+        target: &mut T2,
+        f: impl FnMut((T2::Input<'_>, MultiDriverItem<'_, Self>)) -> ControlFlow<B>,
+    ) -> ControlFlow<B> {
+        target.handle_driver(self, f)
+    }
+}
+
+impl<'a, 'b, T> QueryDriverTypes<'a> for VecEventListRev<'b, T> {
+    type Item = &'a T;
+    type ArchIterInfo = ();
+    type HeapIterInfo = ();
+    type BlockIterInfo = ();
+}
+
+impl<T> QueryDriver for VecEventListRev<'_, T> {
+    fn drive_query<B>(
+        &self,
+        query_key: impl QueryKey,
+        tags: impl IntoIterator<Item = RawTag>,
+        _include_entities: bool,
+        handler: impl QueryDriverEntryHandler<Self, B>,
+    ) -> ControlFlow<B> {
+        self.0.drive_query_ordered(query_key, tags, true, handler)
     }
 
     fn foreach_heap<B>(
@@ -211,6 +414,413 @@ impl<T> QueryDriver for VecEventList<T> {
     }
 }
 
+// === BoundedEventList === //
+
+/// A [`VecEventList`] which refuses to accept events once it holds `capacity` of them. The list
+/// dereferences to its underlying [`VecEventList`] for processing.
+#[derive(Debug)]
+pub struct BoundedEventList<T> {
+    capacity: usize,
+    events: VecEventList<T>,
+}
+
+impl<T> BoundedEventList<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            events: VecEventList {
+                events: Vec::with_capacity(capacity),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.events.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() >= self.capacity
+    }
+
+    pub fn try_fire(&mut self, target: Entity, event: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(event);
+        }
+
+        self.events.fire(target, event);
+        Ok(())
+    }
+
+    pub fn try_fire_owned(
+        &mut self,
+        target: OwnedEntity,
+        event: T,
+    ) -> Result<(), (OwnedEntity, T)> {
+        if self.is_full() {
+            return Err((target, event));
+        }
+
+        self.events.fire_owned(target, event);
+        Ok(())
+    }
+
+    pub fn drain_into(&mut self, other: &mut impl EventTarget<T>) {
+        self.events.drain_into(other);
+    }
+
+    pub fn retain(&mut self, f: impl FnMut(Entity, &T) -> bool) {
+        self.events.retain(f);
+    }
+
+    pub fn process_with_budget(&mut self, max: usize, handler: impl FnMut(Entity, T)) -> usize {
+        self.events.process_with_budget(max, handler)
+    }
+}
+
+impl<T> ProcessableEvent for BoundedEventList<T> {
+    type Version = (u64, usize);
+
+    fn version(&self) -> Self::Version {
+        self.events.version()
+    }
+
+    fn has_updated_since(&self, old: Self::Version) -> (bool, Self::Version) {
+        self.events.has_updated_since(old)
+    }
+}
+
+impl<T> ClearableEvent for BoundedEventList<T> {
+    fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl<T> Deref for BoundedEventList<T> {
+    type Target = VecEventList<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.events
+    }
+}
+
+// === InstantEventList === //
+
+/// A [`VecEventList`] which records the [`Instant`] at which each event was fired. The list
+/// dereferences to a [`VecEventList`] of `(Instant, T)` pairs for processing.
+#[derive(Debug)]
+#[derive_where(Default)]
+pub struct InstantEventList<T> {
+    events: VecEventList<(Instant, T)>,
+}
+
+impl<T> InstantEventList<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn drain_into(&mut self, other: &mut impl EventTarget<(Instant, T)>) {
+        self.events.drain_into(other);
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(Entity, Instant, &T) -> bool) {
+        self.events
+            .retain(|target, (fired_at, event)| f(target, *fired_at, event));
+    }
+}
+
+impl<T> EventTarget<T> for InstantEventList<T> {
+    fn fire_cx(&mut self, target: Entity, event: T, _context: ()) {
+        self.events.fire(target, (Instant::now(), event));
+    }
+
+    fn fire_owned_cx(&mut self, target: OwnedEntity, event: T, _context: ()) {
+        self.events.fire_owned(target, (Instant::now(), event));
+    }
+}
+
+impl<T> ProcessableEvent for InstantEventList<T> {
+    type Version = (u64, usize);
+
+    fn version(&self) -> Self::Version {
+        self.events.version()
+    }
+
+    fn has_updated_since(&self, old: Self::Version) -> (bool, Self::Version) {
+        self.events.has_updated_since(old)
+    }
+}
+
+impl<T> ClearableEvent for InstantEventList<T> {
+    fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl<T> Deref for InstantEventList<T> {
+    type Target = VecEventList<(Instant, T)>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.events
+    }
+}
+
+// === RoutedEventList === //
+
+type RoutedEventHandler<T> = (RawTag, Box<dyn Send + FnMut(Entity, &T)>);
+
+/// An event list whose handlers are each associated with a component's [`Tag`]. When the list is
+/// [`process`](RoutedEventList::process)ed, every event is dispatched to the handlers whose tag
+/// was present on the event's target as of the last flush, just like a [`query!`] over the same
+/// tag would see it.
+///
+/// Handlers are run in registration order and each handler sees its events in the order in which
+/// they were fired.
+///
+/// [`query!`]: crate::query::query
+#[derive_where(Default)]
+pub struct RoutedEventList<T> {
+    handlers: Vec<RoutedEventHandler<T>>,
+    events: VecEventList<T>,
+}
+
+impl<T> fmt::Debug for RoutedEventList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoutedEventList")
+            .field("handler_count", &self.handlers.len())
+            .field("event_count", &self.events.events.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> RoutedEventList<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<C: 'static>(
+        &mut self,
+        tag: impl Into<Tag<C>>,
+        handler: impl 'static + Send + FnMut(Entity, &T),
+    ) {
+        self.handlers.push((tag.into().raw(), Box::new(handler)));
+    }
+
+    pub fn process(&mut self) {
+        for (tag, handler) in &mut self.handlers {
+            let filter = VecEventList::<T>::tag_filter([*tag]);
+
+            for (target, event, _owner) in &self.events.events {
+                if filter(*target) {
+                    handler(*target, event);
+                }
+            }
+        }
+
+        self.events.clear();
+    }
+}
+
+impl<T> EventTarget<T> for RoutedEventList<T> {
+    fn fire_cx(&mut self, target: Entity, event: T, _context: ()) {
+        self.events.fire(target, event);
+    }
+
+    fn fire_owned_cx(&mut self, target: OwnedEntity, event: T, _context: ()) {
+        self.events.fire_owned(target, event);
+    }
+}
+
+impl<T> ClearableEvent for RoutedEventList<T> {
+    fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+// === EventLog === //
+
+/// A [`VecEventList`] which keeps a copy of every event ever fired into it. Unlike the events
+/// themselves, the log survives [`clear`](ClearableEvent::clear) and can be re-fired in its
+/// original order with [`replay_into`](EventLog::replay_into).
+#[derive(Debug)]
+#[derive_where(Default)]
+pub struct EventLog<T> {
+    log: Vec<(Entity, T)>,
+    events: VecEventList<T>,
+}
+
+impl<T: Clone> EventLog<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn log(&self) -> &[(Entity, T)] {
+        &self.log
+    }
+
+    pub fn clear_log(&mut self) {
+        self.log.clear();
+    }
+
+    pub fn replay_into(&self, target: &mut impl EventTarget<T>) {
+        for (entity, event) in &self.log {
+            target.fire(*entity, event.clone());
+        }
+    }
+}
+
+impl<T: Clone> EventTarget<T> for EventLog<T> {
+    fn fire_cx(&mut self, target: Entity, event: T, _context: ()) {
+        self.log.push((target, event.clone()));
+        self.events.fire(target, event);
+    }
+
+    fn fire_owned_cx(&mut self, target: OwnedEntity, event: T, _context: ()) {
+        self.log.push((target.entity(), event.clone()));
+        self.events.fire_owned(target, event);
+    }
+}
+
+impl<T> ProcessableEvent for EventLog<T> {
+    type Version = (u64, usize);
+
+    fn version(&self) -> Self::Version {
+        self.events.version()
+    }
+
+    fn has_updated_since(&self, old: Self::Version) -> (bool, Self::Version) {
+        self.events.has_updated_since(old)
+    }
+}
+
+impl<T> ClearableEvent for EventLog<T> {
+    fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl<T> Deref for EventLog<T> {
+    type Target = VecEventList<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.events
+    }
+}
+
+// === ChannelEventList === //
+
+type ChannelEvent<E> = (Entity, E, Option<OwnedEntity>);
+
+/// Owned entities whose events could not be delivered because their [`ChannelEventList`] was
+/// dropped. These may have been fired from any thread so they are destroyed by the next
+/// [`flush`](crate::query::flush) instead.
+static ORPHANED_OWNERS: Mutex<Vec<OwnedEntity>> = Mutex::new(Vec::new());
+
+pub(crate) fn destroy_orphaned_owners() {
+    let orphans = mem::take(&mut *unpoison(ORPHANED_OWNERS.lock()));
+    drop(orphans);
+}
+
+/// An event list which can be fired from any thread through a [`ChannelEventSender`]. Events are
+/// only ever handled on the main thread through [`process`](ChannelEventList::process) or
+/// [`drain_into`](ChannelEventList::drain_into).
+#[derive(Debug)]
+pub struct ChannelEventList<E: Send> {
+    sender: mpsc::Sender<ChannelEvent<E>>,
+    receiver: mpsc::Receiver<ChannelEvent<E>>,
+}
+
+impl<E: Send> Default for ChannelEventList<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: Send> ChannelEventList<E> {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self { sender, receiver }
+    }
+
+    pub fn sender(&self) -> ChannelEventSender<E> {
+        ChannelEventSender {
+            sender: self.sender.clone(),
+        }
+    }
+
+    pub fn process(&mut self, mut handler: impl FnMut(Entity, E)) {
+        assert_main_thread("process a channel event list");
+
+        for (target, event, owned) in self.receiver.try_iter() {
+            handler(target, event);
+            drop(owned);
+        }
+    }
+
+    pub fn drain_into(&mut self, other: &mut impl EventTarget<E>) {
+        assert_main_thread("process a channel event list");
+
+        for (target, event, owned) in self.receiver.try_iter() {
+            match owned {
+                Some(owned) => other.fire_owned(owned, event),
+                None => other.fire(target, event),
+            }
+        }
+    }
+}
+
+impl<E: Send> EventTarget<E> for ChannelEventList<E> {
+    fn fire_cx(&mut self, target: Entity, event: E, _context: ()) {
+        // We own the receiver so this can never fail.
+        let _ = self.sender.send((target, event, None));
+    }
+
+    fn fire_owned_cx(&mut self, target: OwnedEntity, event: E, _context: ()) {
+        let _ = self.sender.send((target.entity(), event, Some(target)));
+    }
+}
+
+impl<E: Send> ClearableEvent for ChannelEventList<E> {
+    fn clear(&mut self) {
+        // Clearing drops the owned entities of the discarded events.
+        assert_main_thread("clear a channel event list");
+        for _ in self.receiver.try_iter() {}
+    }
+}
+
+/// A handle through which events can be fired into a [`ChannelEventList`] from any thread.
+///
+/// If the list has been dropped, fired events are discarded. Their owned entities, if any, are
+/// destroyed by the next [`flush`](crate::query::flush) since the sender may not be on the main
+/// thread.
+#[derive(Debug)]
+#[derive_where(Clone)]
+pub struct ChannelEventSender<E: Send> {
+    sender: mpsc::Sender<ChannelEvent<E>>,
+}
+
+impl<E: Send> EventTarget<E> for ChannelEventSender<E> {
+    fn fire_cx(&mut self, target: Entity, event: E, _context: ()) {
+        let _ = self.sender.send((target, event, None));
+    }
+
+    fn fire_owned_cx(&mut self, target: OwnedEntity, event: E, _context: ()) {
+        if let Err(mpsc::SendError((_, _, Some(owned)))) =
+            self.sender.send((target.entity(), event, Some(target)))
+        {
+            unpoison(ORPHANED_OWNERS.lock()).push(owned);
+        }
+    }
+}
+
 // === CountingEvent === //
 
 #[derive(Debug, Default)]
@@ -235,6 +845,10 @@ impl<E> CountingEvent<E> {
         had_event
     }
 
+    pub fn take(&mut self) -> u64 {
+        mem::take(&mut self.count)
+    }
+
     pub fn take_one_event(&mut self) -> bool {
         let had_event = self.has_event();
         self.count -= 1;
@@ -379,6 +993,7 @@ pub trait SimpleEventList:
     + Send
     + EventTarget<Self::Event>
     + ClearableEvent
+    + CountableEvent
     + MultiQueryDriver
     + for<'a> MultiQueryDriverTypes<'a, Item = &'a Self::Event>
 {
@@ -387,7 +1002,7 @@ pub trait SimpleEventList:
 
 impl<E, L> SimpleEventList for L
 where
-    L: 'static + Default + Send + EventTarget<E> + ClearableEvent,
+    L: 'static + Default + Send + EventTarget<E> + ClearableEvent + CountableEvent,
     L: MultiQueryDriver + for<'a> MultiQueryDriverTypes<'a, Item = &'a E>,
 {
     type Event = E;
@@ -419,19 +1034,25 @@ pub struct EventGroup<G: ?Sized> {
     version: u64,
 }
 
-trait ErasedEvent: Any + Send + ClearableEvent {
+trait ErasedEvent: Any + Send + ClearableEvent + CountableEvent {
     fn ty_name(&self) -> &'static str;
 
+    fn event_ty_name(&self) -> &'static str;
+
     fn as_any(&self) -> &dyn Any;
 
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
-impl<T: Any + Send + ClearableEvent> ErasedEvent for T {
+impl<T: SimpleEventList> ErasedEvent for T {
     fn ty_name(&self) -> &'static str {
         type_name::<T>()
     }
 
+    fn event_ty_name(&self) -> &'static str {
+        type_name::<T::Event>()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -511,6 +1132,20 @@ impl<G: ?Sized> EventGroup<G> {
             .fire_owned(target, event);
     }
 
+    /// Lists every event list this group has created alongside the number of events currently
+    /// queued in it, in the order in which the lists were first written to.
+    pub fn event_stats(&self) -> Vec<EventListStat> {
+        self.events
+            .iter()
+            .map(|list| EventListStat {
+                list_type: list.as_any().type_id(),
+                list_type_name: list.ty_name(),
+                event_type_name: list.event_ty_name(),
+                count: list.event_count(),
+            })
+            .collect()
+    }
+
     pub fn clear_single_raw<L: SimpleEventList>(&mut self) {
         // N.B. we don't update the version here since this action didn't actually add any new events.
 
@@ -594,6 +1229,12 @@ impl<G: ?Sized> ProcessableEvent for EventGroup<G> {
     }
 }
 
+impl<G: ?Sized> CountableEvent for EventGroup<G> {
+    fn event_count(&self) -> usize {
+        self.events.iter().map(|list| list.event_count()).sum()
+    }
+}
+
 impl<G: ?Sized> ClearableEvent for EventGroup<G> {
     fn clear(&mut self) {
         // N.B. we don't update the version here since this action didn't actually add any new events.
@@ -604,6 +1245,14 @@ impl<G: ?Sized> ClearableEvent for EventGroup<G> {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct EventListStat {
+    pub list_type: TypeId,
+    pub list_type_name: &'static str,
+    pub event_type_name: &'static str,
+    pub count: usize,
+}
+
 #[derive_where(Debug)]
 pub struct EventGroupWriter<'g, G: ?Sized> {
     group: RefCell<&'g mut EventGroup<G>>,
@@ -716,3 +1365,495 @@ where
         self.get(false).clear();
     }
 }
+
+// === Tests === //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{query::query, util::testing::run_on_main_thread};
+
+    fn collect_rev(events: &VecEventList<u32>) -> Vec<u32> {
+        let mut seen = Vec::new();
+        query! {
+            for (event ev in events.process_rev()) {
+                seen.push(*ev);
+            }
+        }
+        seen
+    }
+
+    fn collect_fwd(events: &VecEventList<u32>) -> Vec<u32> {
+        let mut seen = Vec::new();
+        query! {
+            for (event ev in events) {
+                seen.push(*ev);
+            }
+        }
+        seen
+    }
+
+    fn collect_instants(events: &VecEventList<(Instant, u32)>) -> Vec<(Instant, u32)> {
+        let mut seen = Vec::new();
+        query! {
+            for (event ev in events) {
+                seen.push(*ev);
+            }
+        }
+        seen
+    }
+
+    #[test]
+    fn process_rev_is_lifo() {
+        run_on_main_thread(|| {
+            let target = OwnedEntity::new();
+            let mut events = VecEventList::default();
+
+            for i in 0..4 {
+                events.fire(target.entity(), i);
+            }
+
+            assert_eq!(collect_rev(&events), [3, 2, 1, 0]);
+
+            // Already processed events are not visited again.
+            assert!(collect_rev(&events).is_empty());
+        });
+    }
+
+    #[test]
+    fn retain_keeps_order_and_owners() {
+        run_on_main_thread(|| {
+            let target = OwnedEntity::new();
+            let owned = OwnedEntity::new();
+            let owned_entity = owned.entity();
+            let mut events = VecEventList::default();
+
+            // An unowned event targeting the same entity precedes the owned one so that the owner
+            // cannot be matched up by position.
+            events.fire(owned_entity, 0);
+            events.fire(target.entity(), 1);
+            events.fire_owned(owned, 2);
+            events.fire(target.entity(), 3);
+            events.fire(target.entity(), 4);
+
+            events.retain(|_, &ev| ev % 2 == 0);
+            assert!(owned_entity.is_alive());
+            assert_eq!(collect_fwd(&events), [0, 2, 4]);
+
+            events.retain(|_, &ev| ev != 0);
+            assert!(owned_entity.is_alive());
+
+            let mut drained = VecEventList::default();
+            events.drain_into(&mut drained);
+            assert!(owned_entity.is_alive());
+            assert_eq!(collect_fwd(&drained), [2, 4]);
+
+            drained.retain(|_, &ev| ev != 2);
+            assert!(!owned_entity.is_alive());
+            assert!(collect_fwd(&drained).is_empty());
+        });
+    }
+
+    #[test]
+    fn process_and_process_rev_track_separate_cursors() {
+        run_on_main_thread(|| {
+            let target = OwnedEntity::new();
+            let mut events = VecEventList::default();
+
+            events.fire(target.entity(), 1);
+            events.fire(target.entity(), 2);
+
+            assert_eq!(collect_fwd(&events), [1, 2]);
+            assert_eq!(collect_rev(&events), [2, 1]);
+
+            events.fire(target.entity(), 3);
+            events.fire(target.entity(), 4);
+
+            // Each query only sees the events fired since it last ran, in its own order.
+            assert_eq!(collect_rev(&events), [4, 3]);
+            assert_eq!(collect_fwd(&events), [3, 4]);
+
+            // Clearing resets both cursors.
+            events.clear();
+            events.fire(target.entity(), 5);
+            events.fire(target.entity(), 6);
+
+            assert_eq!(collect_rev(&events), [6, 5]);
+            assert_eq!(collect_fwd(&events), [5, 6]);
+        });
+    }
+
+    #[test]
+    fn channel_events_fired_from_other_threads_are_processed_on_the_main_thread() {
+        run_on_main_thread(|| {
+            let target = OwnedEntity::new();
+            let owned = OwnedEntity::new();
+            let owned_entity = owned.entity();
+            let mut events = ChannelEventList::new();
+
+            let mut sender = events.sender();
+            let target_entity = target.entity();
+            std::thread::spawn(move || {
+                sender.fire(target_entity, 1);
+                sender.fire_owned(owned, 2);
+                sender.fire(target_entity, 3);
+            })
+            .join()
+            .unwrap();
+
+            let mut seen = Vec::new();
+            events.process(|entity, ev| {
+                assert!(crate::is_main_thread());
+                seen.push((entity, ev));
+            });
+
+            assert_eq!(
+                seen,
+                [(target_entity, 1), (owned_entity, 2), (target_entity, 3)]
+            );
+            assert!(!owned_entity.is_alive());
+        });
+    }
+
+    #[test]
+    fn orphaned_channel_event_owners_are_destroyed_on_flush() {
+        run_on_main_thread(|| {
+            let owned = OwnedEntity::new();
+            let owned_entity = owned.entity();
+            let events = ChannelEventList::new();
+            let mut sender = events.sender();
+            drop(events);
+
+            std::thread::spawn(move || sender.fire_owned(owned, ()))
+                .join()
+                .unwrap();
+
+            assert!(owned_entity.is_alive());
+            crate::query::flush();
+            assert!(!owned_entity.is_alive());
+        });
+    }
+
+    #[test]
+    fn routed_events_only_reach_handlers_for_present_components() {
+        use std::sync::{Arc, Mutex};
+
+        struct Health;
+        struct Armor;
+
+        run_on_main_thread(|| {
+            let health_tag = Tag::<Health>::new();
+            let armor_tag = Tag::<Armor>::new();
+
+            let both = OwnedEntity::new()
+                .with(Health)
+                .with_tag(health_tag)
+                .with(Armor)
+                .with_tag(armor_tag);
+            let health_only = OwnedEntity::new().with(Health).with_tag(health_tag);
+            let neither = OwnedEntity::new();
+            crate::query::flush();
+
+            let seen = Arc::new(Mutex::new(Vec::new()));
+            let mut events = RoutedEventList::new();
+
+            let health_seen = seen.clone();
+            events.register(health_tag, move |entity, &ev| {
+                health_seen.lock().unwrap().push(("health", entity, ev));
+            });
+
+            let armor_seen = seen.clone();
+            events.register(armor_tag, move |entity, &ev| {
+                armor_seen.lock().unwrap().push(("armor", entity, ev));
+            });
+
+            events.fire(both.entity(), 1);
+            events.fire(health_only.entity(), 2);
+            events.fire(neither.entity(), 3);
+            events.process();
+
+            assert_eq!(
+                *seen.lock().unwrap(),
+                [
+                    ("health", both.entity(), 1),
+                    ("health", health_only.entity(), 2),
+                    ("armor", both.entity(), 1),
+                ]
+            );
+
+            // Processed events are not dispatched again.
+            seen.lock().unwrap().clear();
+            events.process();
+            assert!(seen.lock().unwrap().is_empty());
+
+            drop((both, health_only, neither));
+            crate::query::flush();
+        });
+    }
+
+    #[test]
+    fn bounded_event_list_rejects_events_at_capacity() {
+        run_on_main_thread(|| {
+            let target = OwnedEntity::new();
+            let mut events = BoundedEventList::new(2);
+
+            assert_eq!(events.try_fire(target.entity(), 1), Ok(()));
+            assert_eq!(events.try_fire(target.entity(), 2), Ok(()));
+            assert!(events.is_full());
+
+            // Overflowing events are handed back, including owned ones.
+            assert_eq!(events.try_fire(target.entity(), 3), Err(3));
+            let owned = OwnedEntity::new();
+            let owned_entity = owned.entity();
+            let (owned, event) = events.try_fire_owned(owned, 4).unwrap_err();
+            assert_eq!((owned.entity(), event), (owned_entity, 4));
+            assert_eq!(events.len(), 2);
+
+            // Draining makes room again.
+            let mut drained = VecEventList::default();
+            events.drain_into(&mut drained);
+            assert!(events.is_empty());
+            assert_eq!(collect_fwd(&drained), [1, 2]);
+            assert_eq!(events.try_fire(target.entity(), 3), Ok(()));
+            assert_eq!(collect_fwd(&events), [3]);
+        });
+    }
+
+    #[test]
+    fn instant_events_are_stamped_in_firing_order() {
+        run_on_main_thread(|| {
+            let target = OwnedEntity::new();
+            let mut events = InstantEventList::new();
+
+            let before = Instant::now();
+            for i in 0..3 {
+                events.fire(target.entity(), i);
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+            let after = Instant::now();
+
+            let seen = collect_instants(&events);
+            assert_eq!(
+                seen.iter().map(|(_, ev)| *ev).collect::<Vec<_>>(),
+                [0, 1, 2]
+            );
+            assert!(seen.windows(2).all(|pair| pair[0].0 < pair[1].0));
+            assert!(before <= seen[0].0 && seen[2].0 <= after);
+
+            // `retain` hands out the recorded instant as well.
+            let second = seen[1].0;
+            events.retain(|_, fired_at, _| fired_at != second);
+            let mut drained = VecEventList::default();
+            events.drain_into(&mut drained);
+            assert_eq!(collect_instants(&drained), [(seen[0].0, 0), (seen[2].0, 2)]);
+        });
+    }
+
+    #[test]
+    fn event_log_replays_in_recorded_order() {
+        run_on_main_thread(|| {
+            let a = OwnedEntity::new();
+            let b = OwnedEntity::new();
+            let owned = OwnedEntity::new();
+            let owned_entity = owned.entity();
+            let mut log = EventLog::new();
+
+            log.fire(a.entity(), 1);
+            log.fire(b.entity(), 2);
+            log.fire_owned(owned, 3);
+            log.fire(a.entity(), 4);
+
+            // Processing and clearing the events leaves the log intact.
+            assert_eq!(collect_fwd(&log), [1, 2, 3, 4]);
+            log.clear();
+            assert!(collect_fwd(&log).is_empty());
+
+            let recorded = [
+                (a.entity(), 1),
+                (b.entity(), 2),
+                (owned_entity, 3),
+                (a.entity(), 4),
+            ];
+            assert_eq!(log.log(), recorded);
+
+            let mut replayed = VecEventList::default();
+            log.replay_into(&mut replayed);
+
+            let mut seen = Vec::new();
+            query! {
+                for (event ev in &replayed, entity target) {
+                    seen.push((target, *ev));
+                }
+            }
+            assert_eq!(seen, recorded);
+
+            log.clear_log();
+            assert!(log.log().is_empty());
+        });
+    }
+
+    #[test]
+    fn drain_into_transfers_events_and_owners() {
+        run_on_main_thread(|| {
+            let target = OwnedEntity::new();
+            let owned = OwnedEntity::new();
+            let owned_entity = owned.entity();
+
+            let mut local = VecEventList::default();
+            local.fire(target.entity(), 1);
+            local.fire_owned(owned, 2);
+            local.fire(target.entity(), 3);
+
+            let mut global = VecEventList::default();
+            global.fire(target.entity(), 0);
+            local.drain_into(&mut global);
+
+            assert_eq!(local.event_count(), 0);
+            assert!(collect_fwd(&local).is_empty());
+            assert_eq!(global.event_count(), 4);
+            assert_eq!(collect_fwd(&global), [0, 1, 2, 3]);
+
+            // The owner moved along with its event.
+            global.clear();
+            assert!(!owned_entity.is_alive());
+
+            // Counting events only keep track of how many events they received, holding onto
+            // their owners until they are dropped.
+            let owned = OwnedEntity::new();
+            let owned_entity = owned.entity();
+            local.fire(target.entity(), 4);
+            local.fire_owned(owned, 5);
+
+            let mut counter = CountingEvent::new();
+            local.drain_into(&mut counter);
+            assert_eq!(local.event_count(), 0);
+            assert_eq!(counter.count(), 2);
+            assert!(owned_entity.is_alive());
+
+            drop(counter);
+            assert!(!owned_entity.is_alive());
+        });
+    }
+
+    #[test]
+    fn counting_event_take_resets_each_frame() {
+        run_on_main_thread(|| {
+            let target = OwnedEntity::new();
+            let mut collisions = CountingEvent::<()>::new();
+
+            for _ in 0..3 {
+                collisions.fire(target.entity(), ());
+            }
+            assert_eq!(collisions.count(), 3);
+            assert_eq!(collisions.take(), 3);
+            assert_eq!(collisions.count(), 0);
+            assert!(!collisions.has_event());
+
+            for _ in 0..5 {
+                collisions.fire(target.entity(), ());
+            }
+            assert_eq!(collisions.take(), 5);
+            assert_eq!(collisions.take(), 0);
+        });
+    }
+
+    #[test]
+    fn event_stats_reports_per_type_counts() {
+        struct Damage;
+        struct Death;
+
+        run_on_main_thread(|| {
+            let target = OwnedEntity::new();
+            let mut group = EventGroup::<()>::new();
+            assert!(group.event_stats().is_empty());
+
+            for _ in 0..10 {
+                group.fire_raw::<VecEventList<Damage>>(target.entity(), Damage);
+            }
+            for _ in 0..2 {
+                group.fire_raw::<VecEventList<Death>>(target.entity(), Death);
+            }
+
+            let mut stats = group
+                .event_stats()
+                .into_iter()
+                .map(|stat| (stat.list_type, stat.event_type_name, stat.count))
+                .collect::<Vec<_>>();
+            stats.sort_by_key(|&(_, _, count)| count);
+
+            assert_eq!(
+                stats,
+                [
+                    (TypeId::of::<VecEventList<Death>>(), type_name::<Death>(), 2),
+                    (
+                        TypeId::of::<VecEventList<Damage>>(),
+                        type_name::<Damage>(),
+                        10
+                    ),
+                ]
+            );
+
+            group.clear_single_raw::<VecEventList<Damage>>();
+            let damage = group
+                .event_stats()
+                .into_iter()
+                .find(|stat| stat.list_type == TypeId::of::<VecEventList<Damage>>())
+                .unwrap();
+            assert_eq!(damage.count, 0);
+        });
+    }
+
+    #[test]
+    fn process_with_budget_resumes_in_fifo_order() {
+        run_on_main_thread(|| {
+            let target = OwnedEntity::new();
+            let mut events = VecEventList::default();
+
+            for i in 0..5 {
+                events.fire(target.entity(), i);
+            }
+
+            // Queries keep their position relative to the remaining events.
+            assert_eq!(collect_fwd(&events), [0, 1, 2, 3, 4]);
+
+            let mut handled = Vec::new();
+            assert_eq!(events.process_with_budget(2, |_, ev| handled.push(ev)), 2);
+            assert_eq!(handled, [0, 1]);
+            assert!(collect_fwd(&events).is_empty());
+
+            events.fire(target.entity(), 5);
+            assert_eq!(events.process_with_budget(3, |_, ev| handled.push(ev)), 3);
+            assert_eq!(handled, [0, 1, 2, 3, 4]);
+            assert_eq!(collect_fwd(&events), [5]);
+
+            // The remainder persists, and the budget is clamped to what is left.
+            assert_eq!(events.event_count(), 1);
+            assert_eq!(events.process_with_budget(10, |_, ev| handled.push(ev)), 1);
+            assert_eq!(handled, [0, 1, 2, 3, 4, 5]);
+            assert_eq!(events.event_count(), 0);
+        });
+    }
+
+    #[test]
+    fn capacity_management_keeps_processing_semantics() {
+        run_on_main_thread(|| {
+            let target = OwnedEntity::new();
+            let mut events = VecEventList::with_capacity(16);
+            assert!(events.capacity() >= 16);
+
+            events.fire(target.entity(), 1);
+            events.reserve(100);
+            assert!(events.capacity() >= 101);
+
+            events.fire(target.entity(), 2);
+            assert_eq!(collect_fwd(&events), [1, 2]);
+
+            events.clear();
+            events.shrink_to_fit();
+            assert_eq!(events.capacity(), 0);
+
+            events.fire(target.entity(), 3);
+            assert_eq!(collect_fwd(&events), [3]);
+        });
+    }
+}
@@ -9,18 +9,22 @@ pub mod entity;
 pub mod event;
 pub mod obj;
 pub mod query;
+pub mod relation;
+#[cfg(feature = "serde")]
+pub mod snapshot;
 mod util;
 
 pub use autoken;
+pub use core::token::{assert_main_thread, is_main_thread};
 
 pub mod prelude {
     pub use crate::{
         autoken,
         behavior::{behavior, delegate, BehaviorRegistry},
-        entity::{storage, CompMut, CompRef, Entity, OwnedEntity, Storage},
+        entity::{storage, CompMut, CompRef, Entity, OwnedEntity, Storage, TaggedBundle},
         event::{
-            ClearableEvent, EventGroup, EventGroupDeclExtends, EventGroupDeclWith, EventSwapper,
-            EventTarget, NopEvent, SimpleEventList, VecEventList,
+            ClearableEvent, CountableEvent, EventGroup, EventGroupDeclExtends, EventGroupDeclWith,
+            EventSwapper, EventTarget, NopEvent, SimpleEventList, VecEventList,
         },
         obj::{Obj, OwnedObj},
         query::{
@@ -1,15 +1,23 @@
-use std::{any::type_name, borrow::Borrow, mem};
+use std::{
+    any::{type_name, TypeId},
+    borrow::Borrow,
+    fmt,
+    hash::{self, Hash},
+    mem,
+};
 
-use autoken::{ImmutableBorrow, MutableBorrow, Nothing};
+use autoken::{
+    ImmutableBorrow, MutableBorrow, Nothing, PotentialImmutableBorrow, PotentialMutableBorrow,
+};
 use derive_where::derive_where;
 
 use crate::{
     core::{
-        heap::Slot,
+        heap::{ErasedSlot, Slot},
         token::{MainThreadToken, Token},
     },
     debug::AsDebugLabel,
-    entity::{CompRef, Entity, OwnedEntity},
+    entity::{CompRef, ComponentAccessError, Entity, OwnedEntity},
     CompMut,
 };
 
@@ -93,6 +101,54 @@ impl<T: 'static> Obj<T> {
             .map(|r| CompMut::new(self, r))
     }
 
+    /// Borrows the component without panicking. See [`ComponentAccessError`] for the ways in
+    /// which this can fail.
+    #[track_caller]
+    pub fn try_borrow(
+        self,
+        loaner: &PotentialImmutableBorrow<T>,
+    ) -> Result<CompRef<'static, T, Nothing<'_>>, ComponentAccessError> {
+        let token = MainThreadToken::acquire_fmt("fetch entity component data");
+
+        if !self.is_alive_internal(token) {
+            return Err(self.dead_error());
+        }
+
+        match self.value.try_borrow(token, loaner) {
+            Ok(Some(value)) => Ok(CompRef::new(self, value)),
+            Ok(None) => Err(self.dead_error()),
+            Err(err) => Err(ComponentAccessError::Borrowed(err)),
+        }
+    }
+
+    /// Mutably borrows the component without panicking. See [`ComponentAccessError`] for the ways
+    /// in which this can fail.
+    #[track_caller]
+    pub fn try_borrow_mut(
+        self,
+        loaner: &mut PotentialMutableBorrow<T>,
+    ) -> Result<CompMut<'static, T, Nothing<'_>>, ComponentAccessError> {
+        let token = MainThreadToken::acquire_fmt("fetch entity component data");
+
+        if !self.is_alive_internal(token) {
+            return Err(self.dead_error());
+        }
+
+        match self.value.try_borrow_mut(token, loaner) {
+            Ok(Some(value)) => Ok(CompMut::new(self, value)),
+            Ok(None) => Err(self.dead_error()),
+            Err(err) => Err(ComponentAccessError::BorrowedMut(err)),
+        }
+    }
+
+    fn dead_error(self) -> ComponentAccessError {
+        if self.entity.is_alive() {
+            ComponentAccessError::Missing
+        } else {
+            ComponentAccessError::EntityDead
+        }
+    }
+
     #[track_caller]
     pub fn get(self) -> CompRef<'static, T, T> {
         let token = MainThreadToken::acquire_fmt("fetch entity component data");
@@ -198,6 +254,21 @@ impl<T: 'static> Obj<T> {
         CompMut::new(self, self.value.borrow_mut_on_loan(token, loaner))
     }
 
+    pub fn map<U: ?Sized>(self, f: fn(&T) -> &U, f_mut: fn(&mut T) -> &mut U) -> ProjObj<T, U> {
+        ProjObj {
+            obj: self,
+            f,
+            f_mut,
+        }
+    }
+
+    pub fn erase(self) -> AnyObj {
+        AnyObj {
+            entity: self.entity,
+            value: self.value.erase(),
+        }
+    }
+
     pub fn destroy(self) {
         self.entity.destroy()
     }
@@ -209,6 +280,125 @@ impl<T: 'static> Borrow<Entity> for Obj<T> {
     }
 }
 
+// === ProjObj === //
+
+/// A handle to a part of an [`Obj`]'s value, created by [`Obj::map`]. Borrows through this handle
+/// borrow the entire underlying component.
+#[derive_where(Copy, Clone)]
+pub struct ProjObj<T: 'static, U: ?Sized> {
+    obj: Obj<T>,
+    f: fn(&T) -> &U,
+    f_mut: fn(&mut T) -> &mut U,
+}
+
+impl<T: 'static, U: ?Sized> fmt::Debug for ProjObj<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProjObj")
+            .field("entity", &self.obj.entity())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: 'static, U: ?Sized> ProjObj<T, U> {
+    pub fn obj(self) -> Obj<T> {
+        self.obj
+    }
+
+    pub fn entity(self) -> Entity {
+        self.obj.entity()
+    }
+
+    pub fn is_alive(self) -> bool {
+        self.obj.is_alive()
+    }
+
+    #[track_caller]
+    pub fn get(self) -> CompRef<'static, U, T, Obj<T>> {
+        CompRef::map(self.obj.get(), self.f)
+    }
+
+    #[track_caller]
+    pub fn get_mut(self) -> CompMut<'static, U, T, Obj<T>> {
+        CompMut::map(self.obj.get_mut(), self.f_mut)
+    }
+
+    #[track_caller]
+    pub fn try_get(
+        self,
+        loaner: &ImmutableBorrow<T>,
+    ) -> Option<CompRef<'static, U, Nothing<'_>, Obj<T>>> {
+        self.obj.try_get(loaner).map(|r| CompRef::map(r, self.f))
+    }
+
+    #[track_caller]
+    pub fn try_get_mut(
+        self,
+        loaner: &mut MutableBorrow<T>,
+    ) -> Option<CompMut<'static, U, Nothing<'_>, Obj<T>>> {
+        self.obj
+            .try_get_mut(loaner)
+            .map(|r| CompMut::map(r, self.f_mut))
+    }
+}
+
+// === AnyObj === //
+
+#[derive(Debug, Copy, Clone)]
+pub struct AnyObj {
+    entity: Entity,
+    value: ErasedSlot,
+}
+
+impl AnyObj {
+    pub fn entity(self) -> Entity {
+        self.entity
+    }
+
+    pub fn type_id(self) -> TypeId {
+        self.value.type_id()
+    }
+
+    pub fn is<T: 'static>(self) -> bool {
+        self.type_id() == TypeId::of::<T>()
+    }
+
+    pub fn downcast<T: 'static>(self) -> Option<Obj<T>> {
+        self.value
+            .downcast()
+            .map(|value| Obj::from_raw_parts(self.entity, value))
+    }
+
+    pub fn is_alive(self) -> bool {
+        let token = MainThreadToken::acquire_fmt("determine whether an AnyObj was alive");
+        self.value.owner(token) == Some(self.entity)
+    }
+
+    pub fn destroy(self) {
+        self.entity.destroy()
+    }
+}
+
+impl Hash for AnyObj {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.entity.hash(state);
+        self.type_id().hash(state);
+    }
+}
+
+impl Eq for AnyObj {}
+
+impl PartialEq for AnyObj {
+    fn eq(&self, other: &Self) -> bool {
+        self.entity == other.entity && self.type_id() == other.type_id()
+    }
+}
+
+impl<T: 'static> From<Obj<T>> for AnyObj {
+    fn from(obj: Obj<T>) -> Self {
+        obj.erase()
+    }
+}
+
 // === OwnedObj === //
 
 #[derive(Debug)]
@@ -270,11 +460,44 @@ impl<T: 'static> OwnedObj<T> {
         obj
     }
 
+    pub fn into_parts(self) -> (OwnedEntity, Obj<T>) {
+        let obj = self.unmanage();
+        (OwnedEntity::from_raw_entity(obj.entity()), obj)
+    }
+
+    pub fn from_parts(entity: OwnedEntity, obj: Obj<T>) -> Self {
+        assert_eq!(
+            entity.entity(),
+            obj.entity(),
+            "attempted to construct an `OwnedObj<{}>` from an `Obj` belonging to another entity",
+            type_name::<T>(),
+        );
+        entity.unmanage();
+        Self::from_raw_obj(obj)
+    }
+
     pub fn split_guard(self) -> (Self, Obj<T>) {
         let obj = self.obj();
         (self, obj)
     }
 
+    /// Spawns an independent copy of this object as per [`Entity::duplicate`]. The `T` component is
+    /// cloned into the copy even if it wasn't [registered](crate::entity::register_cloneable) as
+    /// cloneable but, as with entities, the object's other unregistered components are left out.
+    pub fn duplicate(&self) -> Self
+    where
+        T: Clone,
+    {
+        let copy = self.entity().duplicate();
+
+        if !copy.has::<T>() {
+            let value = T::clone(&self.get());
+            copy.insert(value);
+        }
+
+        copy.into_obj()
+    }
+
     // === Forwards === //
 
     pub fn with_debug_label<L: AsDebugLabel>(self, label: L) -> Self {
@@ -344,6 +567,10 @@ impl<T: 'static> OwnedObj<T> {
         self.obj.get_mut_maybe_aba_on_loan(loaner)
     }
 
+    pub fn map<U: ?Sized>(&self, f: fn(&T) -> &U, f_mut: fn(&mut T) -> &mut U) -> ProjObj<T, U> {
+        self.obj.map(f, f_mut)
+    }
+
     pub fn is_alive(&self) -> bool {
         self.obj.is_alive()
     }
@@ -376,3 +603,129 @@ impl<T: 'static> Borrow<Entity> for OwnedObj<T> {
         &self.obj.entity
     }
 }
+
+// === Tests === //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{query::flush, util::testing::run_on_main_thread};
+
+    #[test]
+    fn projections_borrow_through_to_the_component() {
+        #[derive(Debug)]
+        struct Transform {
+            position: (f32, f32),
+            scale: f32,
+        }
+
+        run_on_main_thread(|| {
+            let transform = OwnedObj::new(Transform {
+                position: (1.0, 2.0),
+                scale: 3.0,
+            });
+
+            let position = transform.map(|t| &t.position, |t| &mut t.position);
+            let scale = transform.obj().map(|t| &t.scale, |t| &mut t.scale);
+            assert_eq!(position.entity(), transform.entity());
+            assert_eq!(position.obj(), transform.obj());
+
+            assert_eq!(*position.get(), (1.0, 2.0));
+            position.get_mut().0 = 5.0;
+            *scale.get_mut() *= 2.0;
+            assert_eq!(transform.get().position, (5.0, 2.0));
+            assert_eq!(*scale.get(), 6.0);
+
+            // Borrowing a projection borrows the entire component.
+            {
+                let _guard = position.get_mut();
+                let loaner = PotentialImmutableBorrow::new();
+                assert!(scale.obj().try_borrow(&loaner).is_err());
+            }
+
+            assert!(position.is_alive());
+            drop(transform);
+            assert!(!position.is_alive());
+            assert!(scale.try_get(&ImmutableBorrow::new()).is_none());
+            flush();
+        });
+    }
+
+    #[test]
+    fn parts_round_trip_without_destroying_twice() {
+        use std::sync::atomic::{AtomicU32, Ordering::Relaxed};
+
+        static DROPS: AtomicU32 = AtomicU32::new(0);
+
+        struct Tracked(u32);
+
+        impl Drop for Tracked {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Relaxed);
+            }
+        }
+
+        run_on_main_thread(|| {
+            let original = OwnedObj::new(Tracked(7));
+            let original_obj = original.obj();
+
+            // Splitting the object up doesn't destroy it...
+            let (entity, obj) = original.into_parts();
+            assert_eq!(entity.entity(), obj.entity());
+            assert!(obj == original_obj);
+            assert!(obj.is_alive());
+            assert_eq!(DROPS.load(Relaxed), 0);
+
+            // ...and neither does reassembling it.
+            let reassembled = OwnedObj::from_parts(entity, obj);
+            assert!(reassembled.is_alive());
+            assert_eq!(reassembled.get().0, 7);
+            assert_eq!(DROPS.load(Relaxed), 0);
+
+            // The reassembled object owns the entity exactly once.
+            drop(reassembled);
+            flush();
+            assert!(!original_obj.is_alive());
+            assert_eq!(DROPS.load(Relaxed), 1);
+        });
+    }
+
+    #[test]
+    #[should_panic = "belonging to another entity"]
+    fn from_parts_rejects_mismatched_entities() {
+        run_on_main_thread(|| {
+            let (_entity, obj) = OwnedObj::new(1u32).into_parts();
+            OwnedObj::from_parts(OwnedEntity::new(), obj);
+        });
+    }
+
+    #[test]
+    fn erased_objs_only_downcast_to_their_own_type() {
+        run_on_main_thread(|| {
+            let number = OwnedObj::new(5u32);
+            let text = OwnedObj::new("five");
+
+            let any: AnyObj = number.obj().into();
+            assert_eq!(any.entity(), number.entity());
+            assert_eq!(any.type_id(), TypeId::of::<u32>());
+            assert!(any.is::<u32>() && !any.is::<&str>());
+            assert!(any.is_alive());
+
+            assert!(any.downcast::<u32>() == Some(number.obj()));
+            assert_eq!(*any.downcast::<u32>().unwrap().get(), 5);
+            assert!(any.downcast::<&str>().is_none());
+            assert!(any.downcast::<u64>().is_none());
+
+            // Erased handles compare by both entity and type.
+            assert!(any == number.obj().erase());
+            assert!(any != text.obj().erase());
+
+            drop(number);
+            flush();
+            assert!(!any.is_alive());
+
+            drop(text);
+            flush();
+        });
+    }
+}
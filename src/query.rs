@@ -1,10 +1,15 @@
 use std::{
-    any::{Any, TypeId},
+    any::{type_name, Any, TypeId},
+    cell::RefCell,
+    error::Error,
     fmt,
     hash::Hash,
     marker::PhantomData,
+    mem,
     ops::ControlFlow,
-    sync::Arc,
+    rc::Rc,
+    sync::{atomic::AtomicU64, Arc},
+    time::Duration,
 };
 
 use derive_where::derive_where;
@@ -17,13 +22,14 @@ use crate::{
         token_cell::NMainCell,
     },
     database::{
-        get_global_tag, DbRoot, InertArchetypeId, InertEntity, InertTag, RecursiveQueryGuardTy,
-        ReifiedTagList,
+        find_global_tag_by_name, find_global_tag_name, get_global_tag, DbRoot, InertArchetypeId,
+        InertEntity, InertTag, RecursiveQueryGuardTy, ReifiedTagList,
     },
-    entity::Storage,
+    entity::{storage, Storage},
+    event::destroy_orphaned_owners,
     util::{
         hash_map::{ConstSafeBuildHasherDefault, FxHashMap},
-        iter::hash_one,
+        iter::{hash_one, merge_many},
         misc::NamedTypeId,
     },
     Entity,
@@ -48,13 +54,27 @@ impl<T> Tag<T> {
     pub fn global<G: HasGlobalManagedTag<Component = T>>() -> Self {
         Self {
             _ty: PhantomData,
-            raw: get_global_tag(NamedTypeId::of::<G>(), NamedTypeId::of::<T>()),
+            raw: get_global_tag(
+                NamedTypeId::of::<G>(),
+                type_name::<G>(),
+                NamedTypeId::of::<T>(),
+            ),
         }
     }
 
     pub fn raw(self) -> RawTag {
         self.raw
     }
+
+    /// Captures the set of entities carrying this tag as of the last flush. Unlike a live query,
+    /// the snapshot can be iterated while spawning, despawning, and flushing entities.
+    pub fn snapshot(self) -> TagSnapshot {
+        let _guard = borrow_flush_guard();
+
+        TagSnapshot {
+            entities: entities_in_intersection([self.raw]),
+        }
+    }
 }
 
 impl<T> From<Tag<T>> for RawTag {
@@ -69,6 +89,42 @@ impl<T> Default for Tag<T> {
     }
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct TagSnapshot {
+    entities: Vec<Entity>,
+}
+
+impl TagSnapshot {
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.iter().copied()
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.contains(&entity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+impl IntoIterator for TagSnapshot {
+    type Item = Entity;
+    type IntoIter = std::vec::IntoIter<Entity>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entities.into_iter()
+    }
+}
+
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
 pub struct VirtualTag {
     raw: RawTag,
@@ -83,13 +139,22 @@ impl VirtualTag {
 
     pub fn global<T: HasGlobalVirtualTag>() -> Self {
         Self {
-            raw: get_global_tag(NamedTypeId::of::<T>(), InertTag::inert_ty_id()),
+            raw: get_global_tag(
+                NamedTypeId::of::<T>(),
+                type_name::<T>(),
+                InertTag::inert_ty_id(),
+            ),
         }
     }
 
     pub fn raw(self) -> RawTag {
         self.raw
     }
+
+    /// Enumerates every entity carrying this tag as of the last flush.
+    pub fn iter_entities(self) -> impl Iterator<Item = Entity> {
+        entities_in_intersection([self.raw]).into_iter()
+    }
 }
 
 impl From<VirtualTag> for RawTag {
@@ -135,6 +200,115 @@ impl fmt::Debug for RawTag {
     }
 }
 
+/// Resolves a global tag from the [`type_name`] of the type it was declared on. Only global tags
+/// which have already been fetched at least once through [`Tag::global`], [`VirtualTag::global`],
+/// or their [`GlobalTag`] and [`GlobalVirtualTag`] counterparts can be found.
+pub fn global_tag_by_name(name: &str) -> Option<RawTag> {
+    find_global_tag_by_name(name)
+}
+
+/// The inverse of [`global_tag_by_name`], returning `None` if `tag` is not a global tag.
+pub fn global_tag_name(tag: RawTag) -> Option<&'static str> {
+    find_global_tag_name(tag)
+}
+
+// === TagSet === //
+
+/// A set of [`RawTag`]s which can be assembled at runtime and passed to the `tags` clause of
+/// [`query!`].
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq)]
+pub struct TagSet {
+    // Kept sorted so that equal sets compare equal.
+    tags: Vec<RawTag>,
+}
+
+impl TagSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, tag: impl Into<RawTag>) -> bool {
+        let tag = tag.into();
+
+        match self.tags.binary_search(&tag) {
+            Ok(_) => false,
+            Err(index) => {
+                self.tags.insert(index, tag);
+                true
+            }
+        }
+    }
+
+    pub fn with(mut self, tag: impl Into<RawTag>) -> Self {
+        self.add(tag);
+        self
+    }
+
+    pub fn remove(&mut self, tag: impl Into<RawTag>) -> bool {
+        match self.tags.binary_search(&tag.into()) {
+            Ok(index) => {
+                self.tags.remove(index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn contains(&self, tag: impl Into<RawTag>) -> bool {
+        self.tags.binary_search(&tag.into()).is_ok()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.tags.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = RawTag> + '_ {
+        self.tags.iter().copied()
+    }
+}
+
+impl<T: Into<RawTag>> FromIterator<T> for TagSet {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Into<RawTag>> Extend<T> for TagSet {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for tag in iter {
+            self.add(tag);
+        }
+    }
+}
+
+impl IntoIterator for TagSet {
+    type Item = RawTag;
+    type IntoIter = std::vec::IntoIter<RawTag>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tags.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a TagSet {
+    type Item = RawTag;
+    type IntoIter = std::iter::Copied<std::slice::Iter<'a, RawTag>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tags.iter().copied()
+    }
+}
+
 // === Global Tags === //
 
 // Traits
@@ -212,10 +386,13 @@ impl ArchetypeId {
         let is_non_empty = ReifiedTagList::reify(tags, |tags| {
             if tags.is_non_empty() {
                 DbRoot::get(token).enumerate_tag_intersection(tags, |info| {
+                    let full_heap_lens = info.entities.iter().rev().skip(1).map(|heap| heap.len());
+
                     archetypes.push(ArchetypeQueryInfo {
                         archetype: info.archetype.into_dangerous_archetype_id(),
                         heap_count: info.entities.len(),
                         last_heap_len: info.last_heap_len,
+                        entity_count: full_heap_lens.sum::<usize>() + info.last_heap_len,
                         entities: include_entities.then(|| info.entities.clone()),
                     });
                 });
@@ -234,6 +411,7 @@ pub struct ArchetypeQueryInfo {
     archetype: ArchetypeId,
     heap_count: usize,
     last_heap_len: usize,
+    entity_count: usize,
     entities: Option<Vec<Arc<[NMainCell<InertEntity>]>>>,
 }
 
@@ -250,19 +428,115 @@ impl ArchetypeQueryInfo {
         self.last_heap_len
     }
 
+    pub fn entity_count(&self) -> usize {
+        self.entity_count
+    }
+
     pub fn heaps_for<T>(&self, storage: &Storage<T>) -> Vec<Arc<Heap<T>>> {
-        DbRoot::heaps_from_archetype_aba(self.archetype.0, &storage.inner.borrow(&storage.token))
+        let mut heaps = DbRoot::heaps_from_archetype_aba(
+            self.archetype.0,
+            &storage.inner.borrow(&storage.token),
+        );
+
+        // Warmed-up archetypes may reserve heaps before any entity has been moved into them.
+        heaps.truncate(self.heap_count);
+        heaps
     }
 
     // TODO: Expose entities
 }
 
+/// A tag intersection whose matching archetypes are resolved once and then reused by every
+/// [`query!`] which runs over it with the `compiled` binding, up until the next flush.
+#[derive(Debug)]
+pub struct ArchetypeQuery {
+    tags: Vec<RawTag>,
+    cache: RefCell<Option<(u64, Rc<[ArchetypeQueryInfo]>)>>,
+}
+
+impl ArchetypeQuery {
+    pub fn compile(tags: impl IntoIterator<Item = RawTag>) -> Self {
+        Self {
+            tags: tags.into_iter().collect(),
+            cache: RefCell::new(None),
+        }
+    }
+
+    pub fn tags(&self) -> &[RawTag] {
+        &self.tags
+    }
+
+    /// Fetches the archetypes in the intersection, only re-resolving them if the database has been
+    /// flushed since they were last resolved.
+    pub fn archetypes(&self) -> Rc<[ArchetypeQueryInfo]> {
+        let flush_count = total_flush_count();
+        let mut cache = self.cache.borrow_mut();
+
+        if let Some((resolved_at, archetypes)) = &*cache {
+            if *resolved_at == flush_count {
+                return archetypes.clone();
+            }
+        }
+
+        let archetypes = Rc::<[_]>::from(
+            ArchetypeId::in_intersection(self.tags.iter().copied(), true).unwrap_or_default(),
+        );
+        *cache = Some((flush_count, archetypes.clone()));
+        archetypes
+    }
+}
+
+/// Creates the archetype for the given combination of tags ahead of time and reserves its first
+/// entity heap and component heaps so that the first flush moving entities into it doesn't have to
+/// allocate them.
+///
+/// Component heaps are only reserved for components whose [`Storage`] has already been created.
+/// Warmed-up archetypes are never destroyed, even once they become empty.
+pub fn warm_archetype(tags: impl IntoIterator<Item = RawTag>) {
+    let token = MainThreadToken::acquire_fmt("warm up an archetype");
+
+    ReifiedTagList::reify(tags, |tags| {
+        DbRoot::get(token).warm_archetype(token, tags.iter().copied());
+    });
+}
+
 // === Flushing === //
 
 #[must_use]
 pub fn try_flush() -> bool {
+    try_flush_reason().is_ok()
+}
+
+pub fn try_flush_reason() -> Result<(), FlushBlockedReason> {
     let token = MainThreadToken::acquire_fmt("flush entity archetypes");
-    DbRoot::get(token).flush_archetypes(token).is_ok()
+    destroy_orphaned_owners();
+    DbRoot::get(token).flush_archetypes(token).map_err(|err| {
+        FlushBlockedReason::RecursiveQueryGuard {
+            count: err.active_queries,
+        }
+    })
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum FlushBlockedReason {
+    /// The flush guard was held by `count` active queries or [`FlushGuard`]s.
+    RecursiveQueryGuard { count: usize },
+}
+
+impl Error for FlushBlockedReason {}
+
+impl fmt::Display for FlushBlockedReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RecursiveQueryGuard { count } => write!(
+                f,
+                "the entity database cannot be flushed while the flush guard is held by {count} \
+                 active quer{}",
+                if *count == 1 { "y" } else { "ies" },
+            ),
+        }
+    }
 }
 
 fn flush_with_custom_msg(msg: &'static str) {
@@ -274,10 +548,60 @@ pub fn flush() {
     flush_with_custom_msg("attempted to flush the entity database while a query was active");
 }
 
+/// Returns a guard which flushes the database when dropped. Like [`flush`], the guard panics if a
+/// query is still active at that point, unless the thread is already panicking.
+pub fn flush_scoped() -> ScopedFlush {
+    ScopedFlush {
+        _no_send: PhantomData,
+    }
+}
+
+#[derive(Debug)]
+#[must_use = "the database is flushed when this guard is dropped"]
+pub struct ScopedFlush {
+    _no_send: PhantomData<*const ()>,
+}
+
+impl ScopedFlush {
+    pub fn cancel(self) {
+        mem::forget(self);
+    }
+}
+
+impl Drop for ScopedFlush {
+    fn drop(&mut self) {
+        if !try_flush() && !std::thread::panicking() {
+            panic!(
+                "attempted to flush the entity database at the end of a `flush_scoped` scope while \
+                 a query was active"
+            );
+        }
+    }
+}
+
 pub fn total_flush_count() -> u64 {
     DbRoot::get(MainThreadToken::acquire_fmt("query total flush count")).total_flush_count()
 }
 
+/// Controls how [`flush`] fills the gaps left behind by entities which leave an archetype. By
+/// default, the archetype's last entity is moved into each gap, which is cheap but scrambles
+/// iteration order. When enabled, the entities following a gap are shifted down instead so that
+/// queries visit the entities of each archetype in the order they entered it. This makes query
+/// output independent of slot reuse at the cost of slower flushes.
+///
+/// The guarantee is about the order in which entities *entered the archetype*, not the order in
+/// which they were spawned. An entity enters an archetype during the first flush after its tags
+/// come to match that archetype's, so an entity which gains or loses a tag is moved to the end of
+/// its new archetype. Entities entering the same archetype during the same flush are ordered by
+/// when their tags first changed since the previous flush.
+///
+/// Archetypes are only reordered as they are touched by a flush, so entities which already lost
+/// their order before the mode was enabled are not restored to it.
+pub fn set_preserve_archetype_order(enabled: bool) {
+    DbRoot::get(MainThreadToken::acquire_fmt("configure archetype ordering"))
+        .set_preserve_archetype_order(enabled);
+}
+
 #[derive(Debug)]
 pub struct FlushGuard(OptRef<'static, RecursiveQueryGuardTy>);
 
@@ -322,6 +646,10 @@ impl<V> QueryVersionMap<V> {
         self.versions.clear();
     }
 
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> + '_ {
+        self.versions.values_mut()
+    }
+
     pub fn entry<K>(&mut self, key: K, version_ctor: impl FnOnce() -> V) -> &mut V
     where
         K: QueryKey,
@@ -640,6 +968,271 @@ where
     }
 }
 
+// === Query Timing === //
+
+#[cfg(feature = "query-timing")]
+static QUERY_TIMINGS: std::sync::Mutex<FxHashMap<&'static str, (Duration, u64)>> =
+    std::sync::Mutex::new(FxHashMap::with_hasher(ConstSafeBuildHasherDefault::new()));
+
+pub fn timed<R>(name: &'static str, f: impl FnOnce() -> R) -> R {
+    #[cfg(feature = "query-timing")]
+    {
+        let start = std::time::Instant::now();
+        let res = f();
+        let elapsed = start.elapsed();
+
+        let mut timings = crate::util::misc::unpoison(QUERY_TIMINGS.lock());
+        let (total, calls) = timings.entry(name).or_default();
+        *total += elapsed;
+        *calls += 1;
+
+        res
+    }
+
+    #[cfg(not(feature = "query-timing"))]
+    {
+        let _ = name;
+        f()
+    }
+}
+
+pub fn timing_report() -> Vec<(&'static str, Duration, u64)> {
+    #[cfg(feature = "query-timing")]
+    {
+        let mut report = crate::util::misc::unpoison(QUERY_TIMINGS.lock())
+            .iter()
+            .map(|(&name, &(total, calls))| (name, total, calls))
+            .collect::<Vec<_>>();
+
+        report.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        report
+    }
+
+    #[cfg(not(feature = "query-timing"))]
+    Vec::new()
+}
+
+// === Query Metrics === //
+
+pub(crate) static DEBUG_QUERY_COUNTER: AtomicU64 = AtomicU64::new(0);
+pub(crate) static DEBUG_QUERY_VISIT_COUNTER: AtomicU64 = AtomicU64::new(0);
+pub(crate) static DEBUG_QUERY_FALLBACK_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Adds `amount` to a query metric counter. This compiles to nothing unless the `query-metrics`
+/// feature is enabled so that the hot query loop doesn't contend on the counters.
+#[inline(always)]
+pub(crate) fn record_query_metric(counter: &AtomicU64, amount: u64) {
+    #[cfg(feature = "query-metrics")]
+    counter.fetch_add(amount, std::sync::atomic::Ordering::Relaxed);
+
+    #[cfg(not(feature = "query-metrics"))]
+    let _ = (counter, amount);
+}
+
+// === Parallel Queries === //
+
+/// Invokes `f` with every entity in the intersection of `tags`, splitting the work across the
+/// `rayon` thread pool. Only entity handles are passed to the handler since the main-thread
+/// component accessors are unavailable on the worker threads. Handlers must therefore do their own
+/// thread-safe lookups (e.g. into a snapshot of `Sync` component values cloned beforehand).
+///
+/// The flush guard is held for the duration of the call so the set of entities cannot change
+/// under the workers.
+#[cfg(feature = "rayon")]
+pub fn par_for_each(tags: impl IntoIterator<Item = RawTag>, f: impl Fn(Entity) + Sync) {
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+    let _guard = borrow_flush_guard();
+    entities_in_intersection(tags).into_par_iter().for_each(&f);
+}
+
+fn entities_in_intersection(tags: impl IntoIterator<Item = RawTag>) -> Vec<Entity> {
+    let mut entities = Vec::new();
+    for_each_entity_in_intersection(tags, |entity| entities.push(entity));
+    entities
+}
+
+fn for_each_entity_in_intersection(
+    tags: impl IntoIterator<Item = RawTag>,
+    mut f: impl FnMut(Entity),
+) {
+    let token = MainThreadToken::acquire_fmt("enumerate entities in a tag intersection");
+
+    for arch in ArchetypeId::in_intersection(tags, true)
+        .into_iter()
+        .flatten()
+    {
+        let heaps = arch.entities.as_deref().unwrap_or_default();
+
+        for (i, heap) in heaps.iter().enumerate() {
+            let len = if i == heaps.len() - 1 {
+                arch.last_heap_len
+            } else {
+                heap.len()
+            };
+
+            for entity in &heap[..len] {
+                f(entity.get(token).into_dangerous_entity());
+            }
+        }
+    }
+}
+
+// === Sampled Queries === //
+
+/// Uniformly samples up to `n` entities from the intersection of `tags` using reservoir sampling.
+/// Unlike collecting the entire intersection and picking from it, this only ever allocates room for
+/// the `n` sampled entities.
+#[cfg(feature = "rand_core")]
+pub fn query_sample(
+    tags: impl IntoIterator<Item = RawTag>,
+    n: usize,
+    rng: &mut impl rand_core::RngCore,
+) -> Vec<Entity> {
+    let mut reservoir = Vec::with_capacity(n);
+    let mut seen = 0u64;
+
+    for_each_entity_in_intersection(tags, |entity| {
+        if reservoir.len() < n {
+            reservoir.push(entity);
+        } else {
+            // Pick an index in `0..=seen` without modulo bias.
+            let j = ((rng.next_u64() as u128 * (seen as u128 + 1)) >> 64) as usize;
+
+            if j < n {
+                reservoir[j] = entity;
+            }
+        }
+
+        seen += 1;
+    });
+
+    reservoir
+}
+
+// === Sorted Queries === //
+
+/// Collects the entities in the intersection of `tags` which have a component of type `T` and sorts
+/// them by the key derived from that component. The sort is stable so entities with equal keys keep
+/// their query order.
+pub fn query_sorted_by_key<T: 'static, K: Ord>(
+    tags: impl IntoIterator<Item = RawTag>,
+    key: impl Fn(&T) -> K,
+) -> Vec<Entity> {
+    let token = MainThreadToken::acquire_fmt("sort entities in a tag intersection");
+    let storage = storage::<T>();
+    let mut keyed = Vec::new();
+
+    for_each_entity_in_intersection(tags, |entity| {
+        if let Some(slot) = storage.try_get_slot(entity) {
+            keyed.push((key(&slot.borrow(token)), entity));
+        }
+    });
+
+    keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+    keyed.into_iter().map(|(_, entity)| entity).collect()
+}
+
+// === Tag Membership Algebra === //
+
+/// Returns the entities which carry both `a` and `b` as of the last flush. This is resolved at the
+/// archetype level so only the entities in the intersection are ever visited.
+pub fn tag_intersection(a: impl Into<RawTag>, b: impl Into<RawTag>) -> Vec<Entity> {
+    entities_in_intersection([a.into(), b.into()])
+}
+
+/// Returns the entities which carry either `a` or `b` as of the last flush, sorted by entity and
+/// without duplicates.
+pub fn tag_union(a: impl Into<RawTag>, b: impl Into<RawTag>) -> Vec<Entity> {
+    tag_union_many([a.into(), b.into()])
+}
+
+/// Returns the entities which carry at least one of `tags` as of the last flush, sorted by entity
+/// and without duplicates.
+pub fn tag_union_many(tags: impl IntoIterator<Item = RawTag>) -> Vec<Entity> {
+    let members = tags.into_iter().map(|tag| {
+        let mut members = entities_in_intersection([tag]);
+        members.sort_unstable();
+        members
+    });
+
+    let mut union = merge_many(members.collect::<Vec<_>>()).collect::<Vec<_>>();
+    union.dedup();
+    union
+}
+
+// === Paged Queries === //
+
+/// A resumable cursor over the entities in the intersection of a set of tags, allowing a query to
+/// be spread across several frames.
+///
+/// The set of entities to visit is captured at the start of each pass, so the cursor remains valid
+/// across flushes. Every entity which was present at the start of a pass is visited exactly once
+/// in that pass unless it is destroyed beforehand. Entities which join the intersection mid-pass
+/// are picked up by the next pass.
+///
+/// Use [`query_paged!`] to drive a [`query!`] from a cursor.
+#[derive(Debug, Clone)]
+pub struct QueryCursor {
+    tags: Vec<RawTag>,
+    pass: Vec<Entity>,
+    next: usize,
+    pass_count: u64,
+}
+
+impl QueryCursor {
+    pub fn new(tags: impl IntoIterator<Item = RawTag>) -> Self {
+        Self {
+            tags: tags.into_iter().collect(),
+            pass: Vec::new(),
+            next: 0,
+            pass_count: 0,
+        }
+    }
+
+    /// Returns up to `limit` entities which have yet to be visited in the current pass, starting a
+    /// new pass if the previous one was completed.
+    pub fn next_page(&mut self, limit: usize) -> Vec<Entity> {
+        if self.is_pass_complete() {
+            self.pass = entities_in_intersection(self.tags.iter().copied());
+            self.next = 0;
+            self.pass_count += 1;
+        }
+
+        let mut page = Vec::with_capacity(limit.min(self.pass.len() - self.next));
+
+        while page.len() < limit && self.next < self.pass.len() {
+            let entity = self.pass[self.next];
+            self.next += 1;
+
+            if entity.is_alive() {
+                page.push(entity);
+            }
+        }
+
+        page
+    }
+
+    pub fn is_pass_complete(&self) -> bool {
+        self.next >= self.pass.len()
+    }
+
+    pub fn pass_count(&self) -> u64 {
+        self.pass_count
+    }
+
+    pub fn remaining_in_pass(&self) -> usize {
+        self.pass.len() - self.next
+    }
+
+    /// Abandons the current pass so that the next call to [`next_page`](Self::next_page) starts a
+    /// fresh one.
+    pub fn reset(&mut self) {
+        self.pass.clear();
+        self.next = 0;
+    }
+}
+
 // === Query Macro === //
 
 #[doc(hidden)]
@@ -666,21 +1259,24 @@ pub mod query_internals {
         database::InertEntity,
         entity::{CompMut, CompRef, Entity},
         obj::Obj,
-        storage, Storage,
+        storage,
+        util::hash_map::FxHashSet,
+        Storage,
     };
 
     use super::{
         borrow_flush_guard, query_driver_target_sealed::DriverTargetSealed,
-        query_handler_sealed::QueryHandlerSealed, ArchetypeId, ArchetypeQueryInfo,
-        DriverArchIterInfo, DriverBlockIterInfo, DriverHeapIterInfo, DriverItem,
-        HasGlobalManagedTag, MultiDriverItem, MultiQueryDriver, QueryBlockElementHandler,
-        QueryBlockHandler, QueryDriver, QueryDriverEntryHandler, QueryDriverTarget,
-        QueryHeapHandler, QueryKey, RawTag, Tag,
+        query_handler_sealed::QueryHandlerSealed, record_query_metric, ArchetypeId, ArchetypeQuery,
+        ArchetypeQueryInfo, DriverArchIterInfo, DriverBlockIterInfo, DriverHeapIterInfo,
+        DriverItem, HasGlobalManagedTag, MultiDriverItem, MultiQueryDriver,
+        QueryBlockElementHandler, QueryBlockHandler, QueryCursor, QueryDriver,
+        QueryDriverEntryHandler, QueryDriverTarget, QueryHeapHandler, QueryKey, RawTag, Tag,
+        DEBUG_QUERY_COUNTER, DEBUG_QUERY_FALLBACK_COUNTER, DEBUG_QUERY_VISIT_COUNTER,
     };
 
     pub use {
         cbit::cbit,
-        std::{compile_error, concat, iter::Iterator, stringify},
+        std::{clone::Clone, compile_error, concat, iter::Iterator, stringify, vec::Vec},
     };
 
     // === QueryXxHandler === //
@@ -1171,6 +1767,64 @@ pub mod query_internals {
         <Q as QueryPart>::GroupAutokenLoan,
     >>::IterItem<'b>;
 
+    // === Aliasing Checks === //
+
+    pub struct BorrowRef;
+
+    pub struct BorrowMut;
+
+    pub struct AliasCheck<KA, A, KB, B>(PhantomData<(KA, A, KB, B)>);
+
+    pub fn alias_check<KA, A, TA, KB, B, TB>(
+        _: KA,
+        _: &TA,
+        _: KB,
+        _: &TB,
+    ) -> AliasCheck<KA, A, KB, B>
+    where
+        A: 'static,
+        B: 'static,
+        TA: Into<Tag<A>>,
+        TB: Into<Tag<B>>,
+    {
+        AliasCheck(PhantomData)
+    }
+
+    pub struct AliasedBorrow;
+
+    pub struct DistinctBorrow;
+
+    // N.B. method resolution prefers `ResolveAliasedBorrow` because its receiver requires one less
+    // auto-ref, so it is picked whenever the two tags share a component type.
+    pub trait ResolveAliasedBorrow {
+        fn resolve(&self) -> AliasedBorrow {
+            AliasedBorrow
+        }
+    }
+
+    impl<A, KB> ResolveAliasedBorrow for AliasCheck<BorrowMut, A, KB, A> {}
+
+    impl<A> ResolveAliasedBorrow for AliasCheck<BorrowRef, A, BorrowMut, A> {}
+
+    pub trait ResolveDistinctBorrow {
+        fn resolve(&self) -> DistinctBorrow {
+            DistinctBorrow
+        }
+    }
+
+    impl<KA, A, KB, B> ResolveDistinctBorrow for &'_ AliasCheck<KA, A, KB, B> {}
+
+    #[diagnostic::on_unimplemented(
+        message = "`query!` bindings `{A}` and `{B}` borrow the same component and at least one of them borrows it mutably",
+        label = "`{B}` aliases the component borrowed by `{A}`",
+        note = "each component may only be listed once if it is borrowed with `mut`, `omut`, or `obj+mut`"
+    )]
+    pub trait NotAliased<A, B> {}
+
+    impl<A, B> NotAliased<A, B> for DistinctBorrow {}
+
+    pub fn assert_not_aliased<A, B, R: NotAliased<A, B>>(_: R) {}
+
     pub trait QueryPart: Sized {
         type Input<'a>;
         type TagIter: Iterator<Item = RawTag>;
@@ -1211,6 +1865,24 @@ pub mod query_internals {
         fn query<B>(
             self,
             extra_tags: impl IntoIterator<Item = RawTag>,
+            f: impl FnMut(Self::Input<'_>) -> ControlFlow<B>,
+        ) -> ControlFlow<B> {
+            self.query_in(None, extra_tags, f)
+        }
+
+        fn query_compiled<B>(
+            self,
+            compiled: &ArchetypeQuery,
+            extra_tags: impl IntoIterator<Item = RawTag>,
+            f: impl FnMut(Self::Input<'_>) -> ControlFlow<B>,
+        ) -> ControlFlow<B> {
+            self.query_in(Some(compiled), extra_tags, f)
+        }
+
+        fn query_in<B>(
+            self,
+            compiled: Option<&ArchetypeQuery>,
+            extra_tags: impl IntoIterator<Item = RawTag>,
             mut f: impl FnMut(Self::Input<'_>) -> ControlFlow<B>,
         ) -> ControlFlow<B> {
             // Ensure that we're running on the main thread.
@@ -1218,19 +1890,58 @@ pub mod query_internals {
 
             // Ensure that users cannot flush the database while we're running a query.
             let _guard = borrow_flush_guard();
+            record_query_metric(&DEBUG_QUERY_COUNTER, 1);
 
             // Fetch the storages used by this query.
             let storages = <Self::Heap>::storages();
 
             // Fetch the archetypes containing our desired intersection of tags.
-            let archetypes =
-                ArchetypeId::in_intersection(self.tags().chain(extra_tags), Self::NEEDS_ENTITIES)
-                    .unwrap_or_default();
+            let tags = self.tags().chain(extra_tags);
+
+            // N.B. we only collect the tags when tracing since they'd otherwise be visited once.
+            #[cfg(feature = "tracing")]
+            let tags = tags.collect::<Vec<_>>();
+
+            #[cfg(feature = "tracing")]
+            let _span = tracing::trace_span!(
+                "bort::query",
+                tags = ?tags,
+                entities = tracing::field::Empty,
+            )
+            .entered();
+
+            let fresh_archetypes;
+            let compiled_archetypes;
+            let archetypes: &[ArchetypeQueryInfo] = match compiled {
+                Some(compiled) => {
+                    assert!(
+                        tags.into_iter().all(|tag| compiled.tags().contains(&tag)),
+                        "query uses tags which are not part of its compiled archetype query",
+                    );
+
+                    compiled_archetypes = compiled.archetypes();
+                    &compiled_archetypes
+                }
+                None => {
+                    fresh_archetypes = ArchetypeId::in_intersection(tags, Self::NEEDS_ENTITIES)
+                        .unwrap_or_default();
+                    &fresh_archetypes
+                }
+            };
+
+            #[cfg(feature = "tracing")]
+            _span.record(
+                "entities",
+                archetypes
+                    .iter()
+                    .map(ArchetypeQueryInfo::entity_count)
+                    .sum::<usize>(),
+            );
 
             // For each archetype...
             for archetype in archetypes {
                 // Fetch the component heaps associated with that archetype.
-                let heaps = <Self::Heap>::heaps_for_archetype(&storages, &archetype);
+                let heaps = <Self::Heap>::heaps_for_archetype(&storages, archetype);
                 let mut heaps = RandomAccessZip::new(RandomAccessEnumerate, heaps);
 
                 // For each of those heaps...
@@ -1261,6 +1972,11 @@ pub mod query_internals {
                         if let Some(mut block) =
                             <Self::GroupBorrow>::try_borrow_group(&block, token, &mut loaner)
                         {
+                            record_query_metric(
+                                &DEBUG_QUERY_VISIT_COUNTER,
+                                MultiRefCellIndex::COUNT as u64,
+                            );
+
                             for mut elem in <Self::GroupBorrow>::iter(&mut block).into_iter() {
                                 f(Self::elem_from_block_item(token, &mut elem))?;
                             }
@@ -1274,8 +1990,13 @@ pub mod query_internals {
                         drop(loaner);
 
                         // Otherwise, run the slow-path.
+                        record_query_metric(&DEBUG_QUERY_FALLBACK_COUNTER, 1);
+
                         for index in MultiRefCellIndex::iter() {
-                            Self::call_slow_borrow(token, &block, index, &mut f);
+                            Self::call_slow_borrow(token, &block, index, |input| {
+                                record_query_metric(&DEBUG_QUERY_VISIT_COUNTER, 1);
+                                f(input)
+                            })?;
                         }
                     }
 
@@ -1287,7 +2008,10 @@ pub mod query_internals {
                         let block = blocks.get(complete_heap_block_count_or_big).unwrap();
 
                         for index in MultiRefCellIndex::iter().take(leftover) {
-                            Self::call_slow_borrow(token, &block, index, &mut f);
+                            Self::call_slow_borrow(token, &block, index, |input| {
+                                record_query_metric(&DEBUG_QUERY_VISIT_COUNTER, 1);
+                                f(input)
+                            })?;
                         }
                     }
                 }
@@ -1735,11 +2459,16 @@ pub mod query_internals {
         driver: &M,
         f: impl FnMut((P::Input<'_>, MultiDriverItem<'_, M>)) -> ControlFlow<B>,
     ) -> ControlFlow<B> {
+        let tags = part.tags().chain(extra_tags).collect::<Vec<_>>();
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("bort::driven_query", tags = ?tags).entered();
+
         driver.drive_multi_query(
             &mut QueryDriverTargetInstance::<K, P> {
                 _ty: PhantomData,
                 key,
-                tags: part.tags().chain(extra_tags).collect(),
+                tags,
             },
             f,
         )
@@ -1889,13 +2618,17 @@ pub mod query_internals {
             self
         }
     }
+
+    pub fn next_query_page(cursor: &mut QueryCursor, limit: usize) -> FxHashSet<Entity> {
+        cursor.next_page(limit).into_iter().collect()
+    }
 }
 
 #[macro_export]
 macro_rules! query {
     // Entrypoints
     (
-        for ($($input:tt)*) {
+        $($label:lifetime:)? for ($($input:tt)*) $(break $($outer_label:lifetime),+)? {
             $($body:tt)*
         }
     ) => {
@@ -1906,6 +2639,9 @@ macro_rules! query {
                 built_parts = {()};
                 built_extractor = {()};
                 extra_tags = {$crate::query::query_internals::empty_tag_iter()};
+                compiled = {};
+                borrows = {};
+                loop_labels = {$($label:)?; $(break $($outer_label),+)?};
                 body = {$($body)*};
             }
         }
@@ -1919,15 +2655,41 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($label:lifetime:)?; $($outer_labels:tt)*};
             body = {$($body:tt)*};
         }
-    ) => {
+    ) => {{
+        $crate::query::query! { @check_aliasing {$($borrows)*} }
+
         $crate::query::query_internals::cbit!(
-            for $extractor in $crate::query::query_internals::QueryPart::query($parts, $extra_tags) {
+            $($label:)? for $extractor in $crate::query::query_internals::QueryPart::query($parts, $extra_tags) $($outer_labels)* {
                 $($body)*
             }
         )
-    };
+    }};
+    (
+        @internal {
+            remaining_input = {};
+            bound_event = {};
+            built_parts = {$parts:expr};
+            built_extractor = {$extractor:pat};
+            extra_tags = {$extra_tags:expr};
+            compiled = {$compiled:expr};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($label:lifetime:)?; $($outer_labels:tt)*};
+            body = {$($body:tt)*};
+        }
+    ) => {{
+        $crate::query::query! { @check_aliasing {$($borrows)*} }
+
+        $crate::query::query_internals::cbit!(
+            $($label:)? for $extractor in $crate::query::query_internals::QueryPart::query_compiled($parts, &$compiled, $extra_tags) $($outer_labels)* {
+                $($body)*
+            }
+        )
+    }};
     (
         @internal {
             remaining_input = {};
@@ -1935,14 +2697,19 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($label:lifetime:)?; $($outer_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {{
         #[allow(unused_import)]
         use $crate::query::query_internals::ExtractRefOfMultiQueryDriver;
 
+        $crate::query::query! { @check_aliasing {$($borrows)*} }
+
         $crate::query::query_internals::cbit!(
-            for ($extractor, $name) in $crate::query::query_internals::run_driven_query(
+            $($label:)? for ($extractor, $name) in $crate::query::query_internals::run_driven_query(
                 {
                     #[derive(Copy, Clone, Hash, Eq, PartialEq)]
                     struct MyQueryKey;
@@ -1951,11 +2718,28 @@ macro_rules! query {
                 $parts,
                 $extra_tags,
                 $driver.__extract_ref_of_multi_query_driver(),
-            ) {
+            ) $($outer_labels)* {
                 $($body)*
             }
         )
     }};
+    (
+        @internal {
+            remaining_input = {};
+            bound_event = {$name:pat in $driver:expr};
+            built_parts = {$parts:expr};
+            built_extractor = {$extractor:pat};
+            extra_tags = {$extra_tags:expr};
+            compiled = {$compiled:expr};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
+            body = {$($body:tt)*};
+        }
+    ) => {
+        $crate::query::query_internals::compile_error!(
+            "`query!` cannot combine an event driver with a compiled archetype query"
+        );
+    };
     (
         @internal {
             remaining_input = {};
@@ -1963,6 +2747,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -1973,6 +2760,43 @@ macro_rules! query {
         ));
     };
 
+    // Aliasing checks
+    (@check_aliasing {}) => {};
+    (
+        @check_aliasing {
+            ($kind:ident $name:ident $tag:expr)
+            $(($other_kind:ident $other_name:ident $other_tag:expr))*
+        }
+    ) => {
+        $(
+            // N.B. this block is never executed; it only exists so that the type checker can reject
+            // bindings which borrow the same component while one of them borrows it mutably.
+            #[allow(unreachable_code)]
+            if false {
+                #[allow(unused_imports)]
+                use $crate::query::query_internals::{ResolveAliasedBorrow, ResolveDistinctBorrow};
+
+                #[allow(dead_code, non_camel_case_types)]
+                struct $name {}
+
+                #[allow(dead_code, non_camel_case_types)]
+                struct $other_name {}
+
+                let $other_name = (&$crate::query::query_internals::alias_check(
+                    $crate::query::query_internals::$kind,
+                    &$tag,
+                    $crate::query::query_internals::$other_kind,
+                    &$other_tag,
+                ))
+                .resolve();
+
+                $crate::query::query_internals::assert_not_aliased::<$name, $other_name, _>($other_name);
+            }
+        )*
+
+        $crate::query::query! { @check_aliasing {$(($other_kind $other_name $other_tag))*} }
+    };
+
     // event
     (
         @internal {
@@ -1981,6 +2805,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -1991,6 +2818,9 @@ macro_rules! query {
                 built_parts = {$parts};
                 built_extractor = {$extractor};
                 extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)*};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2002,6 +2832,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2014,6 +2847,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2034,6 +2870,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2044,6 +2883,9 @@ macro_rules! query {
                 built_parts = {($parts, $crate::query::query_internals::EntityQueryPart)};
                 built_extractor = {($extractor, $name)};
                 extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)*};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2055,6 +2897,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2075,6 +2920,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2087,6 +2935,9 @@ macro_rules! query {
                 ))};
                 built_extractor = {($extractor, $name)};
                 extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)*};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2098,6 +2949,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2110,6 +2964,9 @@ macro_rules! query {
                 ))};
                 built_extractor = {($extractor, $name)};
                 extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)*};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2123,6 +2980,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2145,6 +3005,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2157,6 +3020,164 @@ macro_rules! query {
         );
     };
 
+    // `obj+ref` and `obj+mut`
+    (
+        @internal {
+            remaining_input = {obj+ref ($obj:ident, $name:ident) : $ty:ty $(, $($rest:tt)*)?};
+            bound_event = {$($bound_event:tt)*};
+            built_parts = {$parts:expr};
+            built_extractor = {$extractor:pat};
+            extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
+            body = {$($body:tt)*};
+        }
+    ) => {
+        $crate::query::query! {
+            @internal {
+                remaining_input = {$($($rest)*)?};
+                bound_event = {$($bound_event)*};
+                built_parts = {($parts, {
+                    let tag = $crate::query::query_internals::get_tag::<$ty>();
+                    (
+                        $crate::query::query_internals::ObjQueryPart(tag),
+                        $crate::query::query_internals::RefQueryPart(tag),
+                    )
+                })};
+                built_extractor = {($extractor, ($obj, $name))};
+                extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)* (BorrowRef $name $crate::query::query_internals::get_tag::<$ty>())};
+                loop_labels = {$($loop_labels)*};
+                body = {$($body)*};
+            }
+        }
+    };
+    (
+        @internal {
+            remaining_input = {obj+ref ($obj:ident, $name:ident) in $tag:expr $(, $($rest:tt)*)?};
+            bound_event = {$($bound_event:tt)*};
+            built_parts = {$parts:expr};
+            built_extractor = {$extractor:pat};
+            extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
+            body = {$($body:tt)*};
+        }
+    ) => {
+        $crate::query::query! {
+            @internal {
+                remaining_input = {$($($rest)*)?};
+                bound_event = {$($bound_event)*};
+                built_parts = {($parts, {
+                    let tag = $crate::query::query_internals::from_tag($tag);
+                    (
+                        $crate::query::query_internals::ObjQueryPart(tag),
+                        $crate::query::query_internals::RefQueryPart(tag),
+                    )
+                })};
+                built_extractor = {($extractor, ($obj, $name))};
+                extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)* (BorrowRef $name $tag)};
+                loop_labels = {$($loop_labels)*};
+                body = {$($body)*};
+            }
+        }
+    };
+    (
+        @internal {
+            remaining_input = {obj+mut ($obj:ident, $name:ident) : $ty:ty $(, $($rest:tt)*)?};
+            bound_event = {$($bound_event:tt)*};
+            built_parts = {$parts:expr};
+            built_extractor = {$extractor:pat};
+            extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
+            body = {$($body:tt)*};
+        }
+    ) => {
+        $crate::query::query! {
+            @internal {
+                remaining_input = {$($($rest)*)?};
+                bound_event = {$($bound_event)*};
+                built_parts = {($parts, {
+                    let tag = $crate::query::query_internals::get_tag::<$ty>();
+                    (
+                        $crate::query::query_internals::ObjQueryPart(tag),
+                        $crate::query::query_internals::MutQueryPart(tag),
+                    )
+                })};
+                built_extractor = {($extractor, ($obj, $name))};
+                extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)* (BorrowMut $name $crate::query::query_internals::get_tag::<$ty>())};
+                loop_labels = {$($loop_labels)*};
+                body = {$($body)*};
+            }
+        }
+    };
+    (
+        @internal {
+            remaining_input = {obj+mut ($obj:ident, $name:ident) in $tag:expr $(, $($rest:tt)*)?};
+            bound_event = {$($bound_event:tt)*};
+            built_parts = {$parts:expr};
+            built_extractor = {$extractor:pat};
+            extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
+            body = {$($body:tt)*};
+        }
+    ) => {
+        $crate::query::query! {
+            @internal {
+                remaining_input = {$($($rest)*)?};
+                bound_event = {$($bound_event)*};
+                built_parts = {($parts, {
+                    let tag = $crate::query::query_internals::from_tag($tag);
+                    (
+                        $crate::query::query_internals::ObjQueryPart(tag),
+                        $crate::query::query_internals::MutQueryPart(tag),
+                    )
+                })};
+                built_extractor = {($extractor, ($obj, $name))};
+                extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)* (BorrowMut $name $tag)};
+                loop_labels = {$($loop_labels)*};
+                body = {$($body)*};
+            }
+        }
+    };
+
+    // `obj+ref` and `obj+mut` error handling
+    (
+        @internal {
+            remaining_input = {obj+$kind:tt $($anything:tt)*};
+            bound_event = {$($bound_event:tt)*};
+            built_parts = {$parts:expr};
+            built_extractor = {$extractor:pat};
+            extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
+            body = {$($body:tt)*};
+        }
+    ) => {
+        $crate::query::query_internals::compile_error!(
+            $crate::query::query_internals::concat!(
+                "expected `obj+ref` or `obj+mut` followed by a pair of identifiers in the form `(<obj>, \
+                 <value>): <type>` or `(<obj>, <value>) in <expr>`; got `obj+",
+                $crate::query::query_internals::stringify!($kind $($anything)*),
+                "`"
+            ),
+        );
+    };
+
     // `obj`
     (
         @internal {
@@ -2165,6 +3186,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2177,6 +3201,9 @@ macro_rules! query {
                 ))};
                 built_extractor = {($extractor, $name)};
                 extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)*};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2188,6 +3215,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2200,6 +3230,9 @@ macro_rules! query {
                 ))};
                 built_extractor = {($extractor, $name)};
                 extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)*};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2213,6 +3246,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2235,6 +3271,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2255,6 +3294,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2267,6 +3309,9 @@ macro_rules! query {
                 ))};
                 built_extractor = {($extractor, $name)};
                 extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)* (BorrowRef $name $crate::query::query_internals::get_tag::<$ty>())};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2278,6 +3323,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2290,6 +3338,9 @@ macro_rules! query {
                 ))};
                 built_extractor = {($extractor, $name)};
                 extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)* (BorrowRef $name $tag)};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2303,6 +3354,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2325,6 +3379,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2345,6 +3402,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2357,6 +3417,9 @@ macro_rules! query {
                 ))};
                 built_extractor = {($extractor, $name)};
                 extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)* (BorrowMut $name $crate::query::query_internals::get_tag::<$ty>())};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2368,6 +3431,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2380,6 +3446,9 @@ macro_rules! query {
                 ))};
                 built_extractor = {($extractor, $name)};
                 extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)* (BorrowMut $name $tag)};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2393,6 +3462,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2415,6 +3487,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2435,6 +3510,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2447,6 +3525,9 @@ macro_rules! query {
                 ))};
                 built_extractor = {($extractor, $name)};
                 extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)* (BorrowRef $name $crate::query::query_internals::get_tag::<$ty>())};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2458,6 +3539,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2470,6 +3554,9 @@ macro_rules! query {
                 ))};
                 built_extractor = {($extractor, $name)};
                 extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)* (BorrowRef $name $tag)};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2483,6 +3570,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2505,6 +3595,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2525,6 +3618,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2537,6 +3633,9 @@ macro_rules! query {
                 ))};
                 built_extractor = {($extractor, mut $name)};
                 extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)* (BorrowMut $name $crate::query::query_internals::get_tag::<$ty>())};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2548,6 +3647,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2560,6 +3662,9 @@ macro_rules! query {
                 ))};
                 built_extractor = {($extractor, mut $name)};
                 extra_tags = {$extra_tags};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)* (BorrowMut $name $tag)};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2573,6 +3678,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2595,6 +3703,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2615,6 +3726,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2624,10 +3738,13 @@ macro_rules! query {
                 bound_event = {$($bound_event)*};
                 built_parts = {$parts};
                 built_extractor = {$extractor};
-                extra_tags = {$crate::query::query_internals::Iterator::join(
+                extra_tags = {$crate::query::query_internals::Iterator::chain(
                     $extra_tags,
                     $tag,
                 )};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)*};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2639,6 +3756,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2652,6 +3772,9 @@ macro_rules! query {
                     $extra_tags,
                     [$crate::query::query_internals::from_tag_virtual($tag)],
                 )};
+                compiled = {$($compiled)*};
+                borrows = {$($borrows)*};
+                loop_labels = {$($loop_labels)*};
                 body = {$($body)*};
             }
         }
@@ -2665,6 +3788,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2683,6 +3809,9 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
@@ -2695,6 +3824,73 @@ macro_rules! query {
         );
     };
 
+    // Compiled archetype queries
+    (
+        @internal {
+            remaining_input = {compiled $compiled:expr $(, $($rest:tt)*)?};
+            bound_event = {$($bound_event:tt)*};
+            built_parts = {$parts:expr};
+            built_extractor = {$extractor:pat};
+            extra_tags = {$extra_tags:expr};
+            compiled = {};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
+            body = {$($body:tt)*};
+        }
+    ) => {
+        $crate::query::query! {
+            @internal {
+                remaining_input = {$($($rest)*)?};
+                bound_event = {$($bound_event)*};
+                built_parts = {$parts};
+                built_extractor = {$extractor};
+                extra_tags = {$extra_tags};
+                compiled = {$compiled};
+                borrows = {$($borrows)*};
+                loop_labels = {$($loop_labels)*};
+                body = {$($body)*};
+            }
+        }
+    };
+    (
+        @internal {
+            remaining_input = {compiled $compiled:expr $(, $($rest:tt)*)?};
+            bound_event = {$($bound_event:tt)*};
+            built_parts = {$parts:expr};
+            built_extractor = {$extractor:pat};
+            extra_tags = {$extra_tags:expr};
+            compiled = {$($anything:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
+            body = {$($body:tt)*};
+        }
+    ) => {
+        $crate::query::query_internals::compile_error!(
+            "`query!` can have at most one compiled archetype query"
+        );
+    };
+    (
+        @internal {
+            remaining_input = {compiled $($anything:tt)*};
+            bound_event = {$($bound_event:tt)*};
+            built_parts = {$parts:expr};
+            built_extractor = {$extractor:pat};
+            extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
+            body = {$($body:tt)*};
+        }
+    ) => {
+        $crate::query::query_internals::compile_error!(
+            $crate::query::query_internals::concat!(
+                "expected an expression after `compiled`; got `",
+                $crate::query::query_internals::stringify!($($anything)*),
+                "`"
+            ),
+        );
+    };
+
     // General error handling
     (
         @internal {
@@ -2703,13 +3899,16 @@ macro_rules! query {
             built_parts = {$parts:expr};
             built_extractor = {$extractor:pat};
             extra_tags = {$extra_tags:expr};
+            compiled = {$($compiled:tt)*};
+            borrows = {$($borrows:tt)*};
+            loop_labels = {$($loop_labels:tt)*};
             body = {$($body:tt)*};
         }
     ) => {
         $crate::query::query_internals::compile_error!(
             $crate::query::query_internals::concat!(
-                "expected `event`, `entity`, `slot`, `obj`, `ref`, `mut`, `oref`, `omut`, `tag`, or \
-                 `tags`; got `",
+                "expected `event`, `entity`, `slot`, `obj`, `obj+ref`, `obj+mut`, `ref`, `mut`, `oref`, \
+                 `omut`, `tag`, `tags`, or `compiled`; got `",
                 $crate::query::query_internals::stringify!($($anything)*),
                 "`"
             ),
@@ -2718,3 +3917,1068 @@ macro_rules! query {
 }
 
 pub use query;
+
+/// Collects the results of a [`query!`] into a struct of parallel `Vec`s.
+/// `query_collect_soa!(Soa { entities, a in tag_a, b in tag_b })` builds a `Soa` whose `entities`
+/// field holds every entity the query visited and whose `a` and `b` fields hold clones of their
+/// components, in the same order.
+///
+/// The struct must have a `Vec` field for the entities and for every component, and no others.
+#[macro_export]
+macro_rules! query_collect_soa {
+    ($soa:ident {
+        $entities:ident,
+        $($field:ident in $tag:expr),+ $(,)?
+    }) => {{
+        let mut soa = $soa {
+            $entities: $crate::query::query_internals::Vec::new(),
+            $($field: $crate::query::query_internals::Vec::new(),)*
+        };
+
+        $crate::query::query! {
+            for (entity entity, $(ref $field in $tag),*) {
+                soa.$entities.push(entity);
+                $(soa.$field.push($crate::query::query_internals::Clone::clone(&*$field));)*
+            }
+        }
+
+        soa
+    }};
+}
+
+pub use query_collect_soa;
+
+/// Runs a [`query!`] over an [`ArchetypeQuery`]. `query_compiled!(q, for (...) { ... })` is
+/// shorthand for a `query!` whose inputs start with `compiled q`.
+#[macro_export]
+macro_rules! query_compiled {
+    (
+        $compiled:expr,
+        $($label:lifetime:)? for ($($input:tt)*) $(break $($outer_label:lifetime),+)? {
+            $($body:tt)*
+        }
+    ) => {
+        $crate::query::query! {
+            $($label:)? for (compiled $compiled, $($input)*) $(break $($outer_label),+)? {
+                $($body)*
+            }
+        }
+    };
+}
+
+pub use query_compiled;
+
+/// Runs a [`query!`] over the next page of a [`QueryCursor`]. `query_paged!(cursor, limit, for
+/// (...) { ... })` runs the body for at most `limit` of the entities which have yet to be visited
+/// in the cursor's current pass, starting a new pass once the previous one is complete.
+///
+/// The query's inputs should select the same tags the cursor was created with. Entities in the
+/// page which the query no longer matches are skipped but still count as visited.
+#[macro_export]
+macro_rules! query_paged {
+    (
+        $cursor:expr, $limit:expr,
+        $($label:lifetime:)? for ($($input:tt)*) $(break $($outer_label:lifetime),+)? {
+            $($body:tt)*
+        }
+    ) => {{
+        let page = $crate::query::query_internals::next_query_page(&mut $cursor, $limit);
+
+        $crate::query::query! {
+            $($label:)? for (entity paged_entity, $($input)*) $(break $($outer_label),+)? {
+                if page.contains(&paged_entity) {
+                    $($body)*
+                }
+            }
+        }
+    }};
+}
+
+pub use query_paged;
+
+// === Tests === //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entity::OwnedEntity, util::testing::run_on_main_thread};
+
+    #[test]
+    fn tag_union_many_merges_members() {
+        run_on_main_thread(|| {
+            let tags = [VirtualTag::new(), VirtualTag::new(), VirtualTag::new()];
+            let memberships: [&[usize]; 5] = [&[0], &[0, 1], &[1, 2], &[0, 1, 2], &[]];
+
+            let entities = memberships
+                .iter()
+                .map(|&membership| {
+                    let entity = OwnedEntity::new();
+                    for &i in membership {
+                        entity.tag(tags[i]);
+                    }
+                    entity
+                })
+                .collect::<Vec<_>>();
+            flush();
+
+            let expected = |of: &[usize]| {
+                let mut expected = entities
+                    .iter()
+                    .zip(memberships)
+                    .filter(|(_, membership)| membership.iter().any(|i| of.contains(i)))
+                    .map(|(entity, _)| entity.entity())
+                    .collect::<Vec<_>>();
+                expected.sort_unstable();
+                expected
+            };
+
+            let raw = tags.map(RawTag::from);
+            assert_eq!(tag_union_many(raw), expected(&[0, 1, 2]));
+            assert_eq!(tag_union_many([raw[2]]), expected(&[2]));
+            assert_eq!(tag_union(raw[0], raw[2]), expected(&[0, 2]));
+            assert!(tag_union_many([]).is_empty());
+
+            drop(entities);
+            flush();
+        });
+    }
+
+    #[test]
+    fn compiled_query_tracks_changes_across_frames() {
+        #[derive(Debug)]
+        struct Health(u32);
+
+        run_on_main_thread(|| {
+            let tag = Tag::<Health>::new();
+            let burning = VirtualTag::new();
+            let compiled = ArchetypeQuery::compile([tag.into()]);
+
+            let mut alive = Vec::new();
+            let mut next_health = 0;
+            let mut spawn = |count: usize, extra: Option<VirtualTag>| {
+                (0..count)
+                    .map(|_| {
+                        let entity = OwnedEntity::new().with(Health(next_health)).with_tag(tag);
+                        if let Some(extra) = extra {
+                            entity.tag(extra);
+                        }
+                        next_health += 1;
+                        entity
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            // Every live entity must be visited exactly once, which we check by bumping its health.
+            let run = |compiled: &ArchetypeQuery, alive: &[OwnedEntity]| {
+                let mut seen = Vec::new();
+                query_compiled!(compiled, for (entity entity, mut health in tag) {
+                    health.0 += 1;
+                    seen.push((entity, health.0));
+                });
+                seen.sort_unstable();
+
+                let mut expected = alive
+                    .iter()
+                    .map(|entity| (entity.entity(), entity.get::<Health>().0))
+                    .collect::<Vec<_>>();
+                expected.sort_unstable();
+                assert_eq!(seen, expected);
+            };
+
+            // Frame 1: a single archetype.
+            alive.extend(spawn(10, None));
+            flush();
+            run(&compiled, &alive);
+
+            // Frame 2: nothing changed so the resolved archetypes are reused.
+            let resolved = compiled.archetypes();
+            assert!(Rc::ptr_eq(&resolved, &compiled.archetypes()));
+            run(&compiled, &alive);
+
+            // Frame 3: entities join a new archetype and some leave.
+            alive.extend(spawn(5, Some(burning)));
+            alive.drain(2..6);
+            flush();
+            assert!(!Rc::ptr_eq(&resolved, &compiled.archetypes()));
+            run(&compiled, &alive);
+
+            // Frame 4: the new archetype empties out again.
+            alive.retain(|entity| !entity.is_tagged_virtual(burning));
+            flush();
+            run(&compiled, &alive);
+
+            // Frame 5: everything is gone.
+            alive.clear();
+            flush();
+            run(&compiled, &alive);
+        });
+    }
+
+    fn entities_in_tag(tag: impl Into<RawTag>) -> Vec<Entity> {
+        let tag = tag.into();
+        let mut entities = Vec::new();
+        query! {
+            for (entity entity, tag tag) {
+                entities.push(entity);
+            }
+        }
+        entities
+    }
+
+    #[test]
+    fn preserved_order_survives_despawns_and_respawns() {
+        run_on_main_thread(|| {
+            let tag = VirtualTag::new();
+            let other = VirtualTag::new();
+            set_preserve_archetype_order(true);
+
+            // Enough entities to span several entity heaps.
+            let mut alive = (0..300)
+                .map(|_| OwnedEntity::new().with_tag(tag))
+                .collect::<Vec<_>>();
+            flush();
+            assert_eq!(
+                entities_in_tag(tag),
+                alive.iter().map(OwnedEntity::entity).collect::<Vec<_>>(),
+            );
+
+            // Despawn a scattering of entities, move a few to another archetype, and spawn more.
+            let mut i = 0;
+            alive.retain(|_| {
+                i += 1;
+                i % 3 != 0 && !(100..140).contains(&i)
+            });
+
+            let moved = alive.drain(10..15).collect::<Vec<_>>();
+            for entity in &moved {
+                entity.tag(other);
+            }
+
+            alive.extend((0..50).map(|_| OwnedEntity::new().with_tag(tag)));
+            flush();
+
+            // Survivors keep their relative order and newcomers follow in spawn order.
+            let in_tag_only = entities_in_tag(tag)
+                .into_iter()
+                .filter(|entity| !entity.is_tagged_physical(other))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                in_tag_only,
+                alive.iter().map(OwnedEntity::entity).collect::<Vec<_>>(),
+            );
+            assert_eq!(
+                entities_in_tag(other),
+                moved.iter().map(OwnedEntity::entity).collect::<Vec<_>>(),
+            );
+
+            set_preserve_archetype_order(false);
+            drop((alive, moved));
+            flush();
+        });
+    }
+
+    #[test]
+    fn preserved_order_resumes_after_toggling() {
+        run_on_main_thread(|| {
+            let tag = VirtualTag::new();
+            let ids = |entities: &[&OwnedEntity]| {
+                entities
+                    .iter()
+                    .map(|entity| entity.entity())
+                    .collect::<Vec<_>>()
+            };
+
+            set_preserve_archetype_order(true);
+            let [a, b, c, d, e] = [(); 5].map(|_| OwnedEntity::new().with_tag(tag));
+            flush();
+            assert_eq!(entities_in_tag(tag), ids(&[&a, &b, &c, &d, &e]));
+
+            // With the mode disabled, the last entity is swapped into the gap.
+            set_preserve_archetype_order(false);
+            drop(b);
+            flush();
+            assert_eq!(entities_in_tag(tag), ids(&[&a, &e, &c, &d]));
+
+            // Once re-enabled, that order is kept and extended.
+            set_preserve_archetype_order(true);
+            drop(c);
+            let f = OwnedEntity::new().with_tag(tag);
+            let g = OwnedEntity::new().with_tag(tag);
+            flush();
+            assert_eq!(entities_in_tag(tag), ids(&[&a, &e, &d, &f, &g]));
+
+            set_preserve_archetype_order(false);
+            drop((a, d, e, f, g));
+            flush();
+        });
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_for_each_matches_serial() {
+        use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+        use crate::{entity::storage, util::hash_map::FxHashMap};
+
+        #[derive(Debug, Copy, Clone)]
+        struct Weight(u64);
+
+        run_on_main_thread(|| {
+            let tag = Tag::<Weight>::new();
+            let entities = (0..1_000_000)
+                .map(|i| OwnedEntity::new().with(Weight(i % 1000)).with_tag(tag))
+                .collect::<Vec<_>>();
+
+            // Entities outside of the intersection must not be visited.
+            let _untagged = OwnedEntity::new().with(Weight(1_000_000));
+            flush();
+
+            // Workers cannot access components so they read from a copy taken on the main thread.
+            let weights = storage::<Weight>();
+            let weights = entities
+                .iter()
+                .map(|entity| (entity.entity(), *weights.get(entity.entity())))
+                .collect::<FxHashMap<_, _>>();
+
+            let serial = weights.values().map(|weight| weight.0).sum::<u64>();
+
+            let sum = AtomicU64::new(0);
+            let visited = AtomicUsize::new(0);
+
+            par_for_each([tag.raw()], |entity| {
+                sum.fetch_add(weights[&entity].0, Relaxed);
+                visited.fetch_add(1, Relaxed);
+            });
+
+            assert_eq!(visited.into_inner(), entities.len());
+            assert_eq!(sum.into_inner(), serial);
+
+            drop(entities);
+            flush();
+        });
+    }
+
+    #[test]
+    fn query_paged_visits_every_entity_once() {
+        struct Visits(u32);
+
+        run_on_main_thread(|| {
+            let tag = Tag::<Visits>::new();
+            let entities = (0..1000)
+                .map(|_| OwnedEntity::new().with(Visits(0)).with_tag(tag))
+                .collect::<Vec<_>>();
+            flush();
+
+            let mut cursor = QueryCursor::new([tag.raw()]);
+            let mut pages = 0;
+
+            loop {
+                let mut page_len = 0;
+                query_paged!(cursor, 64, for (mut visits in tag) {
+                    visits.0 += 1;
+                    page_len += 1;
+                });
+                assert!(page_len <= 64);
+                pages += 1;
+
+                if cursor.is_pass_complete() {
+                    break;
+                }
+            }
+
+            assert_eq!(pages, 16);
+            assert_eq!(cursor.pass_count(), 1);
+            assert!(entities.iter().all(|e| e.get::<Visits>().0 == 1));
+
+            drop(entities);
+            flush();
+        });
+    }
+
+    #[test]
+    fn query_paged_survives_flushes_and_archetype_changes() {
+        struct Visits(u32);
+
+        run_on_main_thread(|| {
+            let tag = Tag::<Visits>::new();
+            let moved_to = VirtualTag::new();
+            let mut entities = (0..30)
+                .map(|_| OwnedEntity::new().with(Visits(0)).with_tag(tag))
+                .collect::<Vec<_>>();
+            flush();
+
+            let mut cursor = QueryCursor::new([tag.raw()]);
+            query_paged!(cursor, 10, for (mut visits in tag) {
+                visits.0 += 1;
+            });
+
+            let visited = entities.iter().filter(|e| e.get::<Visits>().0 == 1).count();
+            assert_eq!(visited, 10);
+
+            // Move every entity to a new archetype, destroy one which has yet to be visited, and
+            // add a newcomer mid-pass.
+            for entity in &entities {
+                entity.tag(moved_to);
+            }
+            let destroyed = entities
+                .iter()
+                .position(|e| e.get::<Visits>().0 == 0)
+                .unwrap();
+            drop(entities.remove(destroyed));
+            let newcomer = OwnedEntity::new().with(Visits(0)).with_tag(tag);
+            flush();
+
+            while !cursor.is_pass_complete() {
+                query_paged!(cursor, 10, for (mut visits in tag) {
+                    visits.0 += 1;
+                });
+            }
+
+            assert!(entities.iter().all(|e| e.get::<Visits>().0 == 1));
+            assert_eq!(newcomer.get::<Visits>().0, 0);
+
+            // The newcomer is picked up by the next pass.
+            query_paged!(cursor, 100, for (mut visits in tag) {
+                visits.0 += 1;
+            });
+            assert_eq!(cursor.pass_count(), 2);
+            assert_eq!(newcomer.get::<Visits>().0, 1);
+            assert!(entities.iter().all(|e| e.get::<Visits>().0 == 2));
+
+            drop((entities, newcomer));
+            flush();
+        });
+    }
+
+    #[test]
+    fn query_collect_soa_collects_parallel_columns() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Pos(u32);
+
+        #[derive(Debug, Clone, PartialEq)]
+        struct Vel(u32);
+
+        struct Soa {
+            entities: Vec<Entity>,
+            pos: Vec<Pos>,
+            vel: Vec<Vel>,
+        }
+
+        run_on_main_thread(|| {
+            let pos_tag = Tag::<Pos>::new();
+            let vel_tag = Tag::<Vel>::new();
+            let entities = (0..10)
+                .map(|i| {
+                    OwnedEntity::new()
+                        .with(Pos(i))
+                        .with(Vel(i * 10))
+                        .with_tag(pos_tag)
+                        .with_tag(vel_tag)
+                })
+                .collect::<Vec<_>>();
+
+            // Entities missing one of the tags are left out.
+            let partial = OwnedEntity::new().with(Pos(100)).with_tag(pos_tag);
+            flush();
+
+            let soa = query_collect_soa!(Soa {
+                entities,
+                pos in pos_tag,
+                vel in vel_tag,
+            });
+
+            assert_eq!(soa.entities.len(), 10);
+            assert_eq!(soa.pos.len(), 10);
+            assert_eq!(soa.vel.len(), 10);
+            assert!(!soa.entities.contains(&partial.entity()));
+
+            for ((entity, pos), vel) in soa.entities.iter().zip(&soa.pos).zip(&soa.vel) {
+                assert_eq!(&*entity.get::<Pos>(), pos);
+                assert_eq!(vel.0, pos.0 * 10);
+            }
+
+            drop((entities, partial));
+            flush();
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "rand_core")]
+    fn query_sample_draws_uniformly_from_the_intersection() {
+        use crate::util::hash_map::FxHashSet;
+
+        // A SplitMix64 generator is plenty for a deterministic test.
+        struct SplitMix(u64);
+
+        impl rand_core::RngCore for SplitMix {
+            fn next_u32(&mut self) -> u32 {
+                self.next_u64() as u32
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+                let mut z = self.0;
+                z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+                z ^ (z >> 31)
+            }
+
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                rand_core::impls::fill_bytes_via_next(self, dest);
+            }
+
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+                self.fill_bytes(dest);
+                Ok(())
+            }
+        }
+
+        run_on_main_thread(|| {
+            let mut rng = SplitMix(4);
+            let a = VirtualTag::new();
+            let b = VirtualTag::new();
+
+            let both = (0..10)
+                .map(|_| OwnedEntity::new().with_tag(a).with_tag(b))
+                .collect::<Vec<_>>();
+            let only_a = (0..20)
+                .map(|_| OwnedEntity::new().with_tag(a))
+                .collect::<Vec<_>>();
+            flush();
+
+            let tags = [a.raw(), b.raw()];
+
+            // Samples are distinct members of the intersection.
+            let sample = query_sample(tags, 4, &mut rng);
+            assert_eq!(sample.len(), 4);
+            assert_eq!(sample.iter().collect::<FxHashSet<_>>().len(), 4);
+            assert!(sample.iter().all(|e| both.iter().any(|b| b.entity() == *e)));
+
+            // Asking for more than there is returns the entire intersection.
+            let mut everything = query_sample(tags, 100, &mut rng);
+            everything.sort_unstable();
+            let mut expected = both.iter().map(OwnedEntity::entity).collect::<Vec<_>>();
+            expected.sort_unstable();
+            assert_eq!(everything, expected);
+            assert!(query_sample(tags, 0, &mut rng).is_empty());
+
+            // Every member is about as likely to be picked as any other.
+            let mut picks = FxHashMap::<Entity, u32>::default();
+            for _ in 0..2000 {
+                *picks.entry(query_sample(tags, 1, &mut rng)[0]).or_default() += 1;
+            }
+            assert_eq!(picks.len(), 10);
+            assert!(picks.values().all(|&count| (100..300).contains(&count)));
+
+            drop((both, only_a));
+            flush();
+        });
+    }
+
+    #[test]
+    fn query_sorted_by_key_orders_stably_and_skips_missing() {
+        struct Score(u32);
+
+        run_on_main_thread(|| {
+            let tag = VirtualTag::new();
+            let scores = [30, 10, 20, 10, 30];
+
+            let scored = scores
+                .iter()
+                .map(|&score| OwnedEntity::new().with(Score(score)).with_tag(tag))
+                .collect::<Vec<_>>();
+            let unscored = OwnedEntity::new().with_tag(tag);
+            flush();
+
+            let sorted = query_sorted_by_key([tag.raw()], |score: &Score| score.0);
+            assert!(!sorted.contains(&unscored.entity()));
+            assert_eq!(
+                sorted
+                    .iter()
+                    .map(|e| e.get::<Score>().0)
+                    .collect::<Vec<_>>(),
+                [10, 10, 20, 30, 30],
+            );
+
+            // The entities share an archetype which they entered in spawn order so ties keep it.
+            let ties = |key| {
+                sorted
+                    .iter()
+                    .copied()
+                    .filter(|e| e.get::<Score>().0 == key)
+                    .collect::<Vec<_>>()
+            };
+            let expected_ties = |key| {
+                scored
+                    .iter()
+                    .map(OwnedEntity::entity)
+                    .filter(|e| e.get::<Score>().0 == key)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(ties(10), expected_ties(10));
+            assert_eq!(ties(30), expected_ties(30));
+
+            drop((scored, unscored));
+            flush();
+        });
+    }
+
+    #[test]
+    fn iter_entities_reflects_the_last_flush() {
+        run_on_main_thread(|| {
+            let tag = VirtualTag::new();
+            let sorted = |iter: &mut dyn Iterator<Item = Entity>| {
+                let mut entities = iter.collect::<Vec<_>>();
+                entities.sort_unstable();
+                entities
+            };
+
+            let entities = (0..5)
+                .map(|_| OwnedEntity::new().with_tag(tag))
+                .collect::<Vec<_>>();
+            let untagged = OwnedEntity::new();
+
+            // Tags only take effect once flushed.
+            assert_eq!(tag.iter_entities().count(), 0);
+            flush();
+
+            let mut expected = entities.iter().map(OwnedEntity::entity).collect::<Vec<_>>();
+            expected.sort_unstable();
+            assert_eq!(sorted(&mut tag.iter_entities()), expected);
+            assert!(!tag.iter_entities().any(|e| e == untagged.entity()));
+
+            entities[0].untag(tag);
+            flush();
+            assert_eq!(tag.iter_entities().count(), 4);
+            assert!(!tag.iter_entities().any(|e| e == entities[0].entity()));
+
+            drop((entities, untagged));
+            flush();
+            assert_eq!(tag.iter_entities().count(), 0);
+        });
+    }
+
+    #[test]
+    fn tag_sets_filter_queries() {
+        struct Hp(u32);
+
+        run_on_main_thread(|| {
+            let hp = Tag::<Hp>::new();
+            let [a, b] = [VirtualTag::new(), VirtualTag::new()];
+
+            let mut set = TagSet::new();
+            assert!(set.add(a));
+            assert!(!set.add(a));
+            assert!(set.contains(a) && !set.contains(b));
+            assert_eq!(set.iter().collect::<Vec<_>>(), [a.raw()]);
+
+            // Sets are ordered independently of insertion order.
+            assert_eq!(
+                [a.raw(), b.raw()].into_iter().collect::<TagSet>(),
+                TagSet::new().with(b).with(a),
+            );
+
+            let only_a = OwnedEntity::new().with(Hp(1)).with_tag(hp).with_tag(a);
+            let both = OwnedEntity::new()
+                .with(Hp(2))
+                .with_tag(hp)
+                .with_tag(a)
+                .with_tag(b);
+            let neither = OwnedEntity::new().with(Hp(3)).with_tag(hp);
+            flush();
+
+            let collect = |set: &TagSet| {
+                let mut visited = Vec::new();
+                query! {
+                    for (ref hp in hp, tags set) {
+                        visited.push(hp.0);
+                    }
+                }
+                visited.sort_unstable();
+                visited
+            };
+
+            assert_eq!(collect(&TagSet::new()), [1, 2, 3]);
+            assert_eq!(collect(&set), [1, 2]);
+            set.add(b);
+            assert_eq!(collect(&set), [2]);
+            assert!(set.remove(a));
+            assert_eq!(collect(&set), [2]);
+
+            // The clause accepts owned sets too.
+            let mut visited = 0;
+            query! {
+                for (entity entity, tags TagSet::new().with(a).with(b)) {
+                    assert_eq!(entity, both.entity());
+                    visited += 1;
+                }
+            }
+            assert_eq!(visited, 1);
+
+            drop((only_a, both, neither));
+            flush();
+        });
+    }
+
+    #[test]
+    fn tag_intersection_only_returns_entities_with_both_tags() {
+        run_on_main_thread(|| {
+            let [a, b] = [VirtualTag::new(), VirtualTag::new()];
+
+            let only_a = OwnedEntity::new().with_tag(a);
+            let only_b = OwnedEntity::new().with_tag(b);
+            let both = (0..3)
+                .map(|_| OwnedEntity::new().with_tag(a).with_tag(b))
+                .collect::<Vec<_>>();
+            flush();
+
+            let mut intersection = tag_intersection(a, b);
+            intersection.sort_unstable();
+            let mut expected = both.iter().map(OwnedEntity::entity).collect::<Vec<_>>();
+            expected.sort_unstable();
+            assert_eq!(intersection, expected);
+
+            // Intersections are symmetric and membership only changes on flush.
+            assert_eq!(tag_intersection(b, a).len(), 3);
+            only_a.tag(b);
+            assert_eq!(tag_intersection(a, b).len(), 3);
+            flush();
+            assert!(tag_intersection(a, b).contains(&only_a.entity()));
+            assert!(!tag_intersection(a, b).contains(&only_b.entity()));
+
+            // Unions include everyone exactly once.
+            assert_eq!(tag_union(a, b).len(), 5);
+
+            drop((only_a, only_b, both));
+            flush();
+            assert!(tag_intersection(a, b).is_empty());
+            assert!(tag_union(a, b).is_empty());
+        });
+    }
+
+    #[test]
+    fn flush_scoped_flushes_on_drop_unless_cancelled() {
+        run_on_main_thread(|| {
+            let tag = VirtualTag::new();
+            let entity = OwnedEntity::new();
+
+            let flushes = total_flush_count();
+            {
+                let _guard = flush_scoped();
+                entity.tag(tag);
+                assert_eq!(tag.iter_entities().count(), 0);
+            }
+            assert_eq!(total_flush_count(), flushes + 1);
+            assert_eq!(tag.iter_entities().collect::<Vec<_>>(), [entity.entity()]);
+
+            // Cancelled guards leave the database untouched.
+            let guard = flush_scoped();
+            entity.untag(tag);
+            guard.cancel();
+            assert_eq!(total_flush_count(), flushes + 1);
+            assert_eq!(tag.iter_entities().count(), 1);
+
+            drop(entity);
+            flush();
+        });
+    }
+
+    #[test]
+    fn query_bodies_can_break_out_of_labeled_loops() {
+        struct Value(u32);
+
+        run_on_main_thread(|| {
+            let tag = Tag::<Value>::new();
+            let entities = (0..10)
+                .map(|i| OwnedEntity::new().with(Value(i)).with_tag(tag))
+                .collect::<Vec<_>>();
+            flush();
+
+            // Breaking an outer loop stops both the query and the loop around it.
+            let mut visited = 0;
+            let mut rounds = 0;
+            'rounds: loop {
+                rounds += 1;
+                query! {
+                    for (ref value in tag) break 'rounds {
+                        visited += 1;
+                        if value.0 < 10 {
+                            break 'rounds;
+                        }
+                    }
+                }
+            }
+            assert_eq!((rounds, visited), (1, 1));
+
+            // Labeled queries can be continued and broken out of from nested loops.
+            let mut odd = Vec::new();
+            query! {
+                'query: for (ref value in tag) {
+                    for _ in 0..3 {
+                        if value.0 % 2 == 0 {
+                            continue 'query;
+                        }
+
+                        odd.push(value.0);
+                        if odd.len() == 3 {
+                            break 'query;
+                        }
+                        continue 'query;
+                    }
+                }
+            }
+            assert_eq!(odd.len(), 3);
+            assert!(odd.iter().all(|value| value % 2 == 1));
+
+            drop(entities);
+            flush();
+        });
+    }
+
+    #[test]
+    fn obj_ref_and_obj_mut_bind_the_obj_and_its_value() {
+        #[derive(Debug)]
+        struct Counter(u32);
+
+        run_on_main_thread(|| {
+            let tag = Tag::<Counter>::new();
+            let entities = (0..5)
+                .map(|i| OwnedEntity::new().with(Counter(i)).with_tag(tag))
+                .collect::<Vec<_>>();
+            flush();
+
+            let mut seen = Vec::new();
+            query! {
+                for (obj+ref (obj, counter) in tag) {
+                    assert_eq!(obj.entity().get::<Counter>().0, counter.0);
+                    seen.push((obj, counter.0));
+                }
+            }
+            assert_eq!(seen.len(), 5);
+
+            query! {
+                for (obj+mut (obj, counter) in tag) {
+                    assert!(obj.is_alive());
+                    counter.0 += 100;
+                }
+            }
+
+            // The mutations went through to the components the objs point at.
+            for (obj, before) in seen {
+                assert_eq!(obj.get().0, before + 100);
+            }
+            for (i, entity) in entities.iter().enumerate() {
+                assert_eq!(entity.get::<Counter>().0, i as u32 + 100);
+            }
+
+            drop(entities);
+            flush();
+        });
+    }
+
+    #[test]
+    fn warm_archetype_reserves_the_heaps_used_by_the_first_flush() {
+        use crate::{debug::heap_count, entity::storage};
+
+        #[derive(Debug)]
+        struct Warm(u32);
+
+        run_on_main_thread(|| {
+            let tag = Tag::<Warm>::new();
+            let warm = storage::<Warm>();
+
+            let heaps_before = heap_count();
+            warm_archetype([tag.raw()]);
+            let heaps_warmed = heap_count();
+            assert!(heaps_warmed > heaps_before);
+
+            // The reserved heaps aren't exposed to queries until entities move into them.
+            let archetypes = ArchetypeId::in_intersection([tag.raw()], false).unwrap();
+            assert_eq!(archetypes.len(), 1);
+            assert_eq!(archetypes[0].heap_count(), 0);
+            assert!(archetypes[0].heaps_for(&warm).is_empty());
+
+            let mut visited = 0;
+            query! {
+                for (ref _warm in tag) {
+                    visited += 1;
+                }
+            }
+            assert_eq!(visited, 0);
+
+            // The first flush moves the components into the reserved heaps rather than allocating
+            // new ones. Releasing the emptied anonymous heaps gets us back to where we started.
+            let entities = (0..10)
+                .map(|i| OwnedEntity::new().with(Warm(i)).with_tag(tag))
+                .collect::<Vec<_>>();
+            flush();
+            warm.shrink_to_fit();
+            assert_eq!(heap_count(), heaps_warmed);
+
+            let archetypes = ArchetypeId::in_intersection([tag.raw()], false).unwrap();
+            assert_eq!(archetypes[0].heaps_for(&warm).len(), 1);
+
+            // Warming an archetype which already has entities leaves them alone.
+            warm_archetype([tag.raw()]);
+            assert_eq!(heap_count(), heaps_warmed);
+            for (i, entity) in entities.iter().enumerate() {
+                assert_eq!(entity.get::<Warm>().0, i as u32);
+            }
+
+            let mut visited = 0;
+            query! {
+                for (ref _warm in tag) {
+                    visited += 1;
+                }
+            }
+            assert_eq!(visited, 10);
+
+            drop(entities);
+            flush();
+        });
+    }
+
+    #[test]
+    fn global_tags_can_be_found_by_name() {
+        struct Named;
+
+        impl HasGlobalManagedTag for Named {
+            type Component = Named;
+        }
+
+        struct NamedVirtual;
+
+        impl HasGlobalVirtualTag for NamedVirtual {}
+
+        struct NeverFetched;
+
+        impl HasGlobalVirtualTag for NeverFetched {}
+
+        run_on_main_thread(|| {
+            let managed = Tag::<Named>::global::<Named>();
+            let virtual_tag = VirtualTag::global::<NamedVirtual>();
+
+            let managed_name = type_name::<Named>();
+            assert_eq!(global_tag_by_name(managed_name), Some(managed.raw()));
+            assert_eq!(global_tag_name(managed.raw()), Some(managed_name));
+
+            let virtual_name = type_name::<NamedVirtual>();
+            assert_eq!(global_tag_by_name(virtual_name), Some(virtual_tag.raw()));
+            assert_eq!(global_tag_name(virtual_tag.raw()), Some(virtual_name));
+
+            // Tags which were never fetched and non-global tags are unknown.
+            assert_eq!(global_tag_by_name(type_name::<NeverFetched>()), None);
+            assert_eq!(global_tag_name(VirtualTag::new().raw()), None);
+            assert_eq!(global_tag_by_name("not a tag"), None);
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "tracing")]
+    fn queries_record_tracing_spans() {
+        use crate::util::testing::record_spans;
+
+        struct Traced;
+
+        run_on_main_thread(|| {
+            let tag = Tag::<Traced>::new();
+            let entities = (0..3)
+                .map(|_| OwnedEntity::new().with(Traced).with_tag(tag))
+                .collect::<Vec<_>>();
+            flush();
+
+            let (visited, spans) = record_spans(|| {
+                let mut visited = 0;
+                query! {
+                    for (ref _traced in tag) {
+                        visited += 1;
+                    }
+                }
+                visited
+            });
+            assert_eq!(visited, 3);
+
+            let span = spans
+                .iter()
+                .find(|span| span.name == "bort::query")
+                .unwrap();
+            assert_eq!(span.field("entities"), Some("3"));
+            assert!(span.field("tags").is_some());
+
+            drop(entities);
+            flush();
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "query-timing")]
+    fn timed_sections_are_accumulated_in_the_report() {
+        use std::{thread, time::Duration};
+
+        const SLOW: &str = "tests::timed_sections_are_accumulated_in_the_report::slow";
+        const FAST: &str = "tests::timed_sections_are_accumulated_in_the_report::fast";
+
+        timed(SLOW, || thread::sleep(Duration::from_millis(5)));
+        assert_eq!(timed(SLOW, || 3), 3);
+        timed(FAST, || ());
+
+        let report = timing_report();
+        let position = |name| {
+            report
+                .iter()
+                .position(|&(other, ..)| other == name)
+                .unwrap()
+        };
+        let (slow, fast) = (report[position(SLOW)], report[position(FAST)]);
+
+        assert_eq!(slow.2, 2);
+        assert!(slow.1 >= Duration::from_millis(5));
+        assert_eq!(fast.2, 1);
+
+        // Sections are sorted by the total time spent in them.
+        assert!(position(SLOW) < position(FAST));
+    }
+
+    #[test]
+    #[cfg(not(feature = "query-timing"))]
+    fn timed_is_transparent_without_the_feature() {
+        assert_eq!(
+            timed("tests::timed_is_transparent_without_the_feature", || 3),
+            3
+        );
+        assert!(timing_report().is_empty());
+    }
+
+    #[test]
+    fn tag_snapshots_are_stable_across_untags_and_flushes() {
+        struct Member(u32);
+
+        run_on_main_thread(|| {
+            let tag = Tag::<Member>::new();
+            let entities = (0..6)
+                .map(|i| OwnedEntity::new().with(Member(i)).with_tag(tag))
+                .collect::<Vec<_>>();
+            flush();
+
+            let snapshot = tag.snapshot();
+            let captured = snapshot.entities().to_vec();
+            assert_eq!(snapshot.len(), 6);
+
+            // Untagging every member while walking the snapshot doesn't disturb it.
+            for (i, entity) in snapshot.iter().enumerate() {
+                entity.untag(tag);
+                if i % 2 == 0 {
+                    flush();
+                }
+            }
+            flush();
+
+            assert!(tag.snapshot().is_empty());
+            assert_eq!(snapshot.entities(), captured);
+            assert!(entities.iter().all(|e| snapshot.contains(e.entity())));
+
+            drop(entities);
+            flush();
+        });
+    }
+}
@@ -0,0 +1,232 @@
+use std::{any::TypeId, marker::PhantomData, sync::Mutex};
+
+use derive_where::derive_where;
+
+use crate::{
+    entity::{register_on_remove, Entity},
+    util::{
+        hash_map::{ConstSafeBuildHasherDefault, FxHashSet},
+        misc::unpoison,
+    },
+};
+
+// === Relation === //
+
+/// A component listing the targets of every `R` link originating from its owning entity.
+///
+/// Links are mirrored by a back-reference component on each target so that despawning either end
+/// of a link, or removing either component, removes the link from the other end as well.
+#[derive_where(Debug, Clone, Default)]
+pub struct Relation<R: 'static> {
+    _ty: PhantomData<fn(R) -> R>,
+    targets: Vec<Entity>,
+}
+
+#[derive_where(Debug, Default)]
+struct RelationSources<R: 'static> {
+    _ty: PhantomData<fn(R) -> R>,
+    sources: Vec<Entity>,
+}
+
+impl<R: 'static> Relation<R> {
+    pub fn targets(&self) -> &[Entity] {
+        &self.targets
+    }
+
+    pub fn contains(&self, target: Entity) -> bool {
+        self.targets.contains(&target)
+    }
+
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    /// Links `source` to `target`, returning `false` if they were already linked.
+    pub fn link(source: Entity, target: Entity) -> bool {
+        assert!(
+            source.is_alive(),
+            "Attempted to link from dead entity {source:?}"
+        );
+        assert!(
+            target.is_alive(),
+            "Attempted to link to dead entity {target:?}"
+        );
+
+        register_relation_hooks::<R>();
+
+        if !source.has::<Self>() {
+            source.insert(Self::default());
+        }
+
+        if source.get::<Self>().contains(target) {
+            return false;
+        }
+
+        if !target.has::<RelationSources<R>>() {
+            target.insert(RelationSources::<R>::default());
+        }
+
+        source.get_mut::<Self>().targets.push(target);
+        target.get_mut::<RelationSources<R>>().sources.push(source);
+        true
+    }
+
+    /// Removes the link from `source` to `target`, returning `false` if there was no such link.
+    pub fn unlink(source: Entity, target: Entity) -> bool {
+        let removed = source.is_alive()
+            && source.has::<Self>()
+            && remove_entity(&mut source.get_mut::<Self>().targets, target);
+
+        if removed && target.is_alive() && target.has::<RelationSources<R>>() {
+            remove_entity(&mut target.get_mut::<RelationSources<R>>().sources, source);
+        }
+
+        removed
+    }
+
+    pub fn is_linked(source: Entity, target: Entity) -> bool {
+        source.is_alive() && source.has::<Self>() && source.get::<Self>().contains(target)
+    }
+
+    /// Returns a snapshot of every entity `source` links to, in the order they were linked.
+    pub fn targets_of(source: Entity) -> Vec<Entity> {
+        if source.is_alive() && source.has::<Self>() {
+            source.get::<Self>().targets.clone()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Returns a snapshot of every entity linking to `target`, in the order they were linked.
+    pub fn sources_of(target: Entity) -> Vec<Entity> {
+        if target.is_alive() && target.has::<RelationSources<R>>() {
+            target.get::<RelationSources<R>>().sources.clone()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+fn remove_entity(list: &mut Vec<Entity>, entity: Entity) -> bool {
+    let Some(idx) = list.iter().position(|&other| other == entity) else {
+        return false;
+    };
+
+    list.remove(idx);
+    true
+}
+
+fn register_relation_hooks<R: 'static>() {
+    static REGISTERED: Mutex<FxHashSet<TypeId>> =
+        Mutex::new(FxHashSet::with_hasher(ConstSafeBuildHasherDefault::new()));
+
+    if !unpoison(REGISTERED.lock()).insert(TypeId::of::<R>()) {
+        return;
+    }
+
+    register_on_remove::<Relation<R>>(|source, relation| {
+        for &target in &relation.targets {
+            if target.is_alive() && target.has::<RelationSources<R>>() {
+                remove_entity(&mut target.get_mut::<RelationSources<R>>().sources, source);
+            }
+        }
+    });
+
+    register_on_remove::<RelationSources<R>>(|target, sources| {
+        for &source in &sources.sources {
+            if source.is_alive() && source.has::<Relation<R>>() {
+                remove_entity(&mut source.get_mut::<Relation<R>>().targets, target);
+            }
+        }
+    });
+}
+
+// === Children === //
+
+/// The relation kind used by [`Entity::add_child`] and friends. A parent holds a
+/// `Relation<Children>` listing its children.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Children;
+
+// === Tests === //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entity::OwnedEntity, query::flush, util::testing::run_on_main_thread};
+
+    #[test]
+    fn despawned_targets_are_unlinked() {
+        struct Follows;
+
+        run_on_main_thread(|| {
+            let leader = OwnedEntity::new();
+            let follower = OwnedEntity::new();
+            let other = OwnedEntity::new();
+
+            assert!(Relation::<Follows>::link(
+                follower.entity(),
+                leader.entity()
+            ));
+            assert!(Relation::<Follows>::link(follower.entity(), other.entity()));
+            assert!(!Relation::<Follows>::link(
+                follower.entity(),
+                leader.entity()
+            ));
+            assert_eq!(
+                Relation::<Follows>::sources_of(leader.entity()),
+                [follower.entity()]
+            );
+
+            let leader_entity = leader.entity();
+            drop(leader);
+            flush();
+
+            assert!(!Relation::<Follows>::is_linked(
+                follower.entity(),
+                leader_entity
+            ));
+            assert_eq!(
+                Relation::<Follows>::targets_of(follower.entity()),
+                [other.entity()]
+            );
+
+            // Despawning the source clears the back-reference on its targets as well.
+            drop(follower);
+            flush();
+            assert!(Relation::<Follows>::sources_of(other.entity()).is_empty());
+
+            drop(other);
+            flush();
+        });
+    }
+
+    #[test]
+    fn despawned_children_leave_their_parent() {
+        run_on_main_thread(|| {
+            let parent = OwnedEntity::new();
+            let first = OwnedEntity::new();
+            let second = OwnedEntity::new();
+
+            parent.add_child(first.entity());
+            parent.add_child(second.entity());
+            assert_eq!(parent.children(), [first.entity(), second.entity()]);
+            assert_eq!(first.parent(), Some(parent.entity()));
+
+            drop(first);
+            flush();
+            assert_eq!(parent.children(), [second.entity()]);
+
+            drop(parent);
+            flush();
+            assert_eq!(second.parent(), None);
+
+            drop(second);
+            flush();
+        });
+    }
+}
@@ -0,0 +1,736 @@
+use std::{
+    any::{type_name, TypeId},
+    borrow::Cow,
+    cell::Cell,
+    error::Error,
+    fmt,
+    sync::Mutex,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    core::token::MainThreadToken,
+    database::DbRoot,
+    entity::{Entity, OwnedEntity},
+    obj::Obj,
+    query::{global_tag_by_name, global_tag_name, RawTag},
+    util::{
+        hash_map::{ConstSafeBuildHasherDefault, FxHashMap, FxHashSet},
+        misc::unpoison,
+    },
+};
+
+// === Registration === //
+
+/// A component which can be written into a [`WorldSnapshot`] and read back out of it.
+///
+/// References to other entities must be written through the [`SaveContext`] and read back through
+/// the [`LoadContext`] since restored entities are assigned new identifiers.
+pub trait SnapshotComponent: 'static + Sized {
+    fn save(&self, cx: &SaveContext) -> Value;
+
+    /// Reconstructs the component, returning `None` if `value` is malformed.
+    fn load(value: &Value, cx: &LoadContext) -> Option<Self>;
+}
+
+/// Converts a component to and from its snapshot representation.
+trait SnapshotCodec<T> {
+    fn save(value: &T, cx: &SaveContext) -> Value;
+
+    fn load(value: &Value, cx: &LoadContext) -> Option<T>;
+}
+
+struct ManualCodec;
+
+impl<T: SnapshotComponent> SnapshotCodec<T> for ManualCodec {
+    fn save(value: &T, cx: &SaveContext) -> Value {
+        value.save(cx)
+    }
+
+    fn load(value: &Value, cx: &LoadContext) -> Option<T> {
+        T::load(value, cx)
+    }
+}
+
+struct SerdeCodec;
+
+impl<T: Serialize + DeserializeOwned> SnapshotCodec<T> for SerdeCodec {
+    fn save(value: &T, _cx: &SaveContext) -> Value {
+        serde_json::to_value(value).unwrap_or_else(|err| {
+            panic!(
+                "Attempted to snapshot a {} component which failed to serialize: {err}",
+                type_name::<T>(),
+            )
+        })
+    }
+
+    fn load(value: &Value, _cx: &LoadContext) -> Option<T> {
+        T::deserialize(value).ok()
+    }
+}
+
+#[derive(Copy, Clone)]
+struct SnapshotComponentType {
+    id: TypeId,
+    name: &'static str,
+    save: fn(Entity, &SaveContext) -> Option<Value>,
+    load: fn(Entity, &Value, &LoadContext) -> LoadOutcome,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum LoadOutcome {
+    Restored,
+    Deferred,
+    Invalid,
+}
+
+impl SnapshotComponentType {
+    fn of<T: 'static, C: SnapshotCodec<T>>() -> Self {
+        fn save<T: 'static, C: SnapshotCodec<T>>(
+            entity: Entity,
+            cx: &SaveContext,
+        ) -> Option<Value> {
+            entity.has::<T>().then(|| C::save(&entity.get::<T>(), cx))
+        }
+
+        fn load<T: 'static, C: SnapshotCodec<T>>(
+            entity: Entity,
+            value: &Value,
+            cx: &LoadContext,
+        ) -> LoadOutcome {
+            cx.deferred.set(false);
+            let value = C::load(value, cx);
+
+            if cx.deferred.get() {
+                return LoadOutcome::Deferred;
+            }
+
+            let Some(value) = value else {
+                return LoadOutcome::Invalid;
+            };
+
+            entity.insert(value);
+            LoadOutcome::Restored
+        }
+
+        Self {
+            id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+            save: save::<T, C>,
+            load: load::<T, C>,
+        }
+    }
+}
+
+static SNAPSHOT_COMPONENTS: Mutex<FxHashMap<Cow<'static, str>, SnapshotComponentType>> =
+    Mutex::new(FxHashMap::with_hasher(ConstSafeBuildHasherDefault::new()));
+
+static SNAPSHOT_TAGS: Mutex<FxHashMap<Cow<'static, str>, RawTag>> =
+    Mutex::new(FxHashMap::with_hasher(ConstSafeBuildHasherDefault::new()));
+
+/// Registers `T` under `name` so that it is included in every [`snapshot`]. Components of
+/// unregistered types are not captured.
+pub fn register_snapshot_component<T: SnapshotComponent>(name: impl Into<Cow<'static, str>>) {
+    register_snapshot_component_type(name.into(), SnapshotComponentType::of::<T, ManualCodec>());
+}
+
+/// Registers `T` under `name` like [`register_snapshot_component`] but uses its `serde`
+/// implementations to save and load it.
+///
+/// Entity references inside `T` are written verbatim and are not remapped to the restored
+/// entities. Components which refer to other entities should implement [`SnapshotComponent`]
+/// instead.
+pub fn register_serde_snapshot_component<T>(name: impl Into<Cow<'static, str>>)
+where
+    T: 'static + Serialize + DeserializeOwned,
+{
+    register_snapshot_component_type(name.into(), SnapshotComponentType::of::<T, SerdeCodec>());
+}
+
+fn register_snapshot_component_type(name: Cow<'static, str>, ty: SnapshotComponentType) {
+    let mut registered = unpoison(SNAPSHOT_COMPONENTS.lock());
+
+    if let Some(existing) = registered.get(&name) {
+        assert_eq!(
+            existing.id, ty.id,
+            "snapshot component name {name:?} is already registered to {}",
+            existing.name,
+        );
+        return;
+    }
+
+    registered.insert(name, ty);
+}
+
+/// Registers `tag` under `name` so that membership in it is included in every [`snapshot`]. Global
+/// tags are captured under their type name without being registered.
+pub fn register_snapshot_tag(name: impl Into<Cow<'static, str>>, tag: impl Into<RawTag>) {
+    let name = name.into();
+    let tag = tag.into();
+    let mut registered = unpoison(SNAPSHOT_TAGS.lock());
+
+    if let Some(&existing) = registered.get(&name) {
+        assert_eq!(
+            existing, tag,
+            "snapshot tag name {name:?} is already registered to {existing:?}",
+        );
+        return;
+    }
+
+    registered.insert(name, tag);
+}
+
+fn snapshot_tag_name(tag: RawTag) -> Option<Cow<'static, str>> {
+    if let Some(name) = global_tag_name(tag) {
+        return Some(Cow::Borrowed(name));
+    }
+
+    unpoison(SNAPSHOT_TAGS.lock())
+        .iter()
+        .find(|&(_, &other)| other == tag)
+        .map(|(name, _)| name.clone())
+}
+
+fn snapshot_tag_by_name(name: &str) -> Option<RawTag> {
+    let registered = unpoison(SNAPSHOT_TAGS.lock()).get(name).copied();
+    registered.or_else(|| global_tag_by_name(name))
+}
+
+// === Snapshot === //
+
+/// A serialized copy of every alive entity alongside its tags and its
+/// [registered](register_snapshot_component) components.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldSnapshot(Value);
+
+impl WorldSnapshot {
+    pub fn from_json(value: Value) -> Self {
+        Self(value)
+    }
+
+    pub fn as_json(&self) -> &Value {
+        &self.0
+    }
+
+    pub fn into_json(self) -> Value {
+        self.0
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(&self.0).expect("snapshots are always serializable")
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(bytes).map(Self)
+    }
+}
+
+impl Serialize for WorldSnapshot {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for WorldSnapshot {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Value::deserialize(deserializer).map(Self)
+    }
+}
+
+/// Returns the components of every entity in `snapshot` keyed by the identifier the entity had at
+/// the time, or `None` if the snapshot is malformed.
+pub(crate) fn snapshot_components(
+    snapshot: &WorldSnapshot,
+) -> Option<FxHashMap<u64, &Map<String, Value>>> {
+    snapshot.0["entities"]
+        .as_array()?
+        .iter()
+        .map(|entry| Some((entry["id"].as_u64()?, entry["components"].as_object()?)))
+        .collect()
+}
+
+#[derive(Debug)]
+pub struct SaveContext {
+    _private: (),
+}
+
+impl SaveContext {
+    /// Writes a reference to `entity`. References to dead entities are written as `null`.
+    pub fn entity(&self, entity: Entity) -> Value {
+        if entity.is_alive() {
+            json!(entity.id())
+        } else {
+            Value::Null
+        }
+    }
+
+    pub fn obj<T: 'static>(&self, obj: Obj<T>) -> Value {
+        self.entity(obj.entity())
+    }
+}
+
+/// Captures every alive entity alongside its tags and its [registered](register_snapshot_component)
+/// components. Tags which are neither global nor [registered](register_snapshot_tag) are skipped.
+pub fn snapshot() -> WorldSnapshot {
+    let components = unpoison(SNAPSHOT_COMPONENTS.lock())
+        .iter()
+        .map(|(name, &ty)| (name.clone(), ty))
+        .collect::<Vec<_>>();
+
+    let mut entities = {
+        let token = MainThreadToken::acquire_fmt("snapshot the world");
+        let db = DbRoot::get(token);
+
+        db.debug_alive_list()
+            .map(|entity| {
+                let tags = db
+                    .get_entity_virtual_tags(entity)
+                    .expect("alive entities have tags");
+
+                (entity.into_dangerous_entity(), tags)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    entities.sort_by_key(|(entity, _)| entity.id());
+
+    let cx = SaveContext { _private: () };
+    let entities = entities
+        .into_iter()
+        .map(|(entity, tags)| {
+            let tags = tags
+                .into_iter()
+                .filter_map(|tag| snapshot_tag_name(tag.into_dangerous_tag()))
+                .collect::<Vec<_>>();
+
+            let components = components
+                .iter()
+                .filter_map(|(name, ty)| Some((name.to_string(), (ty.save)(entity, &cx)?)))
+                .collect::<Map<_, _>>();
+
+            json!({
+                "id": entity.id(),
+                "tags": tags,
+                "components": components,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    WorldSnapshot(json!({ "entities": entities }))
+}
+
+// === Restore === //
+
+#[derive(Debug)]
+pub struct LoadContext {
+    remap: FxHashMap<u64, Entity>,
+    pending: FxHashSet<(Entity, TypeId)>,
+    deferred: Cell<bool>,
+}
+
+impl LoadContext {
+    /// Reads a reference written by [`SaveContext::entity`], returning the restored entity it now
+    /// refers to. Returns `None` for `null` and for entities which were not part of the snapshot.
+    pub fn entity(&self, value: &Value) -> Option<Entity> {
+        self.remap.get(&value.as_u64()?).copied()
+    }
+
+    /// Reads a reference written by [`SaveContext::obj`].
+    ///
+    /// Components are restored in an order which lets every acyclic chain of `Obj`s resolve. If the
+    /// `Obj` refers to a component which has yet to be restored, the load is retried once that
+    /// component is present. `Obj`s forming a cycle can never resolve so [`restore`] fails with
+    /// [`RestoreError::Cycle`] instead.
+    pub fn obj<T: 'static>(&self, value: &Value) -> Option<Obj<T>> {
+        let entity = self.entity(value)?;
+
+        if self.pending.contains(&(entity, TypeId::of::<T>())) {
+            self.deferred.set(true);
+            return None;
+        }
+
+        entity.try_obj()
+    }
+}
+
+/// The entities spawned by [`restore`].
+#[derive(Debug, Default)]
+pub struct RestoredWorld {
+    entities: Vec<OwnedEntity>,
+    remap: FxHashMap<u64, Entity>,
+}
+
+impl RestoredWorld {
+    /// Returns the restored entity corresponding to the entity with identifier `id` at the time
+    /// of the snapshot.
+    pub fn entity(&self, id: u64) -> Option<Entity> {
+        self.remap.get(&id).copied()
+    }
+
+    pub fn entities(&self) -> &[OwnedEntity] {
+        &self.entities
+    }
+
+    pub fn into_entities(self) -> Vec<OwnedEntity> {
+        self.entities
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum RestoreError {
+    /// The snapshot did not have the structure produced by [`snapshot`].
+    Malformed,
+    /// The snapshot contained a component whose name was never registered.
+    UnknownComponent { name: String },
+    /// The snapshot contained a tag whose name was never registered.
+    UnknownTag { name: String },
+    /// The component's [`SnapshotComponent::load`] rejected its value.
+    InvalidComponent { name: String, entity: u64 },
+    /// The component could not be restored because the `Obj`s it refers to form a cycle. See
+    /// [`LoadContext::obj`].
+    Cycle { name: String, entity: u64 },
+}
+
+impl Error for RestoreError {}
+
+impl fmt::Display for RestoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => f.write_str("the snapshot is malformed"),
+            Self::UnknownComponent { name } => {
+                write!(f, "the snapshot contains unregistered component {name:?}")
+            }
+            Self::UnknownTag { name } => {
+                write!(f, "the snapshot contains unregistered tag {name:?}")
+            }
+            Self::InvalidComponent { name, entity } => write!(
+                f,
+                "the snapshot contains an invalid {name:?} component on entity {entity}",
+            ),
+            Self::Cycle { name, entity } => write!(
+                f,
+                "the {name:?} component on entity {entity} is part of a cycle of `Obj` references",
+            ),
+        }
+    }
+}
+
+/// Spawns a fresh copy of every entity in `snapshot`, remapping the entity references inside their
+/// components to point to the new entities.
+///
+/// The entities which are currently alive are left untouched so rolling the world back to a
+/// snapshot involves destroying them first. If restoration fails, every entity spawned so far is
+/// destroyed.
+pub fn restore(snapshot: &WorldSnapshot) -> Result<RestoredWorld, RestoreError> {
+    let entries = snapshot.0["entities"]
+        .as_array()
+        .ok_or(RestoreError::Malformed)?;
+
+    // Spawn every entity up-front so that references between them can be resolved.
+    let mut world = RestoredWorld::default();
+    let mut ids = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        let id = entry["id"].as_u64().ok_or(RestoreError::Malformed)?;
+        let entity = OwnedEntity::new();
+        world.remap.insert(id, entity.entity());
+        world.entities.push(entity);
+        ids.push(id);
+    }
+
+    // Resolve the components which need to be restored.
+    let mut work = Vec::new();
+
+    {
+        let registered = unpoison(SNAPSHOT_COMPONENTS.lock());
+
+        for ((entry, &id), entity) in entries.iter().zip(&ids).zip(&world.entities) {
+            let components = entry["components"]
+                .as_object()
+                .ok_or(RestoreError::Malformed)?;
+
+            for (name, value) in components {
+                let Some(&ty) = registered.get(name.as_str()) else {
+                    return Err(RestoreError::UnknownComponent { name: name.clone() });
+                };
+
+                work.push((id, entity.entity(), name, ty, value));
+            }
+        }
+    }
+
+    // Restore components, deferring those which refer to `Obj`s which have yet to be restored.
+    let mut cx = LoadContext {
+        remap: world.remap.clone(),
+        pending: work
+            .iter()
+            .map(|&(_, entity, _, ty, _)| (entity, ty.id))
+            .collect(),
+        deferred: Cell::new(false),
+    };
+
+    while !work.is_empty() {
+        let prev_len = work.len();
+        let mut remaining = Vec::new();
+
+        for (id, entity, name, ty, value) in work.drain(..) {
+            match (ty.load)(entity, value, &cx) {
+                LoadOutcome::Restored => {
+                    cx.pending.remove(&(entity, ty.id));
+                }
+                LoadOutcome::Deferred => remaining.push((id, entity, name, ty, value)),
+                LoadOutcome::Invalid => {
+                    return Err(RestoreError::InvalidComponent {
+                        name: name.clone(),
+                        entity: id,
+                    })
+                }
+            }
+        }
+
+        // If every remaining component was deferred, none of them will ever be restorable.
+        if remaining.len() == prev_len {
+            let (id, _, name, _, _) = remaining[0];
+
+            return Err(RestoreError::Cycle {
+                name: name.clone(),
+                entity: id,
+            });
+        }
+
+        work = remaining;
+    }
+
+    // Restore tag membership now that the components backing managed tags are present.
+    for (entry, entity) in entries.iter().zip(&world.entities) {
+        let tags = entry["tags"]
+            .as_array()
+            .ok_or(RestoreError::Malformed)?
+            .iter()
+            .map(|name| {
+                let name = name.as_str().ok_or(RestoreError::Malformed)?;
+                snapshot_tag_by_name(name).ok_or_else(|| RestoreError::UnknownTag {
+                    name: name.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        entity.tag_many(tags);
+    }
+
+    Ok(world)
+}
+
+// === Tests === //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        debug::alive_entity_count, query::flush, util::testing::run_on_main_thread, VirtualTag,
+    };
+
+    #[derive(Debug, Eq, PartialEq)]
+    struct Name(String);
+
+    impl SnapshotComponent for Name {
+        fn save(&self, _cx: &SaveContext) -> Value {
+            json!(self.0)
+        }
+
+        fn load(value: &Value, _cx: &LoadContext) -> Option<Self> {
+            Some(Self(value.as_str()?.to_string()))
+        }
+    }
+
+    struct Follows(Option<Entity>);
+
+    impl SnapshotComponent for Follows {
+        fn save(&self, cx: &SaveContext) -> Value {
+            self.0.map_or(Value::Null, |entity| cx.entity(entity))
+        }
+
+        fn load(value: &Value, cx: &LoadContext) -> Option<Self> {
+            Some(Self(cx.entity(value)))
+        }
+    }
+
+    struct Link(Obj<Name>);
+
+    impl SnapshotComponent for Link {
+        fn save(&self, cx: &SaveContext) -> Value {
+            cx.obj(self.0)
+        }
+
+        fn load(value: &Value, cx: &LoadContext) -> Option<Self> {
+            cx.obj(value).map(Self)
+        }
+    }
+
+    struct Chain(Option<Obj<Chain>>);
+
+    impl SnapshotComponent for Chain {
+        fn save(&self, cx: &SaveContext) -> Value {
+            self.0.map_or(Value::Null, |obj| cx.obj(obj))
+        }
+
+        fn load(value: &Value, cx: &LoadContext) -> Option<Self> {
+            if value.is_null() {
+                Some(Self(None))
+            } else {
+                cx.obj(value).map(|obj| Self(Some(obj)))
+            }
+        }
+    }
+
+    #[derive(Debug, Eq, PartialEq)]
+    struct Score(u32);
+
+    impl Serialize for Score {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.0.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Score {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            u32::deserialize(deserializer).map(Self)
+        }
+    }
+
+    fn register() {
+        register_snapshot_component::<Name>("snapshot_tests::name");
+        register_snapshot_component::<Follows>("snapshot_tests::follows");
+        register_snapshot_component::<Link>("snapshot_tests::link");
+        register_snapshot_component::<Chain>("snapshot_tests::chain");
+        register_serde_snapshot_component::<Score>("snapshot_tests::score");
+    }
+
+    #[test]
+    fn round_trip_remaps_references() {
+        run_on_main_thread(|| {
+            register();
+
+            let marked = VirtualTag::new();
+            register_snapshot_tag("snapshot_tests::marked", marked);
+
+            // `leader` links to `follower`'s name so restoring it has to wait for `follower`.
+            let follower = OwnedEntity::new().with(Name("follower".to_string()));
+            let leader = OwnedEntity::new()
+                .with(Name("leader".to_string()))
+                .with(Score(7))
+                .with(Link(follower.obj()))
+                .with_tag(marked);
+            follower.insert(Follows(Some(leader.entity())));
+
+            let dangling = OwnedEntity::new();
+            let stray = OwnedEntity::new().with(Follows(Some(dangling.entity())));
+            drop(dangling);
+
+            let (follower_id, leader_id, stray_id) = (
+                follower.entity().id(),
+                leader.entity().id(),
+                stray.entity().id(),
+            );
+
+            // Snapshots survive being serialized through serde.
+            let encoded = serde_json::to_string(&snapshot()).unwrap();
+            drop((follower, leader, stray));
+            flush();
+            assert_eq!(alive_entity_count(), 0);
+
+            let decoded = serde_json::from_str::<WorldSnapshot>(&encoded).unwrap();
+            let world = restore(&decoded).unwrap();
+            flush();
+
+            assert_eq!(world.entities().len(), 3);
+
+            let follower = world.entity(follower_id).unwrap();
+            let leader = world.entity(leader_id).unwrap();
+            let stray = world.entity(stray_id).unwrap();
+
+            assert_eq!(*leader.get::<Name>(), Name("leader".to_string()));
+            assert_eq!(*leader.get::<Score>(), Score(7));
+            assert_eq!(leader.get::<Link>().0.entity(), follower);
+            assert_eq!(*leader.get::<Link>().0.get(), Name("follower".to_string()));
+            assert!(leader.is_tagged_virtual(marked));
+
+            assert_eq!(follower.get::<Follows>().0, Some(leader));
+            assert!(!follower.has::<Score>());
+            assert!(!follower.is_tagged_virtual(marked));
+
+            // References to entities which were dead at snapshot time restore as `None`.
+            assert_eq!(stray.get::<Follows>().0, None);
+
+            // The byte encoding round-trips as well.
+            assert_eq!(
+                decoded,
+                WorldSnapshot::from_bytes(&decoded.to_bytes()).unwrap()
+            );
+
+            drop(world);
+            flush();
+        });
+    }
+
+    #[test]
+    fn obj_cycles_fail_to_restore() {
+        run_on_main_thread(|| {
+            register();
+
+            // Chains which merely point down a line of entities restore fine.
+            let tail = OwnedEntity::new().with(Chain(None));
+            let middle = OwnedEntity::new().with(Chain(Some(tail.obj())));
+            let head = OwnedEntity::new().with(Chain(Some(middle.obj())));
+            let head_id = head.entity().id();
+
+            let image = snapshot();
+            drop((tail, middle, head));
+            flush();
+
+            let world = restore(&image).unwrap();
+            let head = world.entity(head_id).unwrap();
+            let middle = head.get::<Chain>().0.unwrap();
+            assert!(middle.get().0.unwrap().get().0.is_none());
+            drop(world);
+            flush();
+
+            // ...but closing the loop makes them unrestorable.
+            let first = OwnedEntity::new().with(Chain(None));
+            let second = OwnedEntity::new().with(Chain(Some(first.obj())));
+            first.get_mut::<Chain>().0 = Some(second.obj());
+
+            let image = snapshot();
+            drop((first, second));
+            flush();
+
+            let err = restore(&image).unwrap_err();
+            assert!(matches!(err, RestoreError::Cycle { .. }), "{err:?}");
+
+            // Every entity spawned by the failed restore is destroyed again.
+            flush();
+            assert_eq!(alive_entity_count(), 0);
+        });
+    }
+
+    #[test]
+    fn restore_rejects_unknown_names() {
+        run_on_main_thread(|| {
+            let image = WorldSnapshot::from_json(json!({
+                "entities": [{ "id": 1, "tags": [], "components": { "snapshot_tests::nope": 1 } }],
+            }));
+
+            assert_eq!(
+                restore(&image).unwrap_err(),
+                RestoreError::UnknownComponent {
+                    name: "snapshot_tests::nope".to_string(),
+                },
+            );
+            flush();
+            assert_eq!(alive_entity_count(), 0);
+        });
+    }
+}
@@ -259,6 +259,12 @@ pub struct FreeListArena<T> {
     values: Vec<(NonZeroU32, Option<T>)>,
 }
 
+impl<T> FreeListArena<T> {
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.values.iter().filter_map(|(_, value)| value.as_ref())
+    }
+}
+
 impl<T> Arena for FreeListArena<T> {
     type Value = T;
     type AbaPtr = FreeListAbaPtr<T>;
@@ -10,6 +10,8 @@ pub type BlockPtr<T> = AbaPtrFor<BlockArena, Block<T>>;
 
 const HAMMERED_OR_FULL_BLOCK_SLOT: usize = usize::MAX;
 
+const BLOCK_SIZE: usize = u128::BITS as usize;
+
 #[derive(Debug)]
 #[derive_where(Default)]
 pub struct BlockAllocator<T> {
@@ -35,7 +37,7 @@ impl<T> BlockAllocator<T> {
                     block
                 }
                 None => self.blocks.alloc_aba(Block {
-                    value: block_ctor(128),
+                    value: block_ctor(BLOCK_SIZE),
                     non_full_index: HAMMERED_OR_FULL_BLOCK_SLOT,
                     occupied_mask: 0,
                 }),
@@ -65,6 +67,28 @@ impl<T> BlockAllocator<T> {
         }
     }
 
+    pub fn reserve(&mut self, additional: usize, mut block_ctor: impl FnMut(usize) -> T) {
+        let free_slots = self
+            .hammered
+            .iter()
+            .chain(&self.non_full)
+            .map(|block| self.blocks.get_aba(block).occupied_mask.count_zeros() as usize)
+            .sum::<usize>();
+
+        let mut missing = additional.saturating_sub(free_slots);
+
+        while missing > 0 {
+            let block = self.blocks.alloc_aba(Block {
+                value: block_ctor(BLOCK_SIZE),
+                non_full_index: self.non_full.len(),
+                occupied_mask: 0,
+            });
+            self.non_full.push(block);
+
+            missing = missing.saturating_sub(BLOCK_SIZE);
+        }
+    }
+
     pub fn dealloc(&mut self, reservation: BlockReservation<T>, block_dtor: impl FnOnce(T)) {
         let block_data = self.blocks.get_aba_mut(&reservation.block);
 
@@ -103,6 +127,34 @@ impl<T> BlockAllocator<T> {
         }
     }
 
+    pub fn shrink_to_fit(&mut self, mut block_dtor: impl FnMut(T)) {
+        // Release the hammered block if it's empty.
+        if let Some(hammered) = self.hammered {
+            if self.blocks.get_aba(&hammered).occupied_mask == 0 {
+                self.hammered = None;
+                block_dtor(self.blocks.dealloc_aba(&hammered).value);
+            }
+        }
+
+        // Release every empty non-full block.
+        let mut i = 0;
+
+        while let Some(&block) = self.non_full.get(i) {
+            if self.blocks.get_aba(&block).occupied_mask != 0 {
+                i += 1;
+                continue;
+            }
+
+            self.non_full.swap_remove(i);
+
+            if let Some(perturbed) = self.non_full.get(i) {
+                self.blocks.get_aba_mut(perturbed).non_full_index = i;
+            }
+
+            block_dtor(self.blocks.dealloc_aba(&block).value);
+        }
+    }
+
     pub fn block<'a>(&'a self, block: &'a BlockPtr<T>) -> &'a T {
         &self.blocks.get_aba(block).value
     }
@@ -110,6 +162,10 @@ impl<T> BlockAllocator<T> {
     pub fn block_mut<'a>(&'a mut self, block: &'a BlockPtr<T>) -> &'a mut T {
         &mut self.blocks.get_aba_mut(block).value
     }
+
+    pub fn blocks(&self) -> impl Iterator<Item = &T> + '_ {
+        self.blocks.iter().map(|block| &block.value)
+    }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
@@ -1,4 +1,6 @@
 use std::{
+    cmp,
+    collections::BinaryHeap,
     hash::{self, Hasher},
     iter,
 };
@@ -94,6 +96,83 @@ where
     }
 }
 
+pub struct MergeMany<I: Iterator> {
+    iters: Vec<I>,
+    heads: BinaryHeap<MergeManyHead<I::Item>>,
+}
+
+struct MergeManyHead<T> {
+    value: T,
+    index: usize,
+}
+
+impl<T: Ord> Ord for MergeManyHead<T> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        // `BinaryHeap` is a max-heap so we reverse the value ordering. To match `IterMerger`, ties
+        // are broken in favor of the later iterator.
+        other
+            .value
+            .cmp(&self.value)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+impl<T: Ord> PartialOrd for MergeManyHead<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Eq for MergeManyHead<T> {}
+
+impl<T: Ord> PartialEq for MergeManyHead<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl<I> Iterator for MergeMany<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let MergeManyHead { value, index } = self.heads.pop()?;
+
+        if let Some(next) = self.iters[index].next() {
+            self.heads.push(MergeManyHead { value: next, index });
+        }
+
+        Some(value)
+    }
+}
+
+pub fn merge_many<I>(iters: impl IntoIterator<Item = I>) -> MergeMany<I::IntoIter>
+where
+    I: IntoIterator,
+    I::Item: Ord,
+{
+    let mut iters = iters
+        .into_iter()
+        .map(IntoIterator::into_iter)
+        .collect::<Vec<_>>();
+
+    let heads = iters
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(index, iter)| {
+            Some(MergeManyHead {
+                value: iter.next()?,
+                index,
+            })
+        })
+        .collect();
+
+    MergeMany { iters, heads }
+}
+
 pub fn filter_duplicates<T: PartialEq>(
     iter: impl IntoIterator<Item = T>,
 ) -> impl Iterator<Item = T> {
@@ -125,3 +204,91 @@ where
         self.0.find(|v| v != &self.1)
     }
 }
+
+// === Tests === //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A value which only compares by its `key` so that tie-breaks can be observed.
+    #[derive(Debug, Copy, Clone)]
+    struct Keyed {
+        key: u32,
+        source: usize,
+    }
+
+    impl Ord for Keyed {
+        fn cmp(&self, other: &Self) -> cmp::Ordering {
+            self.key.cmp(&other.key)
+        }
+    }
+
+    impl PartialOrd for Keyed {
+        fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Eq for Keyed {}
+
+    impl PartialEq for Keyed {
+        fn eq(&self, other: &Self) -> bool {
+            self.key == other.key
+        }
+    }
+
+    fn keyed(source: usize, keys: &[u32]) -> Vec<Keyed> {
+        keys.iter().map(|&key| Keyed { key, source }).collect()
+    }
+
+    fn assert_sorted_union(sequences: &[&[u32]]) {
+        let merged =
+            merge_many(sequences.iter().map(|seq| seq.iter().copied())).collect::<Vec<_>>();
+
+        let mut expected = sequences.concat();
+        expected.sort_unstable();
+        assert_eq!(merged, expected);
+    }
+
+    #[test]
+    fn merge_many_three_sequences() {
+        assert_sorted_union(&[&[1, 4, 4, 9], &[2, 4, 7], &[0, 1, 9, 9, 12]]);
+    }
+
+    #[test]
+    fn merge_many_five_sequences() {
+        assert_sorted_union(&[&[3, 3, 3], &[], &[0, 2, 3, 8], &[1, 3, 5, 8, 13, 21], &[8]]);
+    }
+
+    #[test]
+    fn merge_many_matches_merge_iters_on_ties() {
+        let cases: [(&[u32], &[u32]); 4] = [
+            (&[1, 2, 2, 5], &[2, 2, 3, 5]),
+            (&[1], &[1, 1]),
+            (&[0, 0, 0], &[]),
+            (&[4, 6], &[4, 6, 6]),
+        ];
+
+        for (a, b) in cases {
+            let (a, b) = (keyed(0, a), keyed(1, b));
+            let many = merge_many([a.clone(), b.clone()])
+                .map(|v| (v.key, v.source))
+                .collect::<Vec<_>>();
+            let pair = merge_iters(a, b)
+                .map(|v| (v.key, v.source))
+                .collect::<Vec<_>>();
+
+            assert_eq!(many, pair);
+        }
+    }
+
+    #[test]
+    fn merge_many_prefers_later_sequences_on_ties() {
+        let sources = merge_many([keyed(0, &[7]), keyed(1, &[7]), keyed(2, &[7])])
+            .map(|v| v.source)
+            .collect::<Vec<_>>();
+
+        assert_eq!(sources, [2, 1, 0]);
+    }
+}
@@ -131,6 +131,16 @@ impl NamedTypeId {
     pub fn raw(self) -> TypeId {
         self.id
     }
+
+    /// Returns the name of the type, which is only recorded in debug builds and only for ids
+    /// created with [`NamedTypeId::of`].
+    pub fn name(self) -> Option<&'static str> {
+        #[cfg(debug_assertions)]
+        return self.name;
+
+        #[cfg(not(debug_assertions))]
+        None
+    }
 }
 
 impl Borrow<TypeId> for NamedTypeId {
@@ -6,3 +6,6 @@ pub mod hash_map;
 pub mod iter;
 pub mod misc;
 pub mod set_map;
+
+#[cfg(test)]
+pub mod testing;
@@ -3,7 +3,7 @@ use std::{fmt, hash, iter, slice};
 use crate::util::iter::{merge_iters, IterFilter, IterMerger};
 
 use super::{
-    arena::{AbaPtrFor, Arena, ArenaFor, ArenaSupporting, FreeingArena, CheckedPtrFor},
+    arena::{AbaPtrFor, Arena, ArenaFor, ArenaSupporting, CheckedPtrFor, FreeingArena, RefFor},
     hash_map::FxHashMap,
     iter::{eq_iter, hash_iter},
 };
@@ -299,6 +299,29 @@ where
     pub fn len(&self) -> usize {
         self.map.len()
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = RefFor<'_, A, SetMapEntry<K, V, A>>> + '_ {
+        self.map.keys().map(|(_, ptr)| self.arena.get_aba(ptr))
+    }
+
+    pub fn stats(&self) -> SetMapStats {
+        let mut stats = SetMapStats::default();
+
+        for entry in self.iter() {
+            stats.node_count += 1;
+            stats.edge_count += entry.extensions.len() + entry.de_extensions.len();
+            stats.max_set_len = stats.max_set_len.max(entry.keys.len());
+        }
+
+        stats
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct SetMapStats {
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub max_set_len: usize,
 }
 
 trait GoofyIterCtorHack<'a, K: 'static> {
@@ -389,3 +412,118 @@ where
         &self.de_extensions
     }
 }
+
+// === Tests === //
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::arena::FreeListArenaKind;
+
+    type TestSetMap = SetMap<u32, u32, FreeListArenaKind>;
+
+    fn sum(keys: &[u32]) -> u32 {
+        keys.iter().sum()
+    }
+
+    fn extend(
+        map: &mut TestSetMap,
+        base: Option<&SetMapAbaPtr<u32, u32, FreeListArenaKind>>,
+        key: u32,
+    ) -> SetMapAbaPtr<u32, u32, FreeListArenaKind> {
+        map.lookup_extension(base, key, sum, |_, _| {})
+    }
+
+    fn sets(map: &TestSetMap) -> Vec<(Vec<u32>, u32)> {
+        let mut sets = map
+            .iter()
+            .map(|entry| (entry.keys().to_vec(), *entry.value()))
+            .collect::<Vec<_>>();
+        sets.sort_unstable();
+        sets
+    }
+
+    #[test]
+    fn iter_visits_every_set_once() {
+        let mut map = TestSetMap::new(0);
+        assert_eq!(sets(&map), [(vec![], 0)]);
+
+        let three = extend(&mut map, None, 3);
+        let one_three = extend(&mut map, Some(&three), 1);
+        let all = extend(&mut map, Some(&one_three), 2);
+        let one_two = map.lookup_de_extension(&all, 3, sum, |_, _| {});
+
+        // Reaching an existing set through another path doesn't create a duplicate.
+        let one = extend(&mut map, None, 1);
+        assert!(extend(&mut map, Some(&one), 3) == one_three);
+        assert!(extend(&mut map, Some(&one_two), 3) == all);
+
+        let expected = [
+            (vec![], 0),
+            (vec![1], 1),
+            (vec![1, 2], 3),
+            (vec![1, 2, 3], 6),
+            (vec![1, 3], 4),
+            (vec![3], 3),
+        ];
+        assert_eq!(sets(&map), expected);
+        assert_eq!(map.iter().count(), map.len());
+
+        map.remove(one_two);
+        assert_eq!(
+            sets(&map),
+            expected
+                .iter()
+                .filter(|(keys, _)| keys != &[1, 2])
+                .cloned()
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn stats_count_nodes_edges_and_set_lengths() {
+        let mut map = TestSetMap::new(0);
+        let empty = SetMapStats {
+            node_count: 1,
+            edge_count: 0,
+            max_set_len: 0,
+        };
+        assert_eq!(map.stats(), empty);
+
+        // {} -> {3} -> {1, 3} -> {1, 2, 3}. Each new set gets a self loop for every key it holds,
+        // an edge back to its base, and its base gets an edge to it.
+        let three = extend(&mut map, None, 3);
+        let one_three = extend(&mut map, Some(&three), 1);
+        let all = extend(&mut map, Some(&one_three), 2);
+        assert_eq!(
+            map.stats(),
+            SetMapStats {
+                node_count: 4,
+                edge_count: 1 + 3 + 4 + 4,
+                max_set_len: 3,
+            },
+        );
+
+        // {1, 2, 3} -> {1, 2} adds a set with two self loops and an edge in each direction.
+        let one_two = map.lookup_de_extension(&all, 3, sum, |_, _| {});
+        assert_eq!(map.stats().edge_count, 12 + 4);
+
+        // Finding an existing set through a new path only caches the edges between them.
+        let one = extend(&mut map, None, 1);
+        assert_eq!(map.stats().edge_count, 16 + 3);
+        extend(&mut map, Some(&one), 3);
+        assert_eq!(map.stats().edge_count, 19 + 2);
+        assert_eq!(map.stats().node_count, 6);
+
+        // Removing a set drops its own edges along with the ones pointing at it.
+        map.remove(one_two);
+        assert_eq!(
+            map.stats(),
+            SetMapStats {
+                node_count: 5,
+                edge_count: 21 - 4,
+                max_set_len: 3,
+            },
+        );
+    }
+}
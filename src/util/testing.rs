@@ -0,0 +1,132 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Mutex, OnceLock},
+    thread,
+};
+
+use super::misc::unpoison;
+
+// === Main Thread Runner === //
+
+type MainThreadJob = Box<dyn FnOnce() + Send>;
+
+static MAIN_THREAD: OnceLock<Mutex<mpsc::Sender<MainThreadJob>>> = OnceLock::new();
+
+/// Runs `f` on a single long-lived thread shared by every test.
+///
+/// The first thread to touch the database becomes its main thread for the rest of the process so
+/// tests which use it cannot run on the harness' own worker threads. Running every such test on
+/// the same thread also serializes them, which is necessary since they share the global database.
+/// Panics raised by `f` are resumed on the calling thread so `#[should_panic]` still works.
+pub fn run_on_main_thread<R: Send + 'static>(f: impl FnOnce() -> R + Send + 'static) -> R {
+    let sender = MAIN_THREAD.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<MainThreadJob>();
+
+        thread::Builder::new()
+            .name("bort test main thread".to_string())
+            .spawn(move || {
+                for job in receiver {
+                    job();
+                }
+            })
+            .unwrap();
+
+        Mutex::new(sender)
+    });
+
+    let (result_sender, result_receiver) = mpsc::channel();
+
+    unpoison(sender.lock())
+        .send(Box::new(move || {
+            let _ = result_sender.send(panic::catch_unwind(AssertUnwindSafe(f)));
+        }))
+        .unwrap();
+
+    match result_receiver.recv().unwrap() {
+        Ok(result) => result,
+        Err(payload) => panic::resume_unwind(payload),
+    }
+}
+
+// === Span Recorder === //
+
+/// A span created while running [`record_spans`], along with every field recorded on it.
+#[cfg(feature = "tracing")]
+#[derive(Debug, Clone)]
+pub struct RecordedSpan {
+    pub name: &'static str,
+    pub fields: Vec<(&'static str, String)>,
+}
+
+#[cfg(feature = "tracing")]
+impl RecordedSpan {
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .rev()
+            .find(|(field, _)| *field == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Runs `f` with a subscriber installed on the current thread which records every span created in
+/// the meantime. Field values are recorded using their `Debug` representation.
+#[cfg(feature = "tracing")]
+pub fn record_spans<R>(f: impl FnOnce() -> R) -> (R, Vec<RecordedSpan>) {
+    use std::{fmt, sync::Arc};
+
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata, Subscriber,
+    };
+
+    use super::misc::unpoison;
+
+    struct Recorder(Arc<Mutex<Vec<RecordedSpan>>>);
+
+    struct FieldRecorder<'a>(&'a mut Vec<(&'static str, String)>);
+
+    impl Visit for FieldRecorder<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.push((field.name(), format!("{value:?}")));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+            let mut spans = unpoison(self.0.lock());
+            let mut span = RecordedSpan {
+                name: attrs.metadata().name(),
+                fields: Vec::new(),
+            };
+            attrs.record(&mut FieldRecorder(&mut span.fields));
+            spans.push(span);
+
+            span::Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, id: &span::Id, values: &span::Record<'_>) {
+            let mut spans = unpoison(self.0.lock());
+            let span = &mut spans[id.into_u64() as usize - 1];
+            values.record(&mut FieldRecorder(&mut span.fields));
+        }
+
+        fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+        fn event(&self, _event: &Event<'_>) {}
+
+        fn enter(&self, _span: &span::Id) {}
+
+        fn exit(&self, _span: &span::Id) {}
+    }
+
+    let spans = Arc::new(Mutex::new(Vec::new()));
+    let res = tracing::subscriber::with_default(Recorder(spans.clone()), f);
+    let spans = unpoison(spans.lock()).clone();
+
+    (res, spans)
+}
@@ -0,0 +1,11 @@
+use bort::{query, Tag};
+
+fn main() {
+    let tag = Tag::<u32>::new();
+
+    query! {
+        for (mut first in tag, ref second in tag) {
+            *first += *second;
+        }
+    }
+}